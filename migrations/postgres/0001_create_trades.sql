@@ -1,13 +1,5 @@
--- CREATE TABLE IF NOT EXISTS prices (
---     id SERIAL PRIMARY KEY,
---     symbol VARCHAR(20) NOT NULL,
---     open FLOAT NOT NULL,
---     high FLOAT NOT NULL,
---     low FLOAT NOT NULL,
---     close FLOAT NOT NULL,
---     volume FLOAT NOT NULL,
---     timestamp BIGINT NOT NULL
--- );
+-- migrations also run as docker init scripts on fresh volumes (before the bot
+-- records them), so every migration has to be safe to apply twice
 
 CREATE TABLE IF NOT EXISTS trades (
     id SERIAL PRIMARY KEY,
@@ -26,6 +18,5 @@ CREATE TABLE IF NOT EXISTS trades (
     )
 );
 
--- CREATE INDEX IF NOT EXISTS idx_prices_coin_timestamp ON prices(symbol, timestamp);
 CREATE INDEX IF NOT EXISTS idx_trades_symbol_status ON trades(symbol, status);
 CREATE INDEX IF NOT EXISTS idx_trades_entry_time ON trades(entry_time);
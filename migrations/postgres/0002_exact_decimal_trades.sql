@@ -0,0 +1,7 @@
+-- money and quantities are exact decimals, pnl sums reconcile with fills
+ALTER TABLE trades
+    ALTER COLUMN entry_price TYPE NUMERIC,
+    ALTER COLUMN exit_price TYPE NUMERIC,
+    ALTER COLUMN trade_size TYPE NUMERIC,
+    ALTER COLUMN position_size TYPE NUMERIC,
+    ALTER COLUMN pnl TYPE NUMERIC;
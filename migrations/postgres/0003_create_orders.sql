@@ -0,0 +1,23 @@
+-- order lifecycle: NEW -> PARTIALLY_FILLED -> FILLED / CANCELED / EXPIRED,
+-- fills of these orders are booked into `trades`
+CREATE TABLE IF NOT EXISTS orders (
+    id SERIAL PRIMARY KEY,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL CHECK (side IN ('BUY', 'SELL')),
+    order_type VARCHAR(12) NOT NULL CHECK (order_type IN ('MARKET', 'LIMIT', 'STOP_MARKET', 'STOP_LIMIT')),
+    time_in_force VARCHAR(3) NOT NULL CHECK (time_in_force IN ('GTC', 'IOC', 'FOK')),
+    quantity NUMERIC NOT NULL CHECK (quantity > 0),
+    price NUMERIC,
+    stop_price NUMERIC,
+    filled_quantity NUMERIC NOT NULL DEFAULT 0,
+    avg_fill_price NUMERIC,
+    triggered BOOLEAN NOT NULL DEFAULT FALSE,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('NEW', 'PARTIALLY_FILLED', 'FILLED', 'CANCELED', 'EXPIRED')),
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    CHECK (filled_quantity >= 0 AND filled_quantity <= quantity),
+    CHECK (order_type NOT IN ('LIMIT', 'STOP_LIMIT') OR price IS NOT NULL),
+    CHECK (order_type NOT IN ('STOP_MARKET', 'STOP_LIMIT') OR stop_price IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_symbol_status ON orders(symbol, status);
@@ -0,0 +1,6 @@
+-- orders placed on Binance: exchange side id, and orders refused by exchange
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS exchange_order_id BIGINT;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check
+    CHECK (status IN ('NEW', 'PARTIALLY_FILLED', 'FILLED', 'CANCELED', 'EXPIRED', 'REJECTED'));
@@ -0,0 +1,24 @@
+-- market candles seen by the bot, one row per candle
+CREATE TABLE IF NOT EXISTS prices (
+    symbol VARCHAR(20) NOT NULL,
+    timeframe VARCHAR(4) NOT NULL,
+    open_time BIGINT NOT NULL, -- unix millis
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (symbol, timeframe, open_time)
+);
+
+-- one row per run of the trading loop
+CREATE TABLE IF NOT EXISTS sessions (
+    id SERIAL PRIMARY KEY,
+    symbol VARCHAR(20) NOT NULL,
+    mode VARCHAR(8) NOT NULL,
+    initial_balance NUMERIC NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_symbol ON sessions(symbol, started_at);
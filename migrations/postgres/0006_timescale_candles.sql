@@ -0,0 +1,74 @@
+-- optional TimescaleDB mode (docker-compose.timescale.yml): prices become a hypertable,
+-- compressed after 30 days, with 1h/4h/1d candles aggregated from 1m ones.
+-- plain PostgreSQL has no timescaledb extension, there this migration does nothing
+DO $$
+DECLARE
+    bucket RECORD;
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'timescaledb') THEN
+        RAISE NOTICE 'timescaledb is not available, prices stays a plain table';
+        RETURN;
+    END IF;
+
+    CREATE EXTENSION IF NOT EXISTS timescaledb;
+
+    -- open_time is unix millis, one chunk per 7 days
+    PERFORM create_hypertable(
+        'prices', 'open_time',
+        chunk_time_interval => 604800000::BIGINT,
+        if_not_exists => TRUE,
+        migrate_data => TRUE
+    );
+
+    -- integer time column needs "now" in the same unit for policies
+    CREATE OR REPLACE FUNCTION prices_now_ms() RETURNS BIGINT
+        LANGUAGE SQL STABLE AS
+        $now$ SELECT (extract(epoch FROM now()) * 1000)::BIGINT $now$;
+    PERFORM set_integer_now_func('prices', 'prices_now_ms', replace_if_exists => TRUE);
+
+    -- settings cannot change once chunks are compressed, so they are set only once
+    IF NOT (SELECT compression_enabled FROM timescaledb_information.hypertables
+            WHERE hypertable_name = 'prices') THEN
+        ALTER TABLE prices SET (
+            timescaledb.compress,
+            timescaledb.compress_segmentby = 'symbol, timeframe',
+            timescaledb.compress_orderby = 'open_time'
+        );
+    END IF;
+    PERFORM add_compression_policy('prices', 2592000000::BIGINT, if_not_exists => TRUE);
+
+    -- prices_<timeframe>: candles of higher timeframe built from stored 1m candles,
+    -- buckets are aligned to epoch like Binance klines
+    FOR bucket IN
+        SELECT * FROM (VALUES ('1h', 3600000::BIGINT), ('4h', 14400000), ('1d', 86400000))
+            AS b(timeframe, ms)
+    LOOP
+        EXECUTE format(
+            $view$
+            CREATE MATERIALIZED VIEW IF NOT EXISTS %I
+            WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+            SELECT symbol,
+                   time_bucket(%s::BIGINT, open_time) AS open_time,
+                   first(open, open_time) AS open,
+                   max(high) AS high,
+                   min(low) AS low,
+                   last(close, open_time) AS close,
+                   sum(volume) AS volume
+            FROM prices
+            WHERE timeframe = '1m'
+            GROUP BY symbol, time_bucket(%s::BIGINT, open_time)
+            WITH NO DATA
+            $view$,
+            'prices_' || bucket.timeframe, bucket.ms, bucket.ms
+        );
+        -- last 3 buckets are refreshed, newer candles are aggregated on read
+        PERFORM add_continuous_aggregate_policy(
+            ('prices_' || bucket.timeframe)::regclass,
+            start_offset => bucket.ms * 3,
+            end_offset => bucket.ms,
+            schedule_interval => make_interval(secs => bucket.ms / 1000),
+            if_not_exists => TRUE
+        );
+    END LOOP;
+END
+$$;
@@ -0,0 +1,13 @@
+-- mark-to-market equity of a session at the close of every bar
+CREATE TABLE IF NOT EXISTS equity_snapshots (
+    id SERIAL PRIMARY KEY,
+    session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    symbol VARCHAR(20) NOT NULL,
+    time TIMESTAMPTZ NOT NULL,
+    balance NUMERIC NOT NULL,
+    unrealized_pnl NUMERIC NOT NULL,
+    equity NUMERIC NOT NULL,
+    exposure NUMERIC NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_equity_snapshots_session ON equity_snapshots(session_id, time);
@@ -0,0 +1,3 @@
+-- mark price of every snapshot, buy & hold benchmark of a session is derived from it.
+-- older snapshots have no price
+ALTER TABLE equity_snapshots ADD COLUMN IF NOT EXISTS price NUMERIC;
@@ -0,0 +1,4 @@
+-- max adverse / favourable excursion of closed trades, quote amount.
+-- trades closed before this migration are measured on the next run
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS mae NUMERIC;
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS mfe NUMERIC;
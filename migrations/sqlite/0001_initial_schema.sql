@@ -0,0 +1,62 @@
+-- same tables as postgres migrations, decimals are stored as TEXT to keep them
+-- exact (SQLite has no NUMERIC with arbitrary precision), timestamps as TEXT
+
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    entry_price TEXT NOT NULL,
+    exit_price TEXT,
+    trade_size TEXT NOT NULL,
+    position_size TEXT NOT NULL,
+    pnl TEXT,
+    entry_time TEXT NOT NULL,
+    exit_time TEXT,
+    status TEXT NOT NULL CHECK (status IN ('OPEN', 'CLOSED')),
+    CHECK (
+        (status = 'OPEN' AND exit_price IS NULL AND exit_time IS NULL) OR
+        (status = 'CLOSED' AND exit_price IS NOT NULL AND exit_time IS NOT NULL)
+    )
+);
+
+CREATE TABLE IF NOT EXISTS orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('BUY', 'SELL')),
+    order_type TEXT NOT NULL CHECK (order_type IN ('MARKET', 'LIMIT', 'STOP_MARKET', 'STOP_LIMIT')),
+    time_in_force TEXT NOT NULL CHECK (time_in_force IN ('GTC', 'IOC', 'FOK')),
+    quantity TEXT NOT NULL,
+    price TEXT,
+    stop_price TEXT,
+    filled_quantity TEXT NOT NULL DEFAULT '0',
+    avg_fill_price TEXT,
+    triggered BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL CHECK (status IN ('NEW', 'PARTIALLY_FILLED', 'FILLED', 'CANCELED', 'EXPIRED', 'REJECTED')),
+    exchange_order_id INTEGER,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS prices (
+    symbol TEXT NOT NULL,
+    timeframe TEXT NOT NULL,
+    open_time INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume REAL NOT NULL,
+    PRIMARY KEY (symbol, timeframe, open_time)
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    initial_balance TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_symbol_status ON trades(symbol, status);
+CREATE INDEX IF NOT EXISTS idx_orders_symbol_status ON orders(symbol, status);
+CREATE INDEX IF NOT EXISTS idx_sessions_symbol ON sessions(symbol, started_at);
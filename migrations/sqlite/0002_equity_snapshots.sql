@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS equity_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    symbol TEXT NOT NULL,
+    time TEXT NOT NULL,
+    balance TEXT NOT NULL,
+    unrealized_pnl TEXT NOT NULL,
+    equity TEXT NOT NULL,
+    exposure TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_equity_snapshots_session ON equity_snapshots(session_id, time);
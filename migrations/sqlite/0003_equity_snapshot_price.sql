@@ -0,0 +1,2 @@
+-- mark price of every snapshot, buy & hold benchmark of a session is derived from it
+ALTER TABLE equity_snapshots ADD COLUMN price TEXT;
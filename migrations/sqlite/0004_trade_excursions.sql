@@ -0,0 +1,3 @@
+-- max adverse / favourable excursion of closed trades, quote amount
+ALTER TABLE trades ADD COLUMN mae TEXT;
+ALTER TABLE trades ADD COLUMN mfe TEXT;
@@ -1,2 +1,2 @@
 pub mod sma_crossover_backtest;
-pub use sma_crossover_backtest::run_backtest;
+pub use sma_crossover_backtest::{backtest_html_report, run_backtest};
@@ -1,21 +1,144 @@
 use polars::prelude::*;
-use std::ops::{Div, Sub, Mul};
-use crate::utils::data_io::read_parquet;
-use crate::utils::config::Settings;
+use std::ops::{Div, Mul};
+use crate::utils::data_io::{read_market_data, resample_ohlcv};
+use crate::utils::data_quality::validate_frame;
+use crate::utils::config::{timeframe_duration, Backtest, Settings};
+use crate::utils::export::Export;
+use crate::utils::html_report::{ChartTrade, HtmlReport};
+use crate::utils::metrics::{
+    bars_per_year_ms, metrics_frame, performance_metrics, trade_pnls, trade_spans, PerformanceMetrics,
+};
 
-pub fn run_backtest() -> PolarsResult<()> {
+// bars of a backtest with equity of buy & hold (benchmark) and of the strategy
+pub struct BacktestRun {
+    // date, close, SMAs, position and balances of every bar
+    pub df: DataFrame,
+    pub dates: Vec<i64>,
+    // bar t is in market when position was open after t-1
+    pub held: Vec<bool>,
+    pub benchmark_equity: Vec<f64>,
+    pub strategy_equity: Vec<f64>,
+    pub strategy_trades: Vec<f64>,
+    pub benchmark: PerformanceMetrics,
+    pub strategy: PerformanceMetrics,
+}
+
+// `export` also writes metrics, strategy trades and the whole bar frame
+// (`backtest_report`, `backtest_trades`, `backtest_equity`)
+pub fn run_backtest(export: Option<&Export>) -> PolarsResult<()> {
 
     // config load
     let backtest = Settings::load().expect("Failed to load settings").backtest;
 
     // config constants
-    let parquet_path = backtest.parquet_path;
     let test_balance = backtest.test_balance;
     let fast_period = backtest.fast_period;
     let slow_period = backtest.slow_period;
 
-    println!("Reading data from: {}", parquet_path);
-    let df = read_parquet(&parquet_path)?
+    let BacktestRun {
+        df,
+        dates,
+        held,
+        strategy_equity,
+        strategy_trades,
+        benchmark,
+        strategy,
+        ..
+    } = simulate(&backtest)?;
+
+    let benchmark_balance = df
+        .column("benchmark_balance")?
+        .f64()?
+        .tail(Some(1))
+        .get(0)
+        .expect("[ERROR] No values in `benchmark_balance`");
+    
+    let strategy_returns = df
+        .column("strategy_balance")?
+        .f64()?
+        .tail(Some(1))
+        .get(0)
+        .expect("[ERROR] No values in `strategy_balance`");
+
+    println!("-----------------------------------------------------------");
+    println!("SMA Crossover Strategy Backtest Analysis");
+    println!("-----------------------------------------------------------");
+    println!("[PARAMETER] Starting Balance: {:.2}$", test_balance);
+    if let Some(timeframe) = &backtest.timeframe {
+        println!("[PARAMETER] Timeframe: {}", timeframe);
+    }
+    println!("[PARAMETER] Fast SMA: {}", fast_period);
+    println!("[PARAMETER] Slow SMA: {}", slow_period);
+    println!("-----------------------------------------------------------");
+    println!("[BENCHMARK] Total Return: {:.0}$", benchmark_balance);
+    println!("[BENCHMARK] PERFORMANCE: {:.2}%", (benchmark_balance / test_balance - 1.0) * 100.0);
+    print_metrics("BENCHMARK", &benchmark);
+    println!("-----------------------------------------------------------");
+    println!("[STRATEGY] Total Return: {:.0}$", strategy_returns);
+    println!("[STRATEGY] PERFORMANCE: {:.2}%", (strategy_returns / test_balance - 1.0) * 100.0);
+    print_metrics("STRATEGY", &strategy);
+    println!("[STRATEGY] Timing in Market: {:.2}%", strategy.exposure * 100.0);
+    println!("[STRATEGY] Trades: {}", strategy_trades.len());
+    println!("[STRATEGY] Expectancy: {:.2}$", strategy.expectancy);
+    println!("[STRATEGY] Max Consecutive Losses: {}", strategy.max_consecutive_losses);
+    println!("-----------------------------------------------------------");
+
+
+    if let Some(export) = export {
+        let mut report = metrics_frame(&[("benchmark", &benchmark), ("strategy", &strategy)])?;
+        report.with_column(Series::new("final_balance".into(), [benchmark_balance, strategy_returns]))?;
+
+        let spans = trade_spans(&held);
+        let trades = df!(
+            "entry_date" => spans.iter().map(|(start, _)| dates[*start]).collect::<Vec<_>>(),
+            "exit_date" => spans.iter().map(|(_, end)| dates[*end]).collect::<Vec<_>>(),
+            "entry_balance" => spans.iter().map(|(start, _)| strategy_equity[*start]).collect::<Vec<_>>(),
+            "exit_balance" => spans.iter().map(|(_, end)| strategy_equity[*end]).collect::<Vec<_>>(),
+            "pnl" => strategy_trades.clone(),
+        )?
+        .lazy()
+        .with_columns([
+            col("entry_date").cast(DataType::Datetime(TimeUnit::Milliseconds, None)),
+            col("exit_date").cast(DataType::Datetime(TimeUnit::Milliseconds, None)),
+        ])
+        .collect()?;
+
+        let tables = vec![
+            ("backtest_report".to_string(), report),
+            ("backtest_trades".to_string(), trades),
+            ("backtest_equity".to_string(), df),
+        ];
+        for path in export.write_all(tables)? {
+            println!("[EXPORT] {}", path.display());
+        }
+    }
+
+    // println!("{:?}", df.head(Some(10)));
+    // println!("{:?}", df.tail(Some(10)));
+    Ok(())
+}
+
+// runs the strategy over market data of `backtest` config
+pub fn simulate(backtest: &Backtest) -> PolarsResult<BacktestRun> {
+    let test_balance = backtest.test_balance;
+
+    println!("Reading data from: {}", backtest.data_path);
+    // database candles are already in requested timeframe when possible
+    let source = read_market_data(
+        &backtest.data_path,
+        &backtest.columns,
+        backtest.symbol.as_deref(),
+        backtest.timeframe.as_deref().unwrap_or("1m"),
+    )?;
+    let (mut market_data, quality) = validate_frame(source, backtest.data_quality)?;
+    if !quality.is_clean() {
+        println!("[DATA QUALITY] {} -> {:?}", quality, backtest.data_quality);
+    }
+    if let Some(timeframe) = &backtest.timeframe {
+        market_data = resample_ohlcv(market_data, timeframe)?;
+    }
+
+    let df = market_data
         .lazy()
         .select(
             [col("date"), col("close")]
@@ -46,33 +169,10 @@ pub fn run_backtest() -> PolarsResult<()> {
             .alias("benchmark_balance"),
         ])
         .with_columns([
-            (
-                col("benchmark_balance")
-                .cum_max(false)
-            )
-            .alias("benchmark_peak"),
-            (
-                col("benchmark_balance")
-                .sub(col("benchmark_balance")
-                .cum_max(false))
-            )
-            .alias("benchmark_drawdown"),
-            // (
-            //     (
-            //         col("benchmark_balance") - 
-            //         col("benchmark_balance")
-            //         .cum_max(false)
-            //     ) / 
-            //     (
-            //         col("benchmark_balance")
-            //         .cum_max(false)
-            //     ) * lit(100.0)
-            // )
-            //     .alias("benchmark_drawdown_pct"),
             (
                 col("close")
                 .rolling_mean(RollingOptionsFixedWindow {
-                    window_size: fast_period,
+                    window_size: backtest.fast_period,
                     min_periods: 1,
                     weights: None,
                     center: false,
@@ -83,7 +183,7 @@ pub fn run_backtest() -> PolarsResult<()> {
             (
                 col("close")
                 .rolling_mean(RollingOptionsFixedWindow {
-                    window_size: slow_period,
+                    window_size: backtest.slow_period,
                     min_periods: 1,
                     weights: None,
                     center: false,
@@ -117,84 +217,145 @@ pub fn run_backtest() -> PolarsResult<()> {
             )
             .alias("strategy_balance"),
         ])
-        .with_columns([
-            (
-                col("strategy_balance")
-                .cum_max(false)
-            )
-            .alias("strategy_peak"),
-        ])
-        .with_columns([
-            (
-                col("strategy_balance")
-                .sub(col("strategy_peak"))
-            )
-            .alias("strategy_drawdown"),
-        ]) 
         .collect()?;
 
-    let benchmark_balance = df
-        .column("benchmark_balance")?
-        .f64()?
-        .tail(Some(1))
-        .get(0)
-        .expect("[ERROR] No values in `benchmark_balance`");
-    
-    let benchmark_drawdown = df
-        .column("benchmark_drawdown")?
-        .f64()?
-        .div(
-            df.column("benchmark_peak")?
-                .f64()?
-        )
-        .min()
-        .expect("[ERROR] No minimum value in `benchmark_drawdown`")
-        * 100.0;
+    // bar length for annualization: configured timeframe or step of the source data
+    let dates: Vec<i64> = df
+        .column("date")?
+        .cast(&DataType::Int64)?
+        .i64()?
+        .into_no_null_iter()
+        .collect();
+    let bar_ms = match &backtest.timeframe {
+        Some(timeframe) => timeframe_duration(timeframe)
+            .map_err(|e| polars_err!(ComputeError: "{}", e))?
+            .num_milliseconds(),
+        None => dates
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|d| *d > 0)
+            .min()
+            .unwrap_or(60_000),
+    };
+    let bars_per_year = bars_per_year_ms(bar_ms);
 
-    let strategy_returns = df
-        .column("strategy_balance")?
-        .f64()?
-        .tail(Some(1))
-        .get(0)
-        .expect("[ERROR] No values in `strategy_balance`");
-
-    let timing_in_market = df.column("in_position")?
+    // balances start at test balance, bar t is in market when position was open after t-1
+    let balances = |name: &str| -> PolarsResult<Vec<f64>> {
+        Ok(df
+            .column(name)?
+            .f64()?
+            .into_iter()
+            .map(|v| v.unwrap_or(test_balance))
+            .collect())
+    };
+    let in_position: Vec<bool> = df
+        .column("in_position")?
         .bool()?
-        .sum()
-        .expect("[ERROR] Failed to sum `in_position`") as f64
-        / df.height() as f64
-        * 100.0;
+        .into_iter()
+        .map(|v| v.unwrap_or(false))
+        .collect();
+    let held: Vec<bool> = std::iter::once(false)
+        .chain(in_position.iter().copied())
+        .take(in_position.len())
+        .collect();
 
-    let strategy_drawdown_series = df
-        .column("strategy_drawdown")?
-        .f64()?
-        .div(df.column("strategy_peak")?.f64()?);
+    let benchmark_equity = balances("benchmark_balance")?;
+    let benchmark = performance_metrics(
+        &benchmark_equity,
+        &vec![true; benchmark_equity.len()],
+        &[],
+        bars_per_year,
+    );
+    let strategy_equity = balances("strategy_balance")?;
+    let strategy_trades = trade_pnls(&strategy_equity, &held);
+    let strategy = performance_metrics(&strategy_equity, &held, &strategy_trades, bars_per_year);
 
-    let strategy_drawdown = strategy_drawdown_series
-        .min()
-        .expect("[ERROR] No minimum value in `strategy_drawdown`") * 100.0;
+    Ok(BacktestRun {
+        df,
+        dates,
+        held,
+        benchmark_equity,
+        strategy_equity,
+        strategy_trades,
+        benchmark,
+        strategy,
+    })
+}
 
-    println!("-----------------------------------------------------------");
-    println!("SMA Crossover Strategy Backtest Analysis");
-    println!("-----------------------------------------------------------");
-    println!("[PARAMETER] Starting Balance: {:.2}$", test_balance);
-    println!("[PARAMETER] Fast SMA: {}", fast_period);
-    println!("[PARAMETER] Slow SMA: {}", slow_period);
-    println!("-----------------------------------------------------------");
-    println!("[BENCHMARK] Total Return: {:.0}$", benchmark_balance);
-    println!("[BENCHMARK] PERFORMANCE: {:.2}%", (benchmark_balance / test_balance - 1.0) * 100.0);
-    println!("[BENCHMARK] Drawdown: {:.2}%", benchmark_drawdown);
-    println!("-----------------------------------------------------------");
-    println!("[STRATEGY] Total Return: {:.0}$", strategy_returns);
-    println!("[STRATEGY] PERFORMANCE: {:.2}%", (strategy_returns / test_balance - 1.0) * 100.0);
-    println!("[STRATEGY] Drawdown: {:.2}%", strategy_drawdown);
-    println!("[STRATEGY] Timing in Market: {:.2}%", timing_in_market);
-    println!("-----------------------------------------------------------");
+// `bot report --html --backtest`: backtest of the config drawn bar by bar,
+// trades enter and exit at the close of the crossover bar
+pub fn backtest_html_report() -> PolarsResult<HtmlReport> {
+    let backtest = Settings::load().expect("Failed to load settings").backtest;
+    let run = simulate(&backtest)?;
 
+    let close: Vec<f64> = run.df.column("close")?.f64()?.into_iter().map(|v| v.unwrap_or_default()).collect();
+    let sma = |name: &str| -> PolarsResult<Vec<Option<f64>>> {
+        Ok(run.df.column(name)?.f64()?.into_iter().collect())
+    };
+    let still_open = run
+        .df
+        .column("in_position")?
+        .bool()?
+        .tail(Some(1))
+        .get(0)
+        .unwrap_or(false);
+    let last = run.dates.len().saturating_sub(1);
 
-    // println!("{:?}", df.head(Some(10)));
-    // println!("{:?}", df.tail(Some(10)));
-    Ok(())
+    let trades: Vec<ChartTrade> = trade_spans(&run.held)
+        .into_iter()
+        .zip(&run.strategy_trades)
+        .map(|((start, end), pnl)| {
+            let closed = !(end == last && still_open);
+            ChartTrade {
+                entry_time: run.dates[start],
+                entry_price: close[start],
+                exit_time: closed.then(|| run.dates[end]),
+                exit_price: closed.then(|| close[end]),
+                pnl: *pnl,
+            }
+        })
+        .collect();
+
+    let source = match &backtest.symbol {
+        Some(symbol) => format!("{} ({})", backtest.data_path, symbol),
+        None => backtest.data_path.clone(),
+    };
+    let summary = vec![
+        ("Source".to_string(), format!("backtest of {}", source)),
+        ("Timeframe".to_string(), backtest.timeframe.clone().unwrap_or_else(|| "source data".to_string())),
+        ("Fast / Slow SMA".to_string(), format!("{} / {}", backtest.fast_period, backtest.slow_period)),
+        ("Bars".to_string(), run.dates.len().to_string()),
+        ("Starting Balance".to_string(), format!("{:.2}", backtest.test_balance)),
+        ("Final Balance".to_string(), format!("{:.2}", run.strategy_equity.last().copied().unwrap_or(backtest.test_balance))),
+        ("Buy & Hold Balance".to_string(), format!("{:.2}", run.benchmark_equity.last().copied().unwrap_or(backtest.test_balance))),
+        ("Trades".to_string(), trades.len().to_string()),
+    ];
+
+    Ok(HtmlReport {
+        title: "SMA Crossover Backtest Report".to_string(),
+        summary,
+        fast_sma: sma("fast_sma")?,
+        slow_sma: sma("slow_sma")?,
+        close,
+        times: run.dates,
+        equity: run.strategy_equity,
+        benchmark: run.benchmark_equity,
+        trades,
+        metrics: vec![
+            ("strategy".to_string(), run.strategy),
+            ("buy & hold".to_string(), run.benchmark),
+        ],
+    })
+}
+
+fn print_metrics(tag: &str, m: &PerformanceMetrics) {
+    println!("[{}] Drawdown: {:.2}%", tag, 0.0 - m.max_drawdown * 100.0);
+    println!("[{}] Max Drawdown: {}", tag, m.drawdown_text());
+    println!("[{}] Annualized Return: {:.2}%", tag, m.annualized_return * 100.0);
+    println!("[{}] Annualized Volatility: {:.2}%", tag, m.annualized_volatility * 100.0);
+    println!("[{}] Sharpe Ratio: {:.2}", tag, m.sharpe_ratio);
+    println!("[{}] Sortino Ratio: {:.2}", tag, m.sortino_ratio);
+    println!("[{}] Calmar Ratio: {:.2}", tag, m.calmar_ratio);
 }
 
 #[cfg(test)]
@@ -203,7 +364,7 @@ mod tests {
 
     #[test]
     fn test_run_backtest_execution() {
-        let result = run_backtest();
+        let result = run_backtest(None);
         if let Err(e) = result {
             panic!("Backtest failed with error: {:?}", e);
         }
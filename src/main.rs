@@ -2,9 +2,17 @@ pub mod backtest;
 pub mod trading_simulation;
 pub mod utils;
 
-use backtest::run_backtest;
-use clap::{Parser, Subcommand};
+#[cfg(test)]
+pub mod test_support;
+
+use backtest::{backtest_html_report, run_backtest};
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use trading_simulation::database::connection::migrate_database;
+use trading_simulation::report_query::{Period, ReportQuery, parse_time, run_report};
 use trading_simulation::run_trading_simulation;
+use utils::export::{Export, OutputFormat};
 
 #[derive(Parser)]
 #[command(name = "bot")]
@@ -14,10 +22,78 @@ struct Cli {
     command: Commands,
 }
 
+#[derive(Args)]
+struct OutputArgs {
+    /// Also write report, trade ledger and equity curve in given format
+    #[arg(long, value_enum)]
+    output: Option<OutputFormat>,
+    /// Directory for `--output` files
+    #[arg(long, value_name = "DIR", default_value = "reports", requires = "output")]
+    output_dir: PathBuf,
+}
+
+impl OutputArgs {
+    fn export(self) -> Option<Export> {
+        self.output.map(|format| Export {
+            format,
+            dir: self.output_dir,
+        })
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    Backtest,
-    Trade,
+    Backtest {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    Trade {
+        /// Replay recorded candles (parquet, csv, Binance zip or `database`) instead of live market feed
+        #[arg(long, value_name = "PATH")]
+        replay: Option<String>,
+        /// Replay speed as multiple of real time, 0 replays as fast as possible
+        #[arg(long, default_value_t = 0.0, requires = "replay")]
+        speed: f64,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Report trades stored in the database for a symbol, session or date range
+    Report {
+        /// Symbol to report, the session's or the configured one by default
+        #[arg(long)]
+        symbol: Option<String>,
+        /// Only the time range of this trading session
+        #[arg(long, value_name = "ID")]
+        session: Option<i64>,
+        /// Start of the range (inclusive), `YYYY-MM-DD` or RFC 3339 time
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+        /// End of the range (exclusive), `YYYY-MM-DD` or RFC 3339 time
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// Also show results per day, week or month
+        #[arg(long, value_enum)]
+        group_by: Option<Period>,
+        /// Render a self-contained HTML page with charts
+        #[arg(long, value_name = "PATH")]
+        html: Option<PathBuf>,
+        /// Render the HTML page for a backtest of the `[backtest]` config instead
+        #[arg(long, requires = "html", conflicts_with_all = ["symbol", "session", "from", "to", "group_by", "output"])]
+        backtest: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Apply pending schema migrations (also done on every `trade` start)
+    Migrate,
 }
 
 #[tokio::main]
@@ -25,14 +101,55 @@ async fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Backtest => {
-            if let Err(e) = run_backtest() {
+        Commands::Backtest { output } => {
+            if let Err(e) = run_backtest(output.export().as_ref()) {
                 eprintln!("Backtest failed: {e}");
             }
         }
-        Commands::Trade => {
-            if let Err(e) = run_trading_simulation().await {
+        Commands::Trade {
+            replay,
+            speed,
+            output,
+        } => {
+            if let Err(e) = run_trading_simulation(replay, speed, output.export()).await {
                 eprintln!("Trading simulation failed: {e}");
+                // supervisors restart on non-zero exit only
+                std::process::exit(1);
+            }
+        }
+        Commands::Report {
+            html: Some(html),
+            backtest: true,
+            ..
+        } => match backtest_html_report().and_then(|report| Ok(report.write(&html)?)) {
+            Ok(()) => println!("[REPORT] {}", html.display()),
+            Err(e) => eprintln!("Report failed: {e}"),
+        },
+        Commands::Report {
+            symbol,
+            session,
+            from,
+            to,
+            group_by,
+            html,
+            output,
+            ..
+        } => {
+            let query = ReportQuery {
+                symbol,
+                session,
+                from,
+                to,
+            };
+            if let Err(e) = run_report(query, group_by, html.as_deref(), output.export()).await {
+                eprintln!("Report failed: {e}");
+            }
+        }
+        Commands::Db {
+            command: DbCommands::Migrate,
+        } => {
+            if let Err(e) = migrate_database().await {
+                eprintln!("Database migration failed: {e}");
             }
         }
     }
@@ -0,0 +1,23 @@
+use crate::utils::config::{ExecutionMode, TradingSimulation};
+use crate::utils::data_quality::QualityPolicy;
+
+use rust_decimal::dec;
+
+// paper trading of `symbol` on 1m candles with 500 USDT, no exchange or HTTP server
+// configured; tests override the fields they exercise
+pub fn trading_simulation(symbol: &str, fast_period: u32, slow_period: u32) -> TradingSimulation {
+    TradingSimulation {
+        symbol: symbol.into(),
+        timeframe: "1m".into(),
+        initial_balance: dec!(500),
+        fast_period,
+        slow_period,
+        data_quality: QualityPolicy::Reject,
+        api_url: String::new(),
+        ws_url: String::new(),
+        exchange_info_path: String::new(),
+        participation_rate: None,
+        http_addr: None,
+        mode: ExecutionMode::Paper,
+    }
+}
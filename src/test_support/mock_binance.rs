@@ -0,0 +1,696 @@
+// local stand-in for Binance REST and WebSocket APIs, serving recorded fixtures
+// from `tests/fixtures/binance`, so tests never touch the real exchange.
+// signed order endpoints check API key, HMAC-SHA256 signature and recvWindow
+// the same way Binance does
+
+use axum::{
+    Router,
+    extract::{
+        Path, Query, RawQuery, State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+const KLINES_FIXTURE: &str = "tests/fixtures/binance/klines_btcusdt_1m_60.json";
+const EXCHANGE_INFO_FIXTURE: &str = "tests/fixtures/binance/exchange_info_btcusdt.json";
+const DEFAULT_KLINES_LIMIT: usize = 500;
+const DEFAULT_RECV_WINDOW_MS: i64 = 5000;
+
+pub const MOCK_API_KEY: &str = "mock-api-key";
+pub const MOCK_SECRET_KEY: &str = "mock-secret-key";
+
+// failure returned instead of the next REST response
+#[derive(Debug, Clone)]
+pub enum Fault {
+    Http { status: u16, body: String },
+    Binance { status: u16, code: i64, msg: String },
+}
+
+impl Fault {
+    pub fn http(status: u16, body: &str) -> Self {
+        Fault::Http {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    pub fn binance(status: u16, code: i64, msg: &str) -> Self {
+        Fault::Binance {
+            status,
+            code,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for Fault {
+    fn into_response(self) -> Response {
+        match self {
+            Fault::Http { status, body } => (status_code(status), body).into_response(),
+            Fault::Binance { status, code, msg } => (
+                status_code(status),
+                json!({ "code": code, "msg": msg }).to_string(),
+            )
+                .into_response(),
+        }
+    }
+}
+
+fn status_code(status: u16) -> StatusCode {
+    StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// order placed through signed endpoints, market orders fill at last kline close,
+// other types rest until test executes them
+#[derive(Debug, Clone)]
+struct MockOrder {
+    order_id: i64,
+    client_order_id: String,
+    symbol: String,
+    side: String,
+    order_type: String,
+    quantity: Decimal,
+    executed: Decimal,
+    quote: Decimal,
+    status: &'static str,
+    update_time: i64,
+}
+
+impl MockOrder {
+    fn is_working(&self) -> bool {
+        matches!(self.status, "NEW" | "PARTIALLY_FILLED")
+    }
+
+    fn report(&self) -> Value {
+        json!({
+            "symbol": self.symbol,
+            "orderId": self.order_id,
+            "clientOrderId": self.client_order_id,
+            "origQty": self.quantity.to_string(),
+            "executedQty": self.executed.to_string(),
+            "cummulativeQuoteQty": self.quote.to_string(),
+            "status": self.status,
+            "type": self.order_type,
+            "side": self.side,
+            "updateTime": self.update_time,
+        })
+    }
+}
+
+// user data stream events, fanned out to every connected listen key
+struct UserEvents(broadcast::Sender<String>);
+
+impl Default for UserEvents {
+    fn default() -> Self {
+        UserEvents(broadcast::channel(100).0)
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    klines: Mutex<Vec<Value>>,
+    exchange_info: Value,
+    faults: Mutex<VecDeque<Fault>>,
+    latency: Mutex<Duration>,
+    requests: AtomicUsize,
+    orders: Mutex<Vec<MockOrder>>,
+    listen_keys: Mutex<Vec<String>>,
+    user_events: UserEvents,
+}
+
+impl MockState {
+    // every REST call is counted, delayed and may be replaced by injected fault
+    async fn before_request(&self) -> Option<Fault> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let latency = *self.latency.lock().unwrap();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        self.faults.lock().unwrap().pop_front()
+    }
+
+    // `executionReport` of order change, `last_*` describe the trade of TRADE events
+    fn emit_execution(
+        &self,
+        order: &MockOrder,
+        execution_type: &str,
+        last_quantity: Decimal,
+        last_price: Decimal,
+        cancel_id: Option<&str>,
+    ) {
+        let event = json!({
+            "e": "executionReport",
+            "E": now_ms(),
+            "s": order.symbol,
+            "c": cancel_id.unwrap_or(&order.client_order_id),
+            "C": cancel_id.map_or("", |_| order.client_order_id.as_str()),
+            "S": order.side,
+            "o": order.order_type,
+            "q": order.quantity.to_string(),
+            "x": execution_type,
+            "X": order.status,
+            "r": "NONE",
+            "i": order.order_id,
+            "l": last_quantity.to_string(),
+            "z": order.executed.to_string(),
+            "L": last_price.to_string(),
+            "T": order.update_time,
+            "Z": order.quote.to_string(),
+        });
+        // nobody listening is fine
+        let _ = self.user_events.0.send(event.to_string());
+    }
+
+    fn last_price(&self) -> Decimal {
+        self.klines
+            .lock()
+            .unwrap()
+            .last()
+            .and_then(|k| k[4].as_str())
+            .and_then(|p| p.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+// checks signed request like Binance: API key header, HMAC-SHA256 of the query string
+// up to `&signature=` and timestamp within `recvWindow`, returns request parameters
+fn verify_signed(headers: &HeaderMap, query: &str) -> Result<HashMap<String, String>, Fault> {
+    if headers.get("X-MBX-APIKEY").and_then(|k| k.to_str().ok()) != Some(MOCK_API_KEY) {
+        return Err(Fault::binance(
+            401,
+            -2015,
+            "Invalid API-key, IP, or permissions for action.",
+        ));
+    }
+
+    let Some((payload, signature)) = query.rsplit_once("&signature=") else {
+        return Err(Fault::binance(
+            400,
+            -1102,
+            "Mandatory parameter 'signature' was not sent, was empty/null, or malformed.",
+        ));
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(MOCK_SECRET_KEY.as_bytes()).unwrap();
+    mac.update(payload.as_bytes());
+    if format!("{:x}", mac.finalize().into_bytes()) != signature {
+        return Err(Fault::binance(
+            400,
+            -1022,
+            "Signature for this request is not valid.",
+        ));
+    }
+
+    let params: HashMap<String, String> = url::form_urlencoded::parse(payload.as_bytes())
+        .into_owned()
+        .collect();
+    let param = |name: &str| params.get(name).and_then(|v| v.parse::<i64>().ok());
+    let recv_window = param("recvWindow").unwrap_or(DEFAULT_RECV_WINDOW_MS);
+    match param("timestamp") {
+        Some(ts) if ts < now_ms() + 1000 && now_ms() - ts <= recv_window => Ok(params),
+        _ => Err(Fault::binance(
+            400,
+            -1021,
+            "Timestamp for this request is outside of the recvWindow.",
+        )),
+    }
+}
+
+pub struct MockBinance {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockBinance {
+    pub async fn start() -> Self {
+        let read_fixture = |path: &str| -> Value {
+            let content = std::fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("Missing fixture {}: {}", path, e));
+            serde_json::from_str(&content).expect("Invalid fixture JSON")
+        };
+
+        let state = Arc::new(MockState {
+            klines: Mutex::new(
+                read_fixture(KLINES_FIXTURE)
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+            exchange_info: read_fixture(EXCHANGE_INFO_FIXTURE),
+            ..Default::default()
+        });
+
+        let app = Router::new()
+            .route("/api/v3/klines", get(klines))
+            .route("/api/v3/exchangeInfo", get(exchange_info))
+            .route(
+                "/api/v3/order",
+                get(query_order).post(new_order).delete(cancel_order),
+            )
+            .route(
+                "/api/v3/userDataStream",
+                axum::routing::post(new_listen_key).put(renew_listen_key),
+            )
+            .route("/ws/{listen_key}", get(user_data_stream))
+            .route("/stream", get(kline_stream))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock server");
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        MockBinance {
+            addr,
+            state,
+            server,
+        }
+    }
+
+    pub fn http_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    // faults are consumed one per REST request, in order of injection
+    pub fn fail_next(&self, fault: Fault) {
+        self.state.faults.lock().unwrap().push_back(fault);
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        *self.state.latency.lock().unwrap() = latency;
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.state.requests.load(Ordering::SeqCst)
+    }
+
+    pub fn klines(&self) -> Vec<Value> {
+        self.state.klines.lock().unwrap().clone()
+    }
+
+    // market moves on: appends kline opening when the last one closes, closing at `close`
+    pub fn push_kline(&self, close: &str) {
+        let mut klines = self.state.klines.lock().unwrap();
+        let mut kline = klines.last().cloned().expect("Fixture has no klines");
+        let interval = kline[6].as_i64().unwrap() + 1 - kline[0].as_i64().unwrap();
+        kline[0] = json!(kline[0].as_i64().unwrap() + interval);
+        kline[6] = json!(kline[6].as_i64().unwrap() + interval);
+        let price = |v: &Value| v.as_str().unwrap().parse::<f64>().unwrap();
+        let (open, last) = (price(&kline[4]), close.parse::<f64>().unwrap());
+        kline[1] = kline[4].clone();
+        kline[2] = json!(open.max(last).to_string());
+        kline[3] = json!(open.min(last).to_string());
+        kline[4] = json!(close);
+        klines.push(kline);
+    }
+
+    // current state of order placed with `client_order_id`, as Binance reports it
+    pub fn order(&self, client_order_id: &str) -> Option<Value> {
+        let orders = self.state.orders.lock().unwrap();
+        orders
+            .iter()
+            .find(|o| o.client_order_id == client_order_id)
+            .map(MockOrder::report)
+    }
+
+    // simulates execution of a resting order on the exchange
+    pub fn execute_order(&self, client_order_id: &str, quantity: Decimal, price: Decimal) {
+        let mut orders = self.state.orders.lock().unwrap();
+        let order = orders
+            .iter_mut()
+            .find(|o| o.client_order_id == client_order_id && o.is_working())
+            .expect("No working order to execute");
+        order.executed += quantity;
+        order.quote += quantity * price;
+        order.status = if order.executed >= order.quantity {
+            "FILLED"
+        } else {
+            "PARTIALLY_FILLED"
+        };
+        order.update_time = now_ms();
+        self.state
+            .emit_execution(order, "TRADE", quantity, price, None);
+    }
+
+    // raw event for user data stream, e.g. `outboundAccountPosition` or a manual trade
+    pub fn push_user_event(&self, event: Value) {
+        let _ = self.state.user_events.0.send(event.to_string());
+    }
+
+    // events are only delivered to connected streams
+    pub async fn wait_for_user_stream(&self) {
+        for _ in 0..100 {
+            if self.state.user_events.0.receiver_count() > 0 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("No user data stream connected");
+    }
+}
+
+impl Drop for MockBinance {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+// `GET /api/v3/klines`, recorded series is served regardless of symbol and interval
+async fn klines(
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+
+    let param = |name: &str| params.get(name).and_then(|v| v.parse::<i64>().ok());
+    let limit = param("limit").map_or(DEFAULT_KLINES_LIMIT, |l| l as usize);
+    let klines = state.klines.lock().unwrap().clone();
+    let selected: Vec<&Value> = klines
+        .iter()
+        .filter(|k| {
+            let open_time = k[0].as_i64().unwrap_or_default();
+            param("startTime").is_none_or(|t| open_time >= t)
+                && param("endTime").is_none_or(|t| open_time <= t)
+        })
+        .collect();
+
+    // with `startTime` Binance returns oldest klines first, otherwise most recent ones
+    let window: Vec<&Value> = if params.contains_key("startTime") {
+        selected.into_iter().take(limit).collect()
+    } else {
+        let skip = selected.len().saturating_sub(limit);
+        selected.into_iter().skip(skip).collect()
+    };
+
+    json!(window).to_string().into_response()
+}
+
+// `GET /api/v3/exchangeInfo`, optionally narrowed with `symbol`
+async fn exchange_info(
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+
+    let mut info = state.exchange_info.clone();
+    if let Some(symbol) = params.get("symbol") {
+        let symbols: Vec<Value> = info["symbols"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|s| s["symbol"].as_str() == Some(symbol.as_str()))
+            .cloned()
+            .collect();
+        if symbols.is_empty() {
+            return Fault::binance(400, -1121, "Invalid symbol.").into_response();
+        }
+        info["symbols"] = json!(symbols);
+    }
+
+    info.to_string().into_response()
+}
+
+// `POST /api/v3/order` (signed)
+async fn new_order(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+    let params = match verify_signed(&headers, query.as_deref().unwrap_or_default()) {
+        Ok(params) => params,
+        Err(fault) => return fault.into_response(),
+    };
+
+    let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+    let Ok(quantity) = param("quantity").parse::<Decimal>() else {
+        return Fault::binance(400, -1102, "Mandatory parameter 'quantity' was not sent.")
+            .into_response();
+    };
+
+    let mut orders = state.orders.lock().unwrap();
+    let client_order_id = params
+        .get("newClientOrderId")
+        .cloned()
+        .unwrap_or_else(|| format!("mock-{}", orders.len() + 1));
+    if orders
+        .iter()
+        .any(|o| o.client_order_id == client_order_id && o.is_working())
+    {
+        return Fault::binance(400, -2010, "Duplicate order sent.").into_response();
+    }
+
+    let mut order = MockOrder {
+        order_id: orders.len() as i64 + 1,
+        client_order_id,
+        symbol: param("symbol"),
+        side: param("side"),
+        order_type: param("type"),
+        quantity,
+        executed: Decimal::ZERO,
+        quote: Decimal::ZERO,
+        status: "NEW",
+        update_time: now_ms(),
+    };
+    state.emit_execution(&order, "NEW", Decimal::ZERO, Decimal::ZERO, None);
+    if order.order_type == "MARKET" {
+        let price = state.last_price();
+        order.executed = quantity;
+        order.quote = quantity * price;
+        order.status = "FILLED";
+        state.emit_execution(&order, "TRADE", quantity, price, None);
+    }
+    orders.push(order.clone());
+
+    let mut report = order.report();
+    report["transactTime"] = json!(order.update_time);
+    report.to_string().into_response()
+}
+
+// `GET /api/v3/order` (signed), looked up by `origClientOrderId` or `orderId`
+async fn query_order(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+    let params = match verify_signed(&headers, query.as_deref().unwrap_or_default()) {
+        Ok(params) => params,
+        Err(fault) => return fault.into_response(),
+    };
+
+    let orders = state.orders.lock().unwrap();
+    match orders.iter().find(|o| is_requested(o, &params)) {
+        Some(order) => order.report().to_string().into_response(),
+        None => Fault::binance(400, -2013, "Order does not exist.").into_response(),
+    }
+}
+
+// `DELETE /api/v3/order` (signed)
+async fn cancel_order(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+    let params = match verify_signed(&headers, query.as_deref().unwrap_or_default()) {
+        Ok(params) => params,
+        Err(fault) => return fault.into_response(),
+    };
+
+    let mut orders = state.orders.lock().unwrap();
+    match orders
+        .iter_mut()
+        .find(|o| is_requested(o, &params) && o.is_working())
+    {
+        Some(order) => {
+            order.status = "CANCELED";
+            order.update_time = now_ms();
+            let cancel_id = format!("cancel-{}", order.order_id);
+            state.emit_execution(
+                order,
+                "CANCELED",
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Some(&cancel_id),
+            );
+            let mut report = order.report();
+            report["transactTime"] = json!(order.update_time);
+            report.to_string().into_response()
+        }
+        None => Fault::binance(400, -2011, "Unknown order sent.").into_response(),
+    }
+}
+
+fn is_requested(order: &MockOrder, params: &HashMap<String, String>) -> bool {
+    match (params.get("origClientOrderId"), params.get("orderId")) {
+        (Some(client_order_id), _) => order.client_order_id == *client_order_id,
+        (None, Some(order_id)) => order.order_id.to_string() == *order_id,
+        (None, None) => false,
+    }
+}
+
+// `POST /api/v3/userDataStream`, needs API key but no signature
+async fn new_listen_key(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+    if headers.get("X-MBX-APIKEY").and_then(|k| k.to_str().ok()) != Some(MOCK_API_KEY) {
+        return Fault::binance(
+            401,
+            -2015,
+            "Invalid API-key, IP, or permissions for action.",
+        )
+        .into_response();
+    }
+
+    let mut listen_keys = state.listen_keys.lock().unwrap();
+    let listen_key = format!("mock-listen-key-{}", listen_keys.len() + 1);
+    listen_keys.push(listen_key.clone());
+    json!({ "listenKey": listen_key })
+        .to_string()
+        .into_response()
+}
+
+// `PUT /api/v3/userDataStream?listenKey=..`, keepalive
+async fn renew_listen_key(
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(fault) = state.before_request().await {
+        return fault.into_response();
+    }
+    let known = state.listen_keys.lock().unwrap();
+    match params.get("listenKey") {
+        Some(key) if known.contains(key) => "{}".into_response(),
+        _ => Fault::binance(400, -1125, "This listenKey does not exist.").into_response(),
+    }
+}
+
+// `GET /ws/<listenKey>`, forwards account events until client leaves
+async fn user_data_stream(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<MockState>>,
+    Path(listen_key): Path<String>,
+) -> Response {
+    if !state.listen_keys.lock().unwrap().contains(&listen_key) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let events = state.user_events.0.subscribe();
+    ws.on_upgrade(move |socket| forward_user_events(socket, events))
+}
+
+async fn forward_user_events(mut socket: WebSocket, mut events: broadcast::Receiver<String>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if socket.send(Message::Text(event.into())).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+// `GET /stream?streams=<symbol>@kline_<interval>`, replays recorded klines as
+// closed kline events and keeps connection open until client leaves
+async fn kline_stream(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let streams: Vec<String> = params
+        .get("streams")
+        .map(|s| s.split('/').map(str::to_string).collect())
+        .unwrap_or_default();
+
+    ws.on_upgrade(move |socket| replay_klines(socket, state, streams))
+}
+
+async fn replay_klines(mut socket: WebSocket, state: Arc<MockState>, streams: Vec<String>) {
+    for stream in &streams {
+        let Some((symbol, interval)) = stream.split_once("@kline_") else {
+            continue;
+        };
+
+        let klines = state.klines.lock().unwrap().clone();
+        for k in &klines {
+            let latency = *state.latency.lock().unwrap();
+            if !latency.is_zero() {
+                tokio::time::sleep(latency).await;
+            }
+
+            let event = json!({
+                "stream": stream,
+                "data": {
+                    "e": "kline",
+                    "E": k[6].as_i64().unwrap_or_default() + 1,
+                    "s": symbol.to_uppercase(),
+                    "k": {
+                        "t": k[0], "T": k[6], "s": symbol.to_uppercase(), "i": interval,
+                        "f": 0, "L": k[8], "o": k[1], "c": k[4], "h": k[2], "l": k[3],
+                        "v": k[5], "n": k[8], "x": true, "q": k[7], "V": k[9], "Q": k[10],
+                        "B": k[11],
+                    }
+                }
+            });
+            if socket
+                .send(Message::Text(event.to_string().into()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    while let Some(Ok(message)) = socket.recv().await {
+        if matches!(message, Message::Close(_)) {
+            break;
+        }
+    }
+}
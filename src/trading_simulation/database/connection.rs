@@ -1,6 +1,12 @@
+use crate::trading_simulation::database::store::Store;
+
+use dotenv::dotenv;
 use sqlx::PgPool;
+use sqlx::migrate::MigrateError;
+use std::env;
+use std::error::Error;
 
-pub async fn create_db_connection(database_url: &String) -> Option<PgPool> {
+pub async fn create_db_connection(database_url: &str) -> Option<PgPool> {
     match PgPool::connect(database_url).await {
         Ok(pool) => {
             println!("✅[POSTGRES DB] Connected to database successfully");
@@ -10,6 +16,23 @@ pub async fn create_db_connection(database_url: &String) -> Option<PgPool> {
     }
 }
 
+// applies versioned schema migrations from `migrations/postgres/` (embedded into binary)
+// not yet recorded in `_sqlx_migrations`
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    sqlx::migrate!("./migrations/postgres").run(pool).await?;
+    println!("✅[POSTGRES DB] Schema is up to date");
+    Ok(())
+}
+
+// `bot db migrate`: brings database from DATABASE_URL up to date without trading
+pub async fn migrate_database() -> Result<(), Box<dyn Error>> {
+    dotenv().ok(); // load env variables
+
+    let database_url = env::var("DATABASE_URL")?;
+    Store::connect(&database_url).await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -20,7 +43,10 @@ mod tests {
     async fn test_connection() {
         dotenv().ok(); // load .env if present
 
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env");
+        let Ok(database_url) = env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL not set, skipping Postgres test");
+            return;
+        };
 
         let pool = create_db_connection(&database_url)
             .await
@@ -28,4 +54,30 @@ mod tests {
 
         println!("Successfully created PgPool: {:?}", pool);
     }
+
+    #[tokio::test]
+    async fn test_migrations_are_idempotent() {
+        dotenv().ok(); // load .env if present
+
+        let Ok(database_url) = env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL not set, skipping Postgres test");
+            return;
+        };
+        let pool = create_db_connection(&database_url)
+            .await
+            .expect("Failed to connect to database");
+
+        // docker init scripts apply same files before bot records them
+        for migration in sqlx::migrate!("./migrations/postgres").iter() {
+            sqlx::raw_sql(&migration.sql)
+                .execute(&pool)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!("Migration {} not re-runnable: {}", migration.version, e)
+                });
+        }
+        run_migrations(&pool)
+            .await
+            .expect("Failed to run migrations");
+    }
 }
@@ -1,5 +1,9 @@
-use crate::utils::objects::Trade;
-use ::sqlx::PgPool;
+use crate::trading_simulation::exchange::order::Order;
+use crate::utils::config::timeframe_duration;
+use crate::utils::objects::{CandleStick, EquitySnapshot, Session, Trade};
+use ::sqlx::{PgPool, Postgres, QueryBuilder};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 
 pub async fn is_position_open(pool: &sqlx::PgPool, symbol: &str) -> Result<bool, sqlx::Error> {
     sqlx::query_scalar!(
@@ -18,21 +22,23 @@ pub async fn is_position_open(pool: &sqlx::PgPool, symbol: &str) -> Result<bool,
 pub async fn record_open_trade(
     pool: &PgPool,
     symbol: &str,
-    entry_price: f64,
-    trade_size: f64,
-    position_size: f64,
+    entry_price: Decimal,
+    trade_size: Decimal,
+    position_size: Decimal,
+    entry_time: DateTime<Utc>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO trades 
         (symbol, entry_price, trade_size, position_size, entry_time, status)
-        VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP, 'OPEN')
+        VALUES ($1, $2, $3, $4, $5, 'OPEN')
         "#,
     )
     .bind(symbol)
     .bind(entry_price)
     .bind(trade_size)
     .bind(position_size)
+    .bind(entry_time)
     .execute(pool)
     .await?;
 
@@ -42,9 +48,9 @@ pub async fn record_open_trade(
 pub async fn record_close_trade(
     pool: &PgPool,
     trade_id: i64,
-    exit_price: f64,
-    pnl: f64,
-    timestamp: i64,
+    exit_price: Decimal,
+    pnl: Decimal,
+    exit_time: DateTime<Utc>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
@@ -52,14 +58,14 @@ pub async fn record_close_trade(
         SET 
             exit_price = $1,
             pnl = $2,
-            exit_time = to_timestamp($3),
+            exit_time = $3,
             status = 'CLOSED'
         WHERE id = $4
         "#,
     )
     .bind(exit_price)
     .bind(pnl)
-    .bind(timestamp)
+    .bind(exit_time)
     .bind(trade_id)
     .execute(pool)
     .await?;
@@ -67,6 +73,71 @@ pub async fn record_close_trade(
     Ok(())
 }
 
+// position changed by partial fill, open trade keeps its id and entry time
+pub async fn resize_open_trade(
+    pool: &PgPool,
+    trade_id: i64,
+    entry_price: Decimal,
+    trade_size: Decimal,
+    position_size: Decimal,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE trades
+        SET entry_price = $1, trade_size = $2, position_size = $3
+        WHERE id = $4 AND status = 'OPEN'
+        "#,
+    )
+    .bind(entry_price)
+    .bind(trade_size)
+    .bind(position_size)
+    .bind(trade_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// excursions are measured once the trade is closed, see `trade_analysis_report::record_excursions`
+pub async fn set_trade_excursion(
+    pool: &PgPool,
+    trade_id: i64,
+    mae: Decimal,
+    mfe: Decimal,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE trades SET mae = $1, mfe = $2 WHERE id = $3")
+        .bind(mae)
+        .bind(mfe)
+        .bind(trade_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// closed part of a position split off by partial sell
+pub async fn insert_closed_trade(pool: &PgPool, trade: &Trade) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO trades
+        (symbol, entry_price, exit_price, trade_size, position_size, pnl, entry_time, exit_time, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'CLOSED')
+        "#,
+    )
+    .bind(&trade.symbol)
+    .bind(trade.entry_price)
+    .bind(trade.exit_price)
+    .bind(trade.trade_size)
+    .bind(trade.position_size)
+    .bind(trade.pnl)
+    .bind(trade.entry_time)
+    .bind(trade.exit_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn get_open_trade_info(
     pool: &PgPool,
     symbol: &str,
@@ -99,7 +170,9 @@ pub async fn get_closed_trades(pool: &PgPool, symbol: &str) -> Result<Vec<Trade>
             pnl,
             entry_time as "entry_time: chrono::DateTime<chrono::Utc>",
             exit_time  as "exit_time:  chrono::DateTime<chrono::Utc>",
-            status
+            status,
+            mae,
+            mfe
         FROM trades
         WHERE symbol = $1 AND status = 'CLOSED'
         ORDER BY exit_time ASC NULLS LAST, id ASC
@@ -126,7 +199,9 @@ pub async fn get_open_trades(pool: &PgPool, symbol: &str) -> Result<Vec<Trade>,
             pnl,
             entry_time as "entry_time: chrono::DateTime<chrono::Utc>",
             exit_time  as "exit_time:  chrono::DateTime<chrono::Utc>",
-            status
+            status,
+            mae,
+            mfe
         FROM trades
         WHERE symbol = $1 AND status = 'OPEN'
         ORDER BY entry_time ASC, id ASC
@@ -137,44 +212,350 @@ pub async fn get_open_trades(pool: &PgPool, symbol: &str) -> Result<Vec<Trade>,
     .await
 }
 
+// sum of realized pnl over CLOSED trades, current balance = initial balance + this
+pub async fn get_realized_pnl(pool: &PgPool, symbol: &str) -> Result<Decimal, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(pnl), 0) as "pnl!: Decimal"
+        FROM trades
+        WHERE symbol = $1 AND status = 'CLOSED'
+        "#,
+        symbol
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn insert_order(pool: &PgPool, order: &Order) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO orders
+        (symbol, side, order_type, time_in_force, quantity, price, stop_price,
+         filled_quantity, avg_fill_price, triggered, status, exchange_order_id,
+         created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        RETURNING id
+        "#,
+    )
+    .bind(&order.symbol)
+    .bind(order.side.as_str())
+    .bind(order.order_type.as_str())
+    .bind(order.time_in_force.as_str())
+    .bind(order.quantity)
+    .bind(order.price)
+    .bind(order.stop_price)
+    .bind(order.filled_quantity)
+    .bind(order.avg_fill_price)
+    .bind(order.triggered)
+    .bind(order.status.as_str())
+    .bind(order.exchange_order_id)
+    .bind(order.created_at)
+    .bind(order.updated_at)
+    .fetch_one(pool)
+    .await
+    .map(|id| id as i64)
+}
+
+// order progress: fills, trigger and status transitions
+pub async fn update_order(pool: &PgPool, order: &Order) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE orders
+        SET filled_quantity = $1, avg_fill_price = $2, triggered = $3, status = $4,
+            exchange_order_id = $5, updated_at = $6
+        WHERE id = $7
+        "#,
+    )
+    .bind(order.filled_quantity)
+    .bind(order.avg_fill_price)
+    .bind(order.triggered)
+    .bind(order.status.as_str())
+    .bind(order.exchange_order_id)
+    .bind(order.updated_at)
+    .bind(order.id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_order(pool: &PgPool, id: i64) -> Result<Option<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>("SELECT * FROM orders WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+// NEW and PARTIALLY_FILLED orders, oldest first
+pub async fn get_working_orders(pool: &PgPool, symbol: &str) -> Result<Vec<Order>, sqlx::Error> {
+    sqlx::query_as::<_, Order>(
+        r#"
+        SELECT * FROM orders
+        WHERE symbol = $1 AND status IN ('NEW', 'PARTIALLY_FILLED')
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(symbol)
+    .fetch_all(pool)
+    .await
+}
+
+// rows per INSERT statement, keeps bind parameters well below driver limits
+pub const CANDLE_BATCH: usize = 1000;
+
+// candles keyed by (symbol, timeframe, open time), stored candles are replaced
+pub async fn upsert_candles(
+    pool: &PgPool,
+    timeframe: &str,
+    candles: &[CandleStick],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for batch in candles.chunks(CANDLE_BATCH) {
+        QueryBuilder::<Postgres>::new(
+            "INSERT INTO prices (symbol, timeframe, open_time, open, high, low, close, volume) ",
+        )
+        .push_values(batch, |mut row, candle| {
+            row.push_bind(&candle.symbol)
+                .push_bind(timeframe)
+                .push_bind(candle.timestamp)
+                .push_bind(candle.open)
+                .push_bind(candle.high)
+                .push_bind(candle.low)
+                .push_bind(candle.close)
+                .push_bind(candle.volume);
+        })
+        .push(
+            r#"
+            ON CONFLICT (symbol, timeframe, open_time) DO UPDATE
+            SET open = EXCLUDED.open, high = EXCLUDED.high, low = EXCLUDED.low,
+                close = EXCLUDED.close, volume = EXCLUDED.volume
+            "#,
+        )
+        .build()
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// latest `limit` candles opened at or before `until`, oldest first
+pub async fn get_last_candles(
+    pool: &PgPool,
+    symbol: &str,
+    timeframe: &str,
+    until: i64,
+    limit: i64,
+) -> Result<Vec<CandleStick>, sqlx::Error> {
+    let mut candles = sqlx::query_as::<_, CandleStick>(
+        r#"
+        SELECT symbol, open, high, low, close, volume, open_time AS timestamp
+        FROM prices
+        WHERE symbol = $1 AND timeframe = $2 AND open_time <= $3
+        ORDER BY open_time DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(symbol)
+    .bind(timeframe)
+    .bind(until)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    candles.reverse();
+    Ok(candles)
+}
+
+// all candles of a symbol, oldest first; TimescaleDB continuous aggregate `prices_<timeframe>`
+// (built from 1m candles) is preferred where it exists, without its last bucket if that one
+// is still open; candles stored with `timeframe` are read otherwise
+pub async fn get_candle_history(
+    pool: &PgPool,
+    symbol: &str,
+    timeframe: &str,
+) -> Result<Vec<CandleStick>, sqlx::Error> {
+    if let Ok(bucket) = timeframe_duration(timeframe) {
+        let view = format!("prices_{}", timeframe);
+        let timescale: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(&view)
+            .fetch_one(pool)
+            .await?;
+        if timescale {
+            let aggregated = sqlx::query_as::<_, CandleStick>(&format!(
+                r#"
+                SELECT symbol, open, high, low, close, volume, open_time AS timestamp
+                FROM {}
+                WHERE symbol = $1 AND open_time + $2 <= (
+                    SELECT max(open_time) + 60000 FROM prices WHERE symbol = $1 AND timeframe = '1m'
+                )
+                ORDER BY open_time
+                "#,
+                view
+            ))
+            .bind(symbol)
+            .bind(bucket.num_milliseconds())
+            .fetch_all(pool)
+            .await?;
+            if !aggregated.is_empty() {
+                println!(
+                    "[DATABASE] {} {} candles of {} aggregated from 1m candles in {}",
+                    aggregated.len(),
+                    timeframe,
+                    symbol,
+                    view
+                );
+                return Ok(aggregated);
+            }
+        }
+    }
+
+    let stored = get_last_candles(pool, symbol, timeframe, i64::MAX, i64::MAX).await?;
+    println!(
+        "[DATABASE] {} stored {} candles of {}",
+        stored.len(),
+        timeframe,
+        symbol
+    );
+    Ok(stored)
+}
+
+pub async fn start_session(
+    pool: &PgPool,
+    symbol: &str,
+    mode: &str,
+    initial_balance: Decimal,
+    started_at: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO sessions (symbol, mode, initial_balance, started_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(symbol)
+    .bind(mode)
+    .bind(initial_balance)
+    .bind(started_at)
+    .fetch_one(pool)
+    .await
+    .map(|id| id as i64)
+}
+
+pub async fn end_session(
+    pool: &PgPool,
+    id: i64,
+    ended_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE sessions SET ended_at = $1 WHERE id = $2")
+        .bind(ended_at)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_session(pool: &PgPool, id: i64) -> Result<Option<Session>, sqlx::Error> {
+    sqlx::query_as::<_, Session>(
+        r#"
+        SELECT id::BIGINT AS id, symbol, mode, initial_balance, started_at, ended_at
+        FROM sessions
+        WHERE id = $1
+        "#,
+    )
+    .bind(id as i32)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_equity_snapshot(
+    pool: &PgPool,
+    session_id: i64,
+    symbol: &str,
+    snapshot: &EquitySnapshot,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO equity_snapshots
+            (session_id, symbol, time, balance, unrealized_pnl, equity, exposure, price)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(session_id as i32)
+    .bind(symbol)
+    .bind(snapshot.time)
+    .bind(snapshot.balance)
+    .bind(snapshot.unrealized_pnl)
+    .bind(snapshot.equity)
+    .bind(snapshot.exposure)
+    .bind(snapshot.price)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_equity_snapshots(
+    pool: &PgPool,
+    session_id: i64,
+) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, EquitySnapshot>(
+        r#"
+        SELECT time, balance, unrealized_pnl, equity, exposure, price
+        FROM equity_snapshots
+        WHERE session_id = $1
+        ORDER BY time, id
+        "#,
+    )
+    .bind(session_id as i32)
+    .fetch_all(pool)
+    .await
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::trading_simulation::database::connection::create_db_connection;
-    use chrono::Utc;
-    use dotenv::dotenv;
-    use std::env;
+    use crate::trading_simulation::database::store::{Store, TradeStore};
+    use chrono::{DateTime, Duration};
+    use rust_decimal::dec;
 
     #[tokio::test]
+    #[allow(clippy::bool_assert_comparison)]
     async fn test_trade_log() {
-        dotenv().ok();
-
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env");
         let symbol = "TESTSYM";
 
-        let pool = create_db_connection(&database_url)
+        let store = Store::connect("sqlite::memory:")
             .await
             .expect("Failed to connect to database");
 
         println!("[TEST] Using symbol: {}", symbol);
 
-        let is_open = is_position_open(&pool, symbol).await.unwrap();
+        let is_open = store.is_position_open(symbol).await.unwrap();
         println!("[TEST] Is position open initially? {}", is_open);
         assert_eq!(is_open, false, "No open trade should exist initially");
 
+        let entry_time = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let realized_before = store.get_realized_pnl(symbol).await.unwrap();
+
         println!("[TEST] Recording open trade...");
-        record_open_trade(&pool, symbol, 100.0, 1.0, 0.5)
+        store
+            .record_open_trade(symbol, dec!(100.1), dec!(0.3), dec!(30.03), entry_time)
             .await
             .expect("Failed to insert open trade");
 
-        let is_open = is_position_open(&pool, symbol).await.unwrap();
+        let is_open = store.is_position_open(symbol).await.unwrap();
         println!("[TEST] Is position open after insert? {}", is_open);
         assert_eq!(is_open, true, "Open trade should exist after insert");
 
-        let trade = get_open_trade_info(&pool, symbol)
+        let trade = store
+            .get_open_trade_info(symbol)
             .await
             .expect("Failed to get open trade info")
             .expect("Open trade should exist");
+        assert_eq!(trade.entry_time, entry_time);
 
         println!(
             "[TEST] Open trade info -> id: {}, symbol: {}, entry_price: {}, trade_size: {}, position_size: {}",
@@ -182,20 +563,31 @@ mod tests {
         );
 
         let trade_id = trade.id;
-        let exit_price = 110.0;
-        let pnl = exit_price - trade.entry_price;
-        let timestamp = Utc::now().timestamp();
+        let exit_price = dec!(110.2);
+        let pnl = (exit_price - trade.entry_price) * trade.trade_size;
+        let exit_time = entry_time + Duration::minutes(15);
 
         println!(
             "[TEST] Closing trade id {} at exit price {}, PnL {:.2}",
             trade_id, exit_price, pnl
         );
 
-        record_close_trade(&pool, trade_id, exit_price, pnl, timestamp)
+        store
+            .record_close_trade(trade_id, exit_price, pnl, exit_time)
             .await
             .expect("Failed to close trade");
 
-        let is_open = is_position_open(&pool, symbol).await.unwrap();
+        let closed = store.get_closed_trades(symbol).await.unwrap();
+        let closed = closed.iter().find(|t| t.id == trade_id).unwrap();
+        assert_eq!(closed.exit_time, Some(exit_time));
+        // decimal round trip is exact, no float noise in stored pnl
+        assert_eq!(closed.pnl, Some(dec!(3.03)));
+        assert_eq!(
+            store.get_realized_pnl(symbol).await.unwrap() - realized_before,
+            dec!(3.03)
+        );
+
+        let is_open = store.is_position_open(symbol).await.unwrap();
         println!("[TEST] Is position open after closing? {}", is_open);
         assert_eq!(is_open, false, "Trade should be closed");
 
@@ -221,84 +613,3 @@ mod tests {
 
 //     Ok(result.rows_affected())
 // }
-
-// #[allow(dead_code)]
-// // Function for clearing trades table
-// pub async fn clear_prices_table(pool: &PgPool) -> Result<u64, sqlx::Error> {
-//     let result = sqlx::query("DELETE FROM prices").execute(pool).await?;
-
-//     Ok(result.rows_affected())
-// }
-
-// #[allow(dead_code)]
-// pub async fn add_price(
-//     pool: &PgPool,
-//     candle_stick: CandleStick,
-//     lookback: i32,
-// ) -> Result<CandleStick, sqlx::Error> {
-//     // Insert new price
-//     let inserted = sqlx::query_as::<_, CandleStick>(
-//         "INSERT INTO prices (coin, open, high, low, close, volume, timestamp)
-//          VALUES ($1, $2, $3, $4, $5, $6, $7)
-//          RETURNING *",
-//     )
-//     .bind(candle_stick.symbol)
-//     .bind(candle_stick.open)
-//     .bind(candle_stick.high)
-//     .bind(candle_stick.low)
-//     .bind(candle_stick.close)
-//     .bind(candle_stick.volume)
-//     .bind(candle_stick.timestamp)
-//     .fetch_one(pool)
-//     .await?;
-
-//     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM prices")
-//         .fetch_one(pool)
-//         .await?;
-
-//     if count.0 > lookback as i64 {
-//         // Delete the oldest row by id or timestamp
-//         sqlx::query(
-//             "DELETE FROM prices WHERE timestamp = (
-//                 SELECT timestamp FROM prices ORDER BY timestamp ASC LIMIT 1)",
-//         )
-//         .execute(pool)
-//         .await?;
-//     }
-
-//     Ok(inserted)
-// }
-
-// #[allow(dead_code)]
-// pub async fn insert_prices(pool: &PgPool, candles: Vec<CandleStick>) -> Result<(), sqlx::Error> {
-//     let mut tx = pool.begin().await?;
-
-//     for candle in candles {
-//         sqlx::query(
-//             "INSERT INTO prices (coin, open, high, low, close, volume, timestamp)
-//             VALUES ($1, $2, $3, $4, $5, $6, $7)",
-//         )
-//         .bind(candle.symbol)
-//         .bind(candle.open)
-//         .bind(candle.high)
-//         .bind(candle.low)
-//         .bind(candle.close)
-//         .bind(candle.volume)
-//         .bind(candle.timestamp)
-//         .execute(&mut *tx)
-//         .await?;
-//     }
-
-//     tx.commit().await?;
-//     Ok(())
-// }
-
-// #[allow(dead_code)]
-// pub async fn get_last_n_prices(pool: &PgPool, n: i64) -> Result<Vec<CandleStick>, sqlx::Error> {
-//     let result = sqlx::query_as::<_, CandleStick>("SELECT * FROM prices ORDER BY id DESC LIMIT $1")
-//         .bind(n)
-//         .fetch_all(pool)
-//         .await?;
-
-//     Ok(result)
-// }
@@ -0,0 +1,300 @@
+use crate::trading_simulation::database::store::TradeStore;
+use crate::trading_simulation::exchange::order::Order;
+use crate::utils::objects::{CandleStick, EquitySnapshot, Session, Trade};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+struct Tables {
+    trades: Vec<Trade>,
+    orders: Vec<Order>,
+    // (symbol, timeframe, open time) -> candle, ordered like the primary key
+    prices: BTreeMap<(String, String, i64), CandleStick>,
+    // by id - 1
+    sessions: Vec<Session>,
+    // (session id, snapshot) in insert order
+    equity_snapshots: Vec<(i64, EquitySnapshot)>,
+}
+
+// state kept in process memory, lost once the bot stops. Behaves like the
+// database backends, ids are assigned from 1 in insert order
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl MemoryStore {
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        // tables stay consistent even if other holder panicked, each
+        // operation is applied as a whole under the lock
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn trades_where(&self, symbol: &str, status: &str) -> Vec<Trade> {
+        self.tables()
+            .trades
+            .iter()
+            .filter(|t| t.symbol == symbol && t.status == status)
+            .cloned()
+            .collect()
+    }
+}
+
+impl TradeStore for MemoryStore {
+    async fn is_position_open(&self, symbol: &str) -> Result<bool, sqlx::Error> {
+        Ok(!self.trades_where(symbol, "OPEN").is_empty())
+    }
+
+    async fn record_open_trade(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+        entry_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tables = self.tables();
+        let id = tables.trades.len() as i64 + 1;
+        tables.trades.push(Trade {
+            id,
+            symbol: symbol.to_string(),
+            entry_price,
+            exit_price: None,
+            trade_size,
+            position_size,
+            pnl: None,
+            entry_time,
+            exit_time: None,
+            status: "OPEN".to_string(),
+            mae: None,
+            mfe: None,
+        });
+        Ok(())
+    }
+
+    async fn record_close_trade(
+        &self,
+        trade_id: i64,
+        exit_price: Decimal,
+        pnl: Decimal,
+        exit_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        if let Some(trade) = self.tables().trades.iter_mut().find(|t| t.id == trade_id) {
+            trade.exit_price = Some(exit_price);
+            trade.pnl = Some(pnl);
+            trade.exit_time = Some(exit_time);
+            trade.status = "CLOSED".to_string();
+        }
+        Ok(())
+    }
+
+    async fn resize_open_trade(
+        &self,
+        trade_id: i64,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        if let Some(trade) = self
+            .tables()
+            .trades
+            .iter_mut()
+            .find(|t| t.id == trade_id && t.status == "OPEN")
+        {
+            trade.entry_price = entry_price;
+            trade.trade_size = trade_size;
+            trade.position_size = position_size;
+        }
+        Ok(())
+    }
+
+    async fn insert_closed_trade(&self, trade: &Trade) -> Result<(), sqlx::Error> {
+        let mut tables = self.tables();
+        let id = tables.trades.len() as i64 + 1;
+        tables.trades.push(Trade {
+            id,
+            status: "CLOSED".to_string(),
+            mae: None,
+            mfe: None,
+            ..trade.clone()
+        });
+        Ok(())
+    }
+
+    async fn set_trade_excursion(
+        &self,
+        trade_id: i64,
+        mae: Decimal,
+        mfe: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        if let Some(trade) = self.tables().trades.iter_mut().find(|t| t.id == trade_id) {
+            trade.mae = Some(mae);
+            trade.mfe = Some(mfe);
+        }
+        Ok(())
+    }
+
+    async fn get_open_trade_info(&self, symbol: &str) -> Result<Option<Trade>, sqlx::Error> {
+        Ok(self.trades_where(symbol, "OPEN").into_iter().next())
+    }
+
+    async fn get_closed_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        let mut trades = self.trades_where(symbol, "CLOSED");
+        trades.sort_by_key(|t| (t.exit_time, t.id));
+        Ok(trades)
+    }
+
+    async fn get_open_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        let mut trades = self.trades_where(symbol, "OPEN");
+        trades.sort_by_key(|t| (t.entry_time, t.id));
+        Ok(trades)
+    }
+
+    async fn get_realized_pnl(&self, symbol: &str) -> Result<Decimal, sqlx::Error> {
+        Ok(self
+            .trades_where(symbol, "CLOSED")
+            .iter()
+            .filter_map(|t| t.pnl)
+            .sum())
+    }
+
+    async fn insert_order(&self, order: &Order) -> Result<i64, sqlx::Error> {
+        let mut tables = self.tables();
+        let id = tables.orders.len() as i64 + 1;
+        tables.orders.push(Order {
+            id,
+            ..order.clone()
+        });
+        Ok(id)
+    }
+
+    async fn update_order(&self, order: &Order) -> Result<(), sqlx::Error> {
+        if let Some(stored) = self.tables().orders.iter_mut().find(|o| o.id == order.id) {
+            stored.filled_quantity = order.filled_quantity;
+            stored.avg_fill_price = order.avg_fill_price;
+            stored.triggered = order.triggered;
+            stored.status = order.status;
+            stored.exchange_order_id = order.exchange_order_id;
+            stored.updated_at = order.updated_at;
+        }
+        Ok(())
+    }
+
+    async fn get_order(&self, id: i64) -> Result<Option<Order>, sqlx::Error> {
+        Ok(self.tables().orders.iter().find(|o| o.id == id).cloned())
+    }
+
+    async fn get_working_orders(&self, symbol: &str) -> Result<Vec<Order>, sqlx::Error> {
+        let mut orders: Vec<Order> = self
+            .tables()
+            .orders
+            .iter()
+            .filter(|o| o.symbol == symbol && o.status.is_working())
+            .cloned()
+            .collect();
+        orders.sort_by_key(|o| (o.created_at, o.id));
+        Ok(orders)
+    }
+
+    async fn upsert_candles(
+        &self,
+        timeframe: &str,
+        candles: &[CandleStick],
+    ) -> Result<(), sqlx::Error> {
+        let mut tables = self.tables();
+        for candle in candles {
+            let key = (
+                candle.symbol.clone(),
+                timeframe.to_string(),
+                candle.timestamp,
+            );
+            tables.prices.insert(key, candle.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_last_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        until: i64,
+        limit: i64,
+    ) -> Result<Vec<CandleStick>, sqlx::Error> {
+        let tables = self.tables();
+        let range = (symbol.to_string(), timeframe.to_string(), i64::MIN)
+            ..=(symbol.to_string(), timeframe.to_string(), until);
+        let mut candles: Vec<CandleStick> = tables
+            .prices
+            .range(range)
+            .rev()
+            .take(limit.max(0) as usize)
+            .map(|(_, candle)| candle.clone())
+            .collect();
+        candles.reverse();
+        Ok(candles)
+    }
+
+    async fn start_session(
+        &self,
+        symbol: &str,
+        mode: &str,
+        initial_balance: Decimal,
+        started_at: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        let mut tables = self.tables();
+        let id = tables.sessions.len() as i64 + 1;
+        tables.sessions.push(Session {
+            id,
+            symbol: symbol.to_string(),
+            mode: mode.to_string(),
+            initial_balance,
+            started_at,
+            ended_at: None,
+        });
+        Ok(id)
+    }
+
+    async fn end_session(&self, id: i64, ended_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        if let Some(session) = self.tables().sessions.get_mut((id - 1) as usize) {
+            session.ended_at = Some(ended_at);
+        }
+        Ok(())
+    }
+
+    async fn get_session(&self, id: i64) -> Result<Option<Session>, sqlx::Error> {
+        Ok(usize::try_from(id - 1)
+            .ok()
+            .and_then(|i| self.tables().sessions.get(i).cloned()))
+    }
+
+    async fn insert_equity_snapshot(
+        &self,
+        session_id: i64,
+        _symbol: &str,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), sqlx::Error> {
+        self.tables()
+            .equity_snapshots
+            .push((session_id, snapshot.clone()));
+        Ok(())
+    }
+
+    async fn get_equity_snapshots(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+        let mut snapshots: Vec<EquitySnapshot> = self
+            .tables()
+            .equity_snapshots
+            .iter()
+            .filter(|(id, _)| *id == session_id)
+            .map(|(_, snapshot)| snapshot.clone())
+            .collect();
+        // stable sort keeps insert order of equal times, like `ORDER BY time, id`
+        snapshots.sort_by_key(|s| s.time);
+        Ok(snapshots)
+    }
+}
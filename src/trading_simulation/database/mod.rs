@@ -1,4 +1,5 @@
 pub mod connection;
 pub mod crud;
-// pub mod objects;
-// pub mod tables;
+pub mod memory;
+pub mod sqlite;
+pub mod store;
@@ -0,0 +1,526 @@
+// SQLite backend: single file (or in-memory) database, no server needed.
+// Decimals are stored as TEXT, queries are checked at runtime only
+use crate::trading_simulation::database::crud::CANDLE_BATCH;
+use crate::trading_simulation::database::store::TradeStore;
+use crate::trading_simulation::exchange::order::Order;
+use crate::utils::objects::{CandleStick, EquitySnapshot, Session, Trade};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use std::str::FromStr;
+
+// `sqlite://bot.db` or `sqlite::memory:`; with `migrate` a missing file is created
+// and its schema brought up to date, otherwise the database is used as is
+pub async fn connect(url: &str, migrate: bool) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(url)?.create_if_missing(migrate);
+    let pool = if url.contains(":memory:") {
+        // every connection would open its own empty database
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?
+    } else {
+        SqlitePoolOptions::new().connect_with(options).await?
+    };
+    println!("✅[SQLITE DB] Connected to database successfully");
+
+    if migrate {
+        sqlx::migrate!("./migrations/sqlite").run(&pool).await?;
+    }
+    Ok(pool)
+}
+
+fn decode<T: FromStr>(row: &SqliteRow, column: &str) -> Result<T, sqlx::Error>
+where
+    T::Err: std::fmt::Display,
+{
+    row.try_get::<String, _>(column)?
+        .parse()
+        .map_err(|e: T::Err| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: e.to_string().into(),
+        })
+}
+
+fn decode_opt<T: FromStr>(row: &SqliteRow, column: &str) -> Result<Option<T>, sqlx::Error>
+where
+    T::Err: std::fmt::Display,
+{
+    match row.try_get::<Option<String>, _>(column)? {
+        Some(_) => decode(row, column).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn trade_from_row(row: &SqliteRow) -> Result<Trade, sqlx::Error> {
+    Ok(Trade {
+        id: row.try_get("id")?,
+        symbol: row.try_get("symbol")?,
+        entry_price: decode(row, "entry_price")?,
+        exit_price: decode_opt(row, "exit_price")?,
+        trade_size: decode(row, "trade_size")?,
+        position_size: decode(row, "position_size")?,
+        pnl: decode_opt(row, "pnl")?,
+        entry_time: row.try_get("entry_time")?,
+        exit_time: row.try_get("exit_time")?,
+        status: row.try_get("status")?,
+        mae: decode_opt(row, "mae")?,
+        mfe: decode_opt(row, "mfe")?,
+    })
+}
+
+fn order_from_row(row: &SqliteRow) -> Result<Order, sqlx::Error> {
+    Ok(Order {
+        id: row.try_get("id")?,
+        symbol: row.try_get("symbol")?,
+        side: decode(row, "side")?,
+        order_type: decode(row, "order_type")?,
+        time_in_force: decode(row, "time_in_force")?,
+        quantity: decode(row, "quantity")?,
+        price: decode_opt(row, "price")?,
+        stop_price: decode_opt(row, "stop_price")?,
+        filled_quantity: decode(row, "filled_quantity")?,
+        avg_fill_price: decode_opt(row, "avg_fill_price")?,
+        triggered: row.try_get("triggered")?,
+        status: decode(row, "status")?,
+        exchange_order_id: row.try_get("exchange_order_id")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn text(value: Option<Decimal>) -> Option<String> {
+    value.map(|v| v.to_string())
+}
+
+impl TradeStore for SqlitePool {
+    async fn is_position_open(&self, symbol: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM trades WHERE symbol = ? AND status = 'OPEN')",
+        )
+        .bind(symbol)
+        .fetch_one(self)
+        .await
+    }
+
+    async fn record_open_trade(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+        entry_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO trades
+            (symbol, entry_price, trade_size, position_size, entry_time, status)
+            VALUES (?, ?, ?, ?, ?, 'OPEN')
+            "#,
+        )
+        .bind(symbol)
+        .bind(entry_price.to_string())
+        .bind(trade_size.to_string())
+        .bind(position_size.to_string())
+        .bind(entry_time)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_close_trade(
+        &self,
+        trade_id: i64,
+        exit_price: Decimal,
+        pnl: Decimal,
+        exit_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE trades
+            SET exit_price = ?, pnl = ?, exit_time = ?, status = 'CLOSED'
+            WHERE id = ?
+            "#,
+        )
+        .bind(exit_price.to_string())
+        .bind(pnl.to_string())
+        .bind(exit_time)
+        .bind(trade_id)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn resize_open_trade(
+        &self,
+        trade_id: i64,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE trades
+            SET entry_price = ?, trade_size = ?, position_size = ?
+            WHERE id = ? AND status = 'OPEN'
+            "#,
+        )
+        .bind(entry_price.to_string())
+        .bind(trade_size.to_string())
+        .bind(position_size.to_string())
+        .bind(trade_id)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_closed_trade(&self, trade: &Trade) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO trades
+            (symbol, entry_price, exit_price, trade_size, position_size, pnl, entry_time, exit_time, status)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'CLOSED')
+            "#,
+        )
+        .bind(&trade.symbol)
+        .bind(trade.entry_price.to_string())
+        .bind(text(trade.exit_price))
+        .bind(trade.trade_size.to_string())
+        .bind(trade.position_size.to_string())
+        .bind(text(trade.pnl))
+        .bind(trade.entry_time)
+        .bind(trade.exit_time)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_trade_excursion(
+        &self,
+        trade_id: i64,
+        mae: Decimal,
+        mfe: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE trades SET mae = ?, mfe = ? WHERE id = ?")
+            .bind(mae.to_string())
+            .bind(mfe.to_string())
+            .bind(trade_id)
+            .execute(self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_open_trade_info(&self, symbol: &str) -> Result<Option<Trade>, sqlx::Error> {
+        sqlx::query("SELECT * FROM trades WHERE symbol = ? AND status = 'OPEN' LIMIT 1")
+            .bind(symbol)
+            .fetch_optional(self)
+            .await?
+            .as_ref()
+            .map(trade_from_row)
+            .transpose()
+    }
+
+    async fn get_closed_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT * FROM trades
+            WHERE symbol = ? AND status = 'CLOSED'
+            ORDER BY exit_time ASC, id ASC
+            "#,
+        )
+        .bind(symbol)
+        .fetch_all(self)
+        .await?
+        .iter()
+        .map(trade_from_row)
+        .collect()
+    }
+
+    async fn get_open_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT * FROM trades
+            WHERE symbol = ? AND status = 'OPEN'
+            ORDER BY entry_time ASC, id ASC
+            "#,
+        )
+        .bind(symbol)
+        .fetch_all(self)
+        .await?
+        .iter()
+        .map(trade_from_row)
+        .collect()
+    }
+
+    async fn get_realized_pnl(&self, symbol: &str) -> Result<Decimal, sqlx::Error> {
+        // summed in Rust, SQLite would add TEXT decimals as floats
+        Ok(self
+            .get_closed_trades(symbol)
+            .await?
+            .iter()
+            .filter_map(|t| t.pnl)
+            .sum())
+    }
+
+    async fn insert_order(&self, order: &Order) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO orders
+            (symbol, side, order_type, time_in_force, quantity, price, stop_price,
+             filled_quantity, avg_fill_price, triggered, status, exchange_order_id,
+             created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(&order.symbol)
+        .bind(order.side.as_str())
+        .bind(order.order_type.as_str())
+        .bind(order.time_in_force.as_str())
+        .bind(order.quantity.to_string())
+        .bind(text(order.price))
+        .bind(text(order.stop_price))
+        .bind(order.filled_quantity.to_string())
+        .bind(text(order.avg_fill_price))
+        .bind(order.triggered)
+        .bind(order.status.as_str())
+        .bind(order.exchange_order_id)
+        .bind(order.created_at)
+        .bind(order.updated_at)
+        .fetch_one(self)
+        .await
+    }
+
+    async fn update_order(&self, order: &Order) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE orders
+            SET filled_quantity = ?, avg_fill_price = ?, triggered = ?, status = ?,
+                exchange_order_id = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(order.filled_quantity.to_string())
+        .bind(text(order.avg_fill_price))
+        .bind(order.triggered)
+        .bind(order.status.as_str())
+        .bind(order.exchange_order_id)
+        .bind(order.updated_at)
+        .bind(order.id)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_order(&self, id: i64) -> Result<Option<Order>, sqlx::Error> {
+        sqlx::query("SELECT * FROM orders WHERE id = ?")
+            .bind(id)
+            .fetch_optional(self)
+            .await?
+            .as_ref()
+            .map(order_from_row)
+            .transpose()
+    }
+
+    async fn get_working_orders(&self, symbol: &str) -> Result<Vec<Order>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT * FROM orders
+            WHERE symbol = ? AND status IN ('NEW', 'PARTIALLY_FILLED')
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(symbol)
+        .fetch_all(self)
+        .await?
+        .iter()
+        .map(order_from_row)
+        .collect()
+    }
+
+    async fn upsert_candles(
+        &self,
+        timeframe: &str,
+        candles: &[CandleStick],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.begin().await?;
+
+        for batch in candles.chunks(CANDLE_BATCH) {
+            QueryBuilder::<Sqlite>::new(
+                "INSERT INTO prices (symbol, timeframe, open_time, open, high, low, close, volume) ",
+            )
+            .push_values(batch, |mut row, candle| {
+                row.push_bind(&candle.symbol)
+                    .push_bind(timeframe)
+                    .push_bind(candle.timestamp)
+                    .push_bind(candle.open)
+                    .push_bind(candle.high)
+                    .push_bind(candle.low)
+                    .push_bind(candle.close)
+                    .push_bind(candle.volume);
+            })
+            .push(
+                r#"
+                ON CONFLICT (symbol, timeframe, open_time) DO UPDATE
+                SET open = excluded.open, high = excluded.high, low = excluded.low,
+                    close = excluded.close, volume = excluded.volume
+                "#,
+            )
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_last_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        until: i64,
+        limit: i64,
+    ) -> Result<Vec<CandleStick>, sqlx::Error> {
+        let mut candles = sqlx::query_as::<_, CandleStick>(
+            r#"
+            SELECT symbol, open, high, low, close, volume, open_time AS timestamp
+            FROM prices
+            WHERE symbol = ? AND timeframe = ? AND open_time <= ?
+            ORDER BY open_time DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(symbol)
+        .bind(timeframe)
+        .bind(until)
+        .bind(limit)
+        .fetch_all(self)
+        .await?;
+
+        candles.reverse();
+        Ok(candles)
+    }
+
+    async fn start_session(
+        &self,
+        symbol: &str,
+        mode: &str,
+        initial_balance: Decimal,
+        started_at: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO sessions (symbol, mode, initial_balance, started_at)
+            VALUES (?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(symbol)
+        .bind(mode)
+        .bind(initial_balance.to_string())
+        .bind(started_at)
+        .fetch_one(self)
+        .await
+    }
+
+    async fn end_session(&self, id: i64, ended_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET ended_at = ? WHERE id = ?")
+            .bind(ended_at)
+            .bind(id)
+            .execute(self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_session(&self, id: i64) -> Result<Option<Session>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT id, symbol, mode, initial_balance, started_at, ended_at
+            FROM sessions
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self)
+        .await?
+        .map(|row| {
+            Ok(Session {
+                id: row.try_get("id")?,
+                symbol: row.try_get("symbol")?,
+                mode: row.try_get("mode")?,
+                initial_balance: decode(&row, "initial_balance")?,
+                started_at: row.try_get("started_at")?,
+                ended_at: row.try_get("ended_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn insert_equity_snapshot(
+        &self,
+        session_id: i64,
+        symbol: &str,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO equity_snapshots
+                (session_id, symbol, time, balance, unrealized_pnl, equity, exposure, price)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(session_id)
+        .bind(symbol)
+        .bind(snapshot.time)
+        .bind(snapshot.balance.to_string())
+        .bind(snapshot.unrealized_pnl.to_string())
+        .bind(snapshot.equity.to_string())
+        .bind(snapshot.exposure.to_string())
+        .bind(snapshot.price.map(|p| p.to_string()))
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_equity_snapshots(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT time, balance, unrealized_pnl, equity, exposure, price
+            FROM equity_snapshots
+            WHERE session_id = ?
+            ORDER BY time, id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(self)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(EquitySnapshot {
+                time: row.try_get("time")?,
+                balance: decode(row, "balance")?,
+                unrealized_pnl: decode(row, "unrealized_pnl")?,
+                equity: decode(row, "equity")?,
+                exposure: decode(row, "exposure")?,
+                price: decode_opt(row, "price")?,
+            })
+        })
+        .collect()
+    }
+}
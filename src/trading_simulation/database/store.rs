@@ -0,0 +1,735 @@
+use crate::trading_simulation::database::connection::{create_db_connection, run_migrations};
+use crate::trading_simulation::database::crud;
+use crate::trading_simulation::database::memory::MemoryStore;
+use crate::trading_simulation::database::sqlite;
+use crate::trading_simulation::exchange::order::Order;
+use crate::utils::objects::{CandleStick, EquitySnapshot, Session, Trade};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, SqlitePool};
+use std::future::Future;
+
+// persistent state of the bot: trades (ledger), orders, market candles and
+// trading sessions. Postgres is the production backend, SQLite and memory
+// let simulations and tests run without a database server
+pub trait TradeStore: Send + Sync {
+    fn is_position_open(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<bool, sqlx::Error>> + Send;
+
+    fn record_open_trade(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+        entry_time: DateTime<Utc>,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn record_close_trade(
+        &self,
+        trade_id: i64,
+        exit_price: Decimal,
+        pnl: Decimal,
+        exit_time: DateTime<Utc>,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // position changed by partial fill, open trade keeps its id and entry time
+    fn resize_open_trade(
+        &self,
+        trade_id: i64,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // closed part of a position split off by partial sell
+    fn insert_closed_trade(
+        &self,
+        trade: &Trade,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // max adverse / favourable excursion of a closed trade
+    fn set_trade_excursion(
+        &self,
+        trade_id: i64,
+        mae: Decimal,
+        mfe: Decimal,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn get_open_trade_info(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Option<Trade>, sqlx::Error>> + Send;
+
+    // oldest exit first
+    fn get_closed_trades(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Vec<Trade>, sqlx::Error>> + Send;
+
+    // oldest entry first
+    fn get_open_trades(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Vec<Trade>, sqlx::Error>> + Send;
+
+    // sum of realized pnl over CLOSED trades
+    fn get_realized_pnl(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Decimal, sqlx::Error>> + Send;
+
+    // returns id assigned to the order
+    fn insert_order(&self, order: &Order) -> impl Future<Output = Result<i64, sqlx::Error>> + Send;
+
+    // order progress: fills, trigger and status transitions
+    fn update_order(&self, order: &Order) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn get_order(&self, id: i64)
+    -> impl Future<Output = Result<Option<Order>, sqlx::Error>> + Send;
+
+    // NEW and PARTIALLY_FILLED orders, oldest first
+    fn get_working_orders(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Vec<Order>, sqlx::Error>> + Send;
+
+    // candles keyed by (symbol, timeframe, open time), stored candles are replaced
+    fn upsert_candles(
+        &self,
+        timeframe: &str,
+        candles: &[CandleStick],
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // latest `limit` candles opened at or before `until`, oldest first
+    fn get_last_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        until: i64,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<CandleStick>, sqlx::Error>> + Send;
+
+    // returns id of the new session
+    fn start_session(
+        &self,
+        symbol: &str,
+        mode: &str,
+        initial_balance: Decimal,
+        started_at: DateTime<Utc>,
+    ) -> impl Future<Output = Result<i64, sqlx::Error>> + Send;
+
+    fn end_session(
+        &self,
+        id: i64,
+        ended_at: DateTime<Utc>,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn get_session(
+        &self,
+        id: i64,
+    ) -> impl Future<Output = Result<Option<Session>, sqlx::Error>> + Send;
+
+    fn insert_equity_snapshot(
+        &self,
+        session_id: i64,
+        symbol: &str,
+        snapshot: &EquitySnapshot,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    // equity curve of a session, oldest first
+    fn get_equity_snapshots(
+        &self,
+        session_id: i64,
+    ) -> impl Future<Output = Result<Vec<EquitySnapshot>, sqlx::Error>> + Send;
+}
+
+impl TradeStore for PgPool {
+    async fn is_position_open(&self, symbol: &str) -> Result<bool, sqlx::Error> {
+        crud::is_position_open(self, symbol).await
+    }
+
+    async fn record_open_trade(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+        entry_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        crud::record_open_trade(
+            self,
+            symbol,
+            entry_price,
+            trade_size,
+            position_size,
+            entry_time,
+        )
+        .await
+    }
+
+    async fn record_close_trade(
+        &self,
+        trade_id: i64,
+        exit_price: Decimal,
+        pnl: Decimal,
+        exit_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        crud::record_close_trade(self, trade_id, exit_price, pnl, exit_time).await
+    }
+
+    async fn resize_open_trade(
+        &self,
+        trade_id: i64,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        crud::resize_open_trade(self, trade_id, entry_price, trade_size, position_size).await
+    }
+
+    async fn insert_closed_trade(&self, trade: &Trade) -> Result<(), sqlx::Error> {
+        crud::insert_closed_trade(self, trade).await
+    }
+
+    async fn set_trade_excursion(
+        &self,
+        trade_id: i64,
+        mae: Decimal,
+        mfe: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        crud::set_trade_excursion(self, trade_id, mae, mfe).await
+    }
+
+    async fn get_open_trade_info(&self, symbol: &str) -> Result<Option<Trade>, sqlx::Error> {
+        crud::get_open_trade_info(self, symbol).await
+    }
+
+    async fn get_closed_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        crud::get_closed_trades(self, symbol).await
+    }
+
+    async fn get_open_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        crud::get_open_trades(self, symbol).await
+    }
+
+    async fn get_realized_pnl(&self, symbol: &str) -> Result<Decimal, sqlx::Error> {
+        crud::get_realized_pnl(self, symbol).await
+    }
+
+    async fn insert_order(&self, order: &Order) -> Result<i64, sqlx::Error> {
+        crud::insert_order(self, order).await
+    }
+
+    async fn update_order(&self, order: &Order) -> Result<(), sqlx::Error> {
+        crud::update_order(self, order).await
+    }
+
+    async fn get_order(&self, id: i64) -> Result<Option<Order>, sqlx::Error> {
+        crud::get_order(self, id).await
+    }
+
+    async fn get_working_orders(&self, symbol: &str) -> Result<Vec<Order>, sqlx::Error> {
+        crud::get_working_orders(self, symbol).await
+    }
+
+    async fn upsert_candles(
+        &self,
+        timeframe: &str,
+        candles: &[CandleStick],
+    ) -> Result<(), sqlx::Error> {
+        crud::upsert_candles(self, timeframe, candles).await
+    }
+
+    async fn get_last_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        until: i64,
+        limit: i64,
+    ) -> Result<Vec<CandleStick>, sqlx::Error> {
+        crud::get_last_candles(self, symbol, timeframe, until, limit).await
+    }
+
+    async fn start_session(
+        &self,
+        symbol: &str,
+        mode: &str,
+        initial_balance: Decimal,
+        started_at: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        crud::start_session(self, symbol, mode, initial_balance, started_at).await
+    }
+
+    async fn end_session(&self, id: i64, ended_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        crud::end_session(self, id, ended_at).await
+    }
+
+    async fn get_session(&self, id: i64) -> Result<Option<Session>, sqlx::Error> {
+        crud::get_session(self, id).await
+    }
+
+    async fn insert_equity_snapshot(
+        &self,
+        session_id: i64,
+        symbol: &str,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), sqlx::Error> {
+        crud::insert_equity_snapshot(self, session_id, symbol, snapshot).await
+    }
+
+    async fn get_equity_snapshots(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+        crud::get_equity_snapshots(self, session_id).await
+    }
+}
+
+// backend chosen at runtime from `DATABASE_URL` scheme:
+// `postgres://..`, `sqlite://path.db` / `sqlite::memory:` or `memory://`
+#[derive(Debug, Clone)]
+pub enum Store {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+    Memory(MemoryStore),
+}
+
+impl Store {
+    // connects to backend of `url` and brings its schema up to date
+    pub async fn connect(url: &str) -> Result<Store, sqlx::Error> {
+        Store::connect_with(url, true).await
+    }
+
+    // connects to backend of `url` without touching its schema, for readers;
+    // migrating is left to `bot db migrate`
+    pub async fn open(url: &str) -> Result<Store, sqlx::Error> {
+        Store::connect_with(url, false).await
+    }
+
+    async fn connect_with(url: &str, migrate: bool) -> Result<Store, sqlx::Error> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            let pool = create_db_connection(url).await.ok_or_else(|| {
+                sqlx::Error::Configuration("Connection to database failed".into())
+            })?;
+            if migrate {
+                run_migrations(&pool).await?;
+            }
+            Ok(Store::Postgres(pool))
+        } else if url.starts_with("sqlite:") {
+            Ok(Store::Sqlite(sqlite::connect(url, migrate).await?))
+        } else if url.starts_with("memory:") {
+            println!("[MEMORY STORE] State is kept in memory and lost on exit");
+            Ok(Store::Memory(MemoryStore::default()))
+        } else {
+            Err(sqlx::Error::Configuration(
+                format!("Unsupported database url scheme: {}", url).into(),
+            ))
+        }
+    }
+
+    // whether `table` exists, so readers can tell a database that was never
+    // migrated from an empty one
+    pub async fn has_table(&self, table: &str) -> Result<bool, sqlx::Error> {
+        match self {
+            Store::Postgres(pool) => {
+                sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+                    .bind(table)
+                    .fetch_one(pool)
+                    .await
+            }
+            Store::Sqlite(pool) => {
+                sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+                )
+                .bind(table)
+                .fetch_one(pool)
+                .await
+            }
+            Store::Memory(_) => Ok(true),
+        }
+    }
+}
+
+// forwards every call to the selected backend
+macro_rules! dispatch {
+    ($store:expr, $backend:ident => $call:expr) => {
+        match $store {
+            Store::Postgres($backend) => $call.await,
+            Store::Sqlite($backend) => $call.await,
+            Store::Memory($backend) => $call.await,
+        }
+    };
+}
+
+impl TradeStore for Store {
+    async fn is_position_open(&self, symbol: &str) -> Result<bool, sqlx::Error> {
+        dispatch!(self, s => s.is_position_open(symbol))
+    }
+
+    async fn record_open_trade(
+        &self,
+        symbol: &str,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+        entry_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => {
+            s.record_open_trade(symbol, entry_price, trade_size, position_size, entry_time)
+        })
+    }
+
+    async fn record_close_trade(
+        &self,
+        trade_id: i64,
+        exit_price: Decimal,
+        pnl: Decimal,
+        exit_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.record_close_trade(trade_id, exit_price, pnl, exit_time))
+    }
+
+    async fn resize_open_trade(
+        &self,
+        trade_id: i64,
+        entry_price: Decimal,
+        trade_size: Decimal,
+        position_size: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.resize_open_trade(trade_id, entry_price, trade_size, position_size))
+    }
+
+    async fn insert_closed_trade(&self, trade: &Trade) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.insert_closed_trade(trade))
+    }
+
+    async fn set_trade_excursion(
+        &self,
+        trade_id: i64,
+        mae: Decimal,
+        mfe: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.set_trade_excursion(trade_id, mae, mfe))
+    }
+
+    async fn get_open_trade_info(&self, symbol: &str) -> Result<Option<Trade>, sqlx::Error> {
+        dispatch!(self, s => s.get_open_trade_info(symbol))
+    }
+
+    async fn get_closed_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        dispatch!(self, s => s.get_closed_trades(symbol))
+    }
+
+    async fn get_open_trades(&self, symbol: &str) -> Result<Vec<Trade>, sqlx::Error> {
+        dispatch!(self, s => s.get_open_trades(symbol))
+    }
+
+    async fn get_realized_pnl(&self, symbol: &str) -> Result<Decimal, sqlx::Error> {
+        dispatch!(self, s => s.get_realized_pnl(symbol))
+    }
+
+    async fn insert_order(&self, order: &Order) -> Result<i64, sqlx::Error> {
+        dispatch!(self, s => s.insert_order(order))
+    }
+
+    async fn update_order(&self, order: &Order) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.update_order(order))
+    }
+
+    async fn get_order(&self, id: i64) -> Result<Option<Order>, sqlx::Error> {
+        dispatch!(self, s => s.get_order(id))
+    }
+
+    async fn get_working_orders(&self, symbol: &str) -> Result<Vec<Order>, sqlx::Error> {
+        dispatch!(self, s => s.get_working_orders(symbol))
+    }
+
+    async fn upsert_candles(
+        &self,
+        timeframe: &str,
+        candles: &[CandleStick],
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.upsert_candles(timeframe, candles))
+    }
+
+    async fn get_last_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        until: i64,
+        limit: i64,
+    ) -> Result<Vec<CandleStick>, sqlx::Error> {
+        dispatch!(self, s => s.get_last_candles(symbol, timeframe, until, limit))
+    }
+
+    async fn start_session(
+        &self,
+        symbol: &str,
+        mode: &str,
+        initial_balance: Decimal,
+        started_at: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        dispatch!(self, s => s.start_session(symbol, mode, initial_balance, started_at))
+    }
+
+    async fn end_session(&self, id: i64, ended_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.end_session(id, ended_at))
+    }
+
+    async fn get_session(&self, id: i64) -> Result<Option<Session>, sqlx::Error> {
+        dispatch!(self, s => s.get_session(id))
+    }
+
+    async fn insert_equity_snapshot(
+        &self,
+        session_id: i64,
+        symbol: &str,
+        snapshot: &EquitySnapshot,
+    ) -> Result<(), sqlx::Error> {
+        dispatch!(self, s => s.insert_equity_snapshot(session_id, symbol, snapshot))
+    }
+
+    async fn get_equity_snapshots(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+        dispatch!(self, s => s.get_equity_snapshots(session_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading_simulation::exchange::order::{OrderSide, OrderStatus};
+    use dotenv::dotenv;
+    use rust_decimal::dec;
+    use std::env;
+
+    fn candle(symbol: &str, timestamp: i64, close: f64) -> CandleStick {
+        CandleStick {
+            symbol: symbol.into(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1.0,
+            timestamp,
+        }
+    }
+
+    // same scenario has to give same answers on every backend
+    async fn exercise(store: &impl TradeStore, symbol: &str) {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let minutes = |m| t0 + chrono::Duration::minutes(m);
+
+        assert!(!store.is_position_open(symbol).await.unwrap());
+        store
+            .record_open_trade(symbol, dec!(100.1), dec!(0.3), dec!(30.03), t0)
+            .await
+            .unwrap();
+        assert!(store.is_position_open(symbol).await.unwrap());
+
+        let open = store.get_open_trade_info(symbol).await.unwrap().unwrap();
+        assert_eq!(open.entry_price, dec!(100.1));
+        assert_eq!(open.entry_time, t0);
+
+        // partial sell splits off closed part, rest is closed later
+        store
+            .insert_closed_trade(&Trade {
+                exit_price: Some(dec!(110.2)),
+                trade_size: dec!(0.1),
+                position_size: dec!(10.01),
+                pnl: Some(dec!(1.01)),
+                exit_time: Some(minutes(5)),
+                status: "CLOSED".into(),
+                ..open.clone()
+            })
+            .await
+            .unwrap();
+        store
+            .resize_open_trade(open.id, dec!(100.1), dec!(0.2), dec!(20.02))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_open_trades(symbol).await.unwrap()[0].trade_size,
+            dec!(0.2)
+        );
+        store
+            .record_close_trade(open.id, dec!(99.1), dec!(-0.2), minutes(9))
+            .await
+            .unwrap();
+
+        assert!(store.get_open_trades(symbol).await.unwrap().is_empty());
+        let closed = store.get_closed_trades(symbol).await.unwrap();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].exit_time, Some(minutes(5)));
+        assert_eq!(closed[1].id, open.id);
+        assert_eq!(store.get_realized_pnl(symbol).await.unwrap(), dec!(0.81));
+        assert_eq!(closed[1].mae, None);
+        store
+            .set_trade_excursion(open.id, dec!(0.4), dec!(1.5))
+            .await
+            .unwrap();
+        let closed = store.get_closed_trades(symbol).await.unwrap();
+        assert_eq!(
+            (closed[1].mae, closed[1].mfe),
+            (Some(dec!(0.4)), Some(dec!(1.5)))
+        );
+        assert_eq!(closed[0].mfe, None);
+
+        let mut order = Order::limit(symbol, OrderSide::Buy, dec!(0.5), dec!(95.25));
+        order.created_at = minutes(10);
+        order.updated_at = minutes(10);
+        order.id = store.insert_order(&order).await.unwrap();
+        order.filled_quantity = dec!(0.2);
+        order.avg_fill_price = Some(dec!(95.25));
+        order.status = OrderStatus::PartiallyFilled;
+        order.updated_at = minutes(11);
+        store.update_order(&order).await.unwrap();
+        assert_eq!(
+            store.get_order(order.id).await.unwrap(),
+            Some(order.clone())
+        );
+        assert_eq!(
+            store.get_working_orders(symbol).await.unwrap(),
+            vec![order.clone()]
+        );
+        order.status = OrderStatus::Canceled;
+        store.update_order(&order).await.unwrap();
+        assert!(store.get_working_orders(symbol).await.unwrap().is_empty());
+
+        let first = t0.timestamp_millis();
+        let batch: Vec<CandleStick> = (0..3)
+            .map(|i| candle(symbol, first + i * 60_000, 100.0 + i as f64))
+            .collect();
+        store.upsert_candles("1m", &batch).await.unwrap();
+        // overlapping batch, stored candle is replaced
+        store
+            .upsert_candles(
+                "1m",
+                &[
+                    candle(symbol, first + 120_000, 1.0),
+                    candle(symbol, first + 180_000, 103.0),
+                ],
+            )
+            .await
+            .unwrap();
+        let last = store
+            .get_last_candles(symbol, "1m", i64::MAX, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            last.iter().map(|c| c.close).collect::<Vec<_>>(),
+            vec![1.0, 103.0]
+        );
+        // window ends at given open time
+        let until = store
+            .get_last_candles(symbol, "1m", first + 60_000, 5)
+            .await
+            .unwrap();
+        assert_eq!(
+            until.iter().map(|c| c.close).collect::<Vec<_>>(),
+            vec![100.0, 101.0]
+        );
+        assert!(
+            store
+                .get_last_candles(symbol, "5m", i64::MAX, 2)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let session = store
+            .start_session(symbol, "paper", dec!(500), t0)
+            .await
+            .unwrap();
+        let snapshots: Vec<EquitySnapshot> = (0..2)
+            .map(|i| EquitySnapshot {
+                time: minutes(i),
+                balance: dec!(500),
+                unrealized_pnl: dec!(0.01) * Decimal::from(i),
+                equity: dec!(500) + dec!(0.01) * Decimal::from(i),
+                exposure: dec!(93561.23) * dec!(0.00535),
+                price: (i > 0).then_some(dec!(93563.10)),
+            })
+            .collect();
+        for snapshot in &snapshots {
+            store
+                .insert_equity_snapshot(session, symbol, snapshot)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            store.get_equity_snapshots(session).await.unwrap(),
+            snapshots
+        );
+        assert!(
+            store
+                .get_equity_snapshots(session + 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store.get_session(session).await.unwrap().unwrap().ended_at,
+            None
+        );
+        store.end_session(session, minutes(60)).await.unwrap();
+        assert_eq!(
+            store.get_session(session).await.unwrap(),
+            Some(Session {
+                id: session,
+                symbol: symbol.to_string(),
+                mode: "paper".to_string(),
+                initial_balance: dec!(500),
+                started_at: t0,
+                ended_at: Some(minutes(60)),
+            })
+        );
+        assert_eq!(store.get_session(session + 1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_stores_without_server() {
+        let memory = Store::connect("memory://").await.unwrap();
+        assert!(matches!(memory, Store::Memory(_)));
+        exercise(&memory, "STOREUSDT").await;
+
+        let sqlite = Store::connect("sqlite::memory:").await.unwrap();
+        assert!(matches!(sqlite, Store::Sqlite(_)));
+        exercise(&sqlite, "STOREUSDT").await;
+
+        assert!(Store::connect("mysql://localhost/bot").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_postgres_store() {
+        dotenv().ok();
+        let Ok(database_url) = env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL not set, skipping Postgres test");
+            return;
+        };
+        let store = Store::connect(&database_url).await.unwrap();
+        let Store::Postgres(pool) = &store else {
+            panic!("Expected postgres store");
+        };
+
+        let symbol = "PGSTOREUSDT";
+        let clear = async || {
+            for table in ["trades", "orders", "prices", "sessions"] {
+                sqlx::query(&format!("DELETE FROM {} WHERE symbol = $1", table))
+                    .bind(symbol)
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+        };
+        clear().await;
+        exercise(&store, symbol).await;
+        clear().await;
+    }
+}
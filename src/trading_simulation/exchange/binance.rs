@@ -0,0 +1,519 @@
+use crate::trading_simulation::database::store::{Store, TradeStore};
+use crate::trading_simulation::exchange::executor::{ExecutionError, Executor};
+use crate::trading_simulation::exchange::ledger::record_fill;
+use crate::trading_simulation::exchange::order::{
+    Fill, Order, OrderSide, OrderStatus, OrderType, TimeInForce,
+};
+use crate::trading_simulation::network::api::error::ApiError;
+use crate::trading_simulation::network::api::exchange_info::{SymbolFilters, parse_number};
+use crate::utils::clock::Clock;
+use crate::utils::objects::CandleStick;
+
+use binance_spot_connector_rust::http::Credentials;
+use binance_spot_connector_rust::http::request::Request;
+use binance_spot_connector_rust::hyper::BinanceHttpClient;
+use binance_spot_connector_rust::trade;
+use binance_spot_connector_rust::trade::order::{NewOrderResponseType, Side};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// signed requests older than this are refused by Binance
+const RECV_WINDOW_MS: u64 = 5000;
+
+// fill prices are rounded to Binance price precision, average fill price is not,
+// so quote quantity already booked can be recovered from it
+const PRICE_DP: u32 = 8;
+
+// order does not exist on exchange (query of unknown client order id)
+const NO_SUCH_ORDER: i64 = -2013;
+
+// order state as returned by new order (FULL), query order and cancel order endpoints,
+// user data stream execution reports carry the same information
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReport {
+    pub order_id: i64,
+    pub status: String,
+    pub executed_qty: String,
+    #[serde(rename = "cummulativeQuoteQty")] // sic, Binance spelling
+    pub cumulative_quote_qty: String,
+    pub transact_time: Option<i64>,
+    pub update_time: Option<i64>,
+}
+
+// real spot orders, signed with HMAC-SHA256 API keys; fills are discovered
+// by polling order status once per candle batch and from user data stream.
+// clones share `booking` lock, so the same execution is never booked twice
+#[derive(Clone)]
+pub struct BinanceExecutor {
+    api_url: String,
+    credentials: Credentials,
+    store: Store,
+    filters: SymbolFilters,
+    booking: Arc<Mutex<()>>,
+}
+
+impl BinanceExecutor {
+    pub fn new(
+        api_url: &str,
+        api_key: &str,
+        secret_key: &str,
+        store: Store,
+        filters: SymbolFilters,
+    ) -> Self {
+        BinanceExecutor {
+            api_url: api_url.to_string(),
+            credentials: Credentials::from_hmac(api_key, secret_key),
+            store,
+            filters,
+            booking: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    pub fn api_key(&self) -> &str {
+        &self.credentials.api_key
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    // keys are read from `.env`, never from config file
+    pub fn from_env(api_url: &str, store: Store, filters: SymbolFilters) -> Result<Self, String> {
+        let key = |name: &str| {
+            env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| format!("{} must be set inside .env file for live mode", name))
+        };
+        Ok(BinanceExecutor::new(
+            api_url,
+            &key("BINANCE_API_KEY")?,
+            &key("BINANCE_SECRET_KEY")?,
+            store,
+            filters,
+        ))
+    }
+
+    async fn send(&self, request: impl Into<Request>) -> Result<OrderReport, ApiError> {
+        let client =
+            BinanceHttpClient::with_url(&self.api_url).credentials(self.credentials.clone());
+        let body = client.send(request).await?.into_body_str().await?;
+        if let Some(err) = ApiError::from_body(200, &body) {
+            return Err(err);
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    fn new_order_request(&self, order: &Order) -> trade::new_order::NewOrder {
+        let side = match order.side {
+            OrderSide::Buy => Side::Buy,
+            OrderSide::Sell => Side::Sell,
+        };
+        let mut request =
+            trade::new_order(&order.symbol, side, binance_order_type(order.order_type))
+                .quantity(order.quantity)
+                .new_client_order_id(&order.client_order_id())
+                .new_order_resp_type(NewOrderResponseType::Full)
+                .recv_window(RECV_WINDOW_MS);
+
+        // market orders (plain or triggered) take no time in force
+        if matches!(order.order_type, OrderType::Limit | OrderType::StopLimit) {
+            request = request.time_in_force(match order.time_in_force {
+                TimeInForce::Gtc => trade::order::TimeInForce::Gtc,
+                TimeInForce::Ioc => trade::order::TimeInForce::Ioc,
+                TimeInForce::Fok => trade::order::TimeInForce::Fok,
+            });
+        }
+        if let Some(price) = order.price {
+            request = request.price(price);
+        }
+        if let Some(stop_price) = order.stop_price {
+            request = request.stop_price(stop_price);
+        }
+        request
+    }
+
+    async fn apply(
+        &self,
+        order: &mut Order,
+        report: &OrderReport,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Fill>, ExecutionError> {
+        let _booking = self.booking.lock().await;
+        self.apply_locked(order, report, now).await
+    }
+
+    // applies exchange report to stored order, books new fill into `trades`;
+    // `order` is refreshed first, the other report source may have moved it already
+    async fn apply_locked(
+        &self,
+        order: &mut Order,
+        report: &OrderReport,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Fill>, ExecutionError> {
+        if let Some(stored) = self.store.get_order(order.id).await? {
+            *order = stored;
+        }
+
+        let fill = apply_report(order, report, now)?;
+        if let Some(fill) = &fill {
+            record_fill(&self.store, fill).await?;
+        }
+        self.store.update_order(order).await?;
+        Ok(fill)
+    }
+
+    // applies report to order `id`, returns stored order before and after,
+    // None when no such order was placed by the bot
+    pub async fn sync_order(
+        &self,
+        id: i64,
+        report: &OrderReport,
+        clock: &dyn Clock,
+    ) -> Result<Option<(Order, Order)>, ExecutionError> {
+        let _booking = self.booking.lock().await;
+        let Some(before) = self.store.get_order(id).await? else {
+            return Ok(None);
+        };
+        let mut after = before.clone();
+        self.apply_locked(&mut after, report, clock.now()).await?;
+        Ok(Some((before, after)))
+    }
+}
+
+// our order types in Binance spot vocabulary
+fn binance_order_type(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "MARKET",
+        OrderType::Limit => "LIMIT",
+        OrderType::StopMarket => "STOP_LOSS",
+        OrderType::StopLimit => "STOP_LOSS_LIMIT",
+    }
+}
+
+// PENDING_CANCEL keeps current status until exchange settles it
+fn binance_status(status: &str) -> Option<OrderStatus> {
+    match status {
+        "NEW" => Some(OrderStatus::New),
+        "PARTIALLY_FILLED" => Some(OrderStatus::PartiallyFilled),
+        "FILLED" => Some(OrderStatus::Filled),
+        "CANCELED" => Some(OrderStatus::Canceled),
+        "REJECTED" => Some(OrderStatus::Rejected),
+        "EXPIRED" | "EXPIRED_IN_MATCH" => Some(OrderStatus::Expired),
+        _ => None,
+    }
+}
+
+// exchange reports cumulative quantities, fill is the difference to what is
+// already stored; its price is derived from cumulative quote quantity
+fn apply_report(
+    order: &mut Order,
+    report: &OrderReport,
+    now: DateTime<Utc>,
+) -> Result<Option<Fill>, ApiError> {
+    let executed = parse_number("executedQty", &report.executed_qty)?;
+    let quote = parse_number("cummulativeQuoteQty", &report.cumulative_quote_qty)?;
+    let time = report
+        .transact_time
+        .or(report.update_time)
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or(now);
+
+    order.exchange_order_id = Some(report.order_id);
+    let fill = if executed > order.filled_quantity {
+        let quantity = executed - order.filled_quantity;
+        let booked = order.avg_fill_price.unwrap_or_default() * order.filled_quantity;
+        order.filled_quantity = executed;
+        order.avg_fill_price = Some((quote / executed).normalize());
+        Some(Fill {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: ((quote - booked) / quantity).round_dp(PRICE_DP).normalize(),
+            quantity,
+            time,
+        })
+    } else {
+        None
+    };
+
+    if let Some(status) = binance_status(&report.status) {
+        order.status = status;
+    }
+    order.updated_at = order.updated_at.max(time);
+    Ok(fill)
+}
+
+impl Executor for BinanceExecutor {
+    fn filters(&self) -> &SymbolFilters {
+        &self.filters
+    }
+
+    // order is stored before it is sent, so its client order id can always be
+    // looked up on exchange, even when response is lost
+    async fn submit(&self, mut order: Order, clock: &dyn Clock) -> Result<Order, ExecutionError> {
+        order.created_at = clock.now();
+        order.updated_at = order.created_at;
+        order.id = self.store.insert_order(&order).await?;
+
+        match self.send(self.new_order_request(&order)).await {
+            Ok(report) => {
+                self.apply(&mut order, &report, clock.now()).await?;
+                println!("[ORDER] Placed on Binance {}", order);
+            }
+            Err(e) if e.is_transient() => {
+                eprintln!(
+                    "[ORDER] No response for {}, status will be polled: {}",
+                    order, e
+                );
+            }
+            Err(e) => {
+                order.status = OrderStatus::Rejected;
+                self.store.update_order(&order).await?;
+                eprintln!("[ORDER] Binance rejected {}: {}", order, e);
+            }
+        }
+        Ok(order)
+    }
+
+    async fn cancel(&self, mut order: Order, clock: &dyn Clock) -> Result<Order, ExecutionError> {
+        let request = trade::cancel_order(&order.symbol)
+            .orig_client_order_id(&order.client_order_id())
+            .recv_window(RECV_WINDOW_MS);
+
+        match self.send(request).await {
+            Ok(report) => {
+                self.apply(&mut order, &report, clock.now()).await?;
+                println!("[ORDER] Canceled {}", order);
+            }
+            // e.g. filled in the meantime, next poll brings it up to date
+            Err(e) => eprintln!("[ORDER] Failed to cancel {}: {}", order, e),
+        }
+        Ok(order)
+    }
+
+    async fn working_orders(&self, symbol: &str) -> Result<Vec<Order>, ExecutionError> {
+        Ok(self.store.get_working_orders(symbol).await?)
+    }
+
+    // candles are not needed, exchange is asked for status of every working order
+    async fn on_candles(
+        &self,
+        symbol: &str,
+        _candles: &[CandleStick],
+        clock: &dyn Clock,
+    ) -> Result<Vec<Fill>, ExecutionError> {
+        let mut fills = Vec::new();
+
+        for mut order in self.working_orders(symbol).await? {
+            let before = order.status;
+            let request = trade::get_order(symbol)
+                .orig_client_order_id(&order.client_order_id())
+                .recv_window(RECV_WINDOW_MS);
+
+            match self.send(request).await {
+                Ok(report) => {
+                    fills.extend(self.apply(&mut order, &report, clock.now()).await?);
+                }
+                // submit never reached exchange
+                Err(ApiError::Binance { code, .. })
+                    if code == NO_SUCH_ORDER && order.exchange_order_id.is_none() =>
+                {
+                    order.status = OrderStatus::Rejected;
+                    self.store.update_order(&order).await?;
+                }
+                Err(e) => {
+                    eprintln!("[ORDER] Failed to poll {}: {}", order, e);
+                    continue;
+                }
+            }
+
+            if order.status != before {
+                println!("[ORDER] {}", order);
+            }
+        }
+
+        Ok(fills)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_binance::{Fault, MOCK_API_KEY, MOCK_SECRET_KEY, MockBinance};
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::trading_simulation::network::api::exchange_info::parse_exchange_info;
+    use crate::utils::clock::SystemClock;
+    use rust_decimal::{Decimal, dec};
+
+    fn executor(mock: &MockBinance, secret_key: &str) -> BinanceExecutor {
+        let body = std::fs::read_to_string("tests/fixtures/binance/exchange_info_btcusdt.json")
+            .expect("Missing fixture");
+        let filters = parse_exchange_info("BTCUSDT", &body).unwrap();
+        BinanceExecutor::new(
+            &mock.http_url(),
+            MOCK_API_KEY,
+            secret_key,
+            Store::Memory(MemoryStore::default()),
+            filters,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_market_order_placed_and_booked() {
+        let symbol = "LIVEMKTUSDT";
+        let mock = MockBinance::start().await;
+        let exchange = executor(&mock, MOCK_SECRET_KEY);
+
+        let order = exchange
+            .submit(
+                Order::market(symbol, OrderSide::Buy, dec!(0.005)),
+                &SystemClock,
+            )
+            .await
+            .expect("Failed to submit order");
+
+        // mock fills market orders at last kline close
+        let close: Decimal = mock.klines().last().unwrap()[4]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.avg_fill_price, Some(close));
+        assert!(order.exchange_order_id.is_some());
+
+        let sent = mock.order(&order.client_order_id()).unwrap();
+        assert_eq!(sent["type"], "MARKET");
+        assert_eq!(sent["origQty"], "0.005");
+
+        let open = exchange.store().get_open_trades(symbol).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(
+            (open[0].entry_price, open[0].trade_size),
+            (close, dec!(0.005))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_signature_rejected() {
+        let symbol = "LIVESIGUSDT";
+        let mock = MockBinance::start().await;
+        let exchange = executor(&mock, "wrong-secret");
+
+        let order = exchange
+            .submit(
+                Order::market(symbol, OrderSide::Buy, dec!(0.005)),
+                &SystemClock,
+            )
+            .await
+            .expect("Failed to submit order");
+
+        assert_eq!(order.status, OrderStatus::Rejected);
+        assert!(mock.order(&order.client_order_id()).is_none());
+        assert!(exchange.working_orders(symbol).await.unwrap().is_empty());
+        assert!(
+            exchange
+                .store()
+                .get_open_trades(symbol)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resting_order_polled_and_canceled() {
+        let symbol = "LIVELMTUSDT";
+        let mock = MockBinance::start().await;
+        let exchange = executor(&mock, MOCK_SECRET_KEY);
+
+        let order = exchange
+            .submit(
+                Order::limit(symbol, OrderSide::Buy, dec!(0.01), dec!(90000)),
+                &SystemClock,
+            )
+            .await
+            .expect("Failed to submit order");
+        assert_eq!(order.status, OrderStatus::New);
+        assert_eq!(
+            mock.order(&order.client_order_id()).unwrap()["type"],
+            "LIMIT"
+        );
+
+        // two partial executions, fill prices are recovered from cumulative quote quantity
+        mock.execute_order(&order.client_order_id(), dec!(0.004), dec!(90000));
+        mock.execute_order(&order.client_order_id(), dec!(0.002), dec!(89990));
+        let fills = exchange
+            .on_candles(symbol, &[], &SystemClock)
+            .await
+            .unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].quantity, dec!(0.006));
+
+        mock.execute_order(&order.client_order_id(), dec!(0.001), dec!(89000));
+        let fills = exchange
+            .on_candles(symbol, &[], &SystemClock)
+            .await
+            .unwrap();
+        assert_eq!(
+            (fills[0].quantity, fills[0].price),
+            (dec!(0.001), dec!(89000))
+        );
+
+        let order = exchange.working_orders(symbol).await.unwrap().remove(0);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled_quantity, dec!(0.007));
+
+        let order = exchange.cancel(order, &SystemClock).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Canceled);
+        assert!(exchange.working_orders(symbol).await.unwrap().is_empty());
+
+        let open = exchange.store().get_open_trades(symbol).await.unwrap();
+        assert_eq!(open[0].trade_size, dec!(0.007));
+        // 0.006 filled at 89996.67 on average, then 0.001 at 89000
+        assert!((open[0].position_size - dec!(628.98)).abs() < dec!(0.000001));
+    }
+
+    #[tokio::test]
+    async fn test_lost_submit_resolved_by_polling() {
+        let symbol = "LIVELOSTUSDT";
+        let mock = MockBinance::start().await;
+        let exchange = executor(&mock, MOCK_SECRET_KEY);
+
+        // gateway error: order state unknown, it stays working until polled
+        mock.fail_next(Fault::http(502, "Bad Gateway"));
+        let order = exchange
+            .submit(
+                Order::market(symbol, OrderSide::Buy, dec!(0.005)),
+                &SystemClock,
+            )
+            .await
+            .expect("Failed to submit order");
+        assert_eq!(order.status, OrderStatus::New);
+        assert_eq!(exchange.working_orders(symbol).await.unwrap().len(), 1);
+
+        // exchange never saw it
+        exchange
+            .on_candles(symbol, &[], &SystemClock)
+            .await
+            .unwrap();
+        assert!(exchange.working_orders(symbol).await.unwrap().is_empty());
+        assert!(
+            exchange
+                .store()
+                .get_open_trades(symbol)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use crate::trading_simulation::exchange::order::{Fill, Order};
+use crate::trading_simulation::network::api::exchange_info::SymbolFilters;
+use crate::utils::clock::Clock;
+use crate::utils::objects::CandleStick;
+
+use std::future::Future;
+
+pub type ExecutionError = Box<dyn std::error::Error + Send + Sync>;
+
+// where strategy orders go: paper exchange simulates fills on candles,
+// Binance executor places them on the real exchange. Both keep `orders` table
+// up to date and book their fills into `trades` through the ledger
+pub trait Executor: Send + Sync {
+    // rules orders have to comply with
+    fn filters(&self) -> &SymbolFilters;
+
+    fn submit(
+        &self,
+        order: Order,
+        clock: &dyn Clock,
+    ) -> impl Future<Output = Result<Order, ExecutionError>> + Send;
+
+    // returned order may still be working when exchange could not cancel it
+    fn cancel(
+        &self,
+        order: Order,
+        clock: &dyn Clock,
+    ) -> impl Future<Output = Result<Order, ExecutionError>> + Send;
+
+    fn working_orders(
+        &self,
+        symbol: &str,
+    ) -> impl Future<Output = Result<Vec<Order>, ExecutionError>> + Send;
+
+    // brings working orders up to date once new candles arrive,
+    // returns fills booked since last call
+    fn on_candles(
+        &self,
+        symbol: &str,
+        candles: &[CandleStick],
+        clock: &dyn Clock,
+    ) -> impl Future<Output = Result<Vec<Fill>, ExecutionError>> + Send;
+}
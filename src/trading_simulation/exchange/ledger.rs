@@ -0,0 +1,151 @@
+use crate::trading_simulation::database::store::TradeStore;
+use crate::trading_simulation::exchange::order::{Fill, OrderSide};
+use crate::utils::objects::Trade;
+
+// books a fill into `trades`, one long position per symbol:
+// buys open the position or add to it (entry price becomes volume weighted average),
+// sells close it, partial sells split closed part into its own trade row
+pub async fn record_fill(store: &impl TradeStore, fill: &Fill) -> Result<(), sqlx::Error> {
+    let open_trade = store.get_open_trade_info(&fill.symbol).await?;
+
+    match (fill.side, open_trade) {
+        (OrderSide::Buy, None) => {
+            store
+                .record_open_trade(
+                    &fill.symbol,
+                    fill.price,
+                    fill.quantity,
+                    fill.price * fill.quantity,
+                    fill.time,
+                )
+                .await?;
+
+            println!(
+                "[BUY] Long trade open for {} at price {}, quantity {}",
+                fill.symbol, fill.price, fill.quantity
+            );
+        }
+
+        (OrderSide::Buy, Some(open_trade)) => {
+            let trade_size = open_trade.trade_size + fill.quantity;
+            let position_size = open_trade.position_size + fill.price * fill.quantity;
+            store
+                .resize_open_trade(
+                    open_trade.id,
+                    position_size / trade_size,
+                    trade_size,
+                    position_size,
+                )
+                .await?;
+
+            println!(
+                "[BUY] Added {} to long trade for {} at price {}",
+                fill.quantity, fill.symbol, fill.price
+            );
+        }
+
+        (OrderSide::Sell, Some(open_trade)) => {
+            // never sell more than is held, long only
+            let quantity = fill.quantity.min(open_trade.trade_size);
+            // asumming no fees
+            let pnl = (fill.price - open_trade.entry_price) * quantity;
+
+            if quantity == open_trade.trade_size {
+                store
+                    .record_close_trade(open_trade.id, fill.price, pnl, fill.time)
+                    .await?;
+            } else {
+                let remaining = open_trade.trade_size - quantity;
+                store
+                    .insert_closed_trade(&Trade {
+                        exit_price: Some(fill.price),
+                        trade_size: quantity,
+                        position_size: open_trade.entry_price * quantity,
+                        pnl: Some(pnl),
+                        exit_time: Some(fill.time),
+                        status: "CLOSED".into(),
+                        ..open_trade.clone()
+                    })
+                    .await?;
+                store
+                    .resize_open_trade(
+                        open_trade.id,
+                        open_trade.entry_price,
+                        remaining,
+                        open_trade.entry_price * remaining,
+                    )
+                    .await?;
+            }
+
+            println!(
+                "[SOLD] Closed {} of long trade for {} at price {}, PnL: {:.2}",
+                quantity, fill.symbol, fill.price, pnl
+            );
+        }
+
+        (OrderSide::Sell, None) => {
+            eprintln!(
+                "[LEDGER] Sell fill of {} {} without open trade",
+                fill.quantity, fill.symbol
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use chrono::{DateTime, Duration};
+    use rust_decimal::{Decimal, dec};
+
+    #[tokio::test]
+    async fn test_fills_split_into_trades() {
+        let store = MemoryStore::default();
+        let symbol = "LEDGERUSDT";
+
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let fill = |minute: i64, side: OrderSide, price: Decimal, quantity: Decimal| Fill {
+            order_id: 0,
+            symbol: symbol.into(),
+            side,
+            price,
+            quantity,
+            time: t0 + Duration::minutes(minute),
+        };
+
+        // two buys average into one position
+        record_fill(&store, &fill(0, OrderSide::Buy, dec!(100), dec!(0.3)))
+            .await
+            .unwrap();
+        record_fill(&store, &fill(1, OrderSide::Buy, dec!(104), dec!(0.1)))
+            .await
+            .unwrap();
+        let open = store.get_open_trades(symbol).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(
+            (open[0].entry_price, open[0].trade_size),
+            (dec!(101), dec!(0.4))
+        );
+        assert_eq!(open[0].entry_time, t0);
+
+        // partial sell splits off a closed trade, rest of position stays open
+        record_fill(&store, &fill(2, OrderSide::Sell, dec!(110), dec!(0.1)))
+            .await
+            .unwrap();
+        let open = store.get_open_trades(symbol).await.unwrap();
+        assert_eq!(open[0].trade_size, dec!(0.3));
+
+        record_fill(&store, &fill(3, OrderSide::Sell, dec!(99), dec!(0.3)))
+            .await
+            .unwrap();
+        assert!(store.get_open_trades(symbol).await.unwrap().is_empty());
+
+        let closed = store.get_closed_trades(symbol).await.unwrap();
+        let pnl: Vec<_> = closed.iter().map(|t| t.pnl.unwrap()).collect();
+        assert_eq!(pnl, [dec!(0.9), dec!(-0.6)]);
+        assert_eq!(store.get_realized_pnl(symbol).await.unwrap(), dec!(0.3));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod binance;
+pub mod executor;
+pub mod ledger;
+pub mod order;
+pub mod paper;
+pub mod reconcile;
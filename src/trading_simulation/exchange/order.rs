@@ -0,0 +1,303 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
+use std::fmt;
+use std::str::FromStr;
+
+// order vocabulary follows Binance spot API, so the same strings end up
+// in `orders` table, logs and exchange requests
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Market,
+    Limit,
+    StopMarket, // becomes market order once `stop_price` is touched
+    StopLimit,  // becomes limit order at `price` once `stop_price` is touched
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    Gtc, // good till canceled, rests until filled
+    Ioc, // immediate or cancel, unfilled remainder expires
+    Fok, // fill or kill, expires unless filled completely at once
+}
+
+// NEW -> PARTIALLY_FILLED -> FILLED / CANCELED / EXPIRED,
+// REJECTED when exchange refused the order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Expired,
+    Rejected,
+}
+
+impl OrderSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+}
+
+impl OrderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderType::Market => "MARKET",
+            OrderType::Limit => "LIMIT",
+            OrderType::StopMarket => "STOP_MARKET",
+            OrderType::StopLimit => "STOP_LIMIT",
+        }
+    }
+}
+
+impl TimeInForce {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Fok => "FOK",
+        }
+    }
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::New => "NEW",
+            OrderStatus::PartiallyFilled => "PARTIALLY_FILLED",
+            OrderStatus::Filled => "FILLED",
+            OrderStatus::Canceled => "CANCELED",
+            OrderStatus::Expired => "EXPIRED",
+            OrderStatus::Rejected => "REJECTED",
+        }
+    }
+
+    // order can still be filled or canceled
+    pub fn is_working(&self) -> bool {
+        matches!(self, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+}
+
+impl FromStr for OrderSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BUY" => Ok(OrderSide::Buy),
+            "SELL" => Ok(OrderSide::Sell),
+            other => Err(format!("Invalid order side: {}", other)),
+        }
+    }
+}
+
+impl FromStr for OrderType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MARKET" => Ok(OrderType::Market),
+            "LIMIT" => Ok(OrderType::Limit),
+            "STOP_MARKET" => Ok(OrderType::StopMarket),
+            "STOP_LIMIT" => Ok(OrderType::StopLimit),
+            other => Err(format!("Invalid order type: {}", other)),
+        }
+    }
+}
+
+impl FromStr for TimeInForce {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GTC" => Ok(TimeInForce::Gtc),
+            "IOC" => Ok(TimeInForce::Ioc),
+            "FOK" => Ok(TimeInForce::Fok),
+            other => Err(format!("Invalid time in force: {}", other)),
+        }
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NEW" => Ok(OrderStatus::New),
+            "PARTIALLY_FILLED" => Ok(OrderStatus::PartiallyFilled),
+            "FILLED" => Ok(OrderStatus::Filled),
+            "CANCELED" => Ok(OrderStatus::Canceled),
+            "EXPIRED" => Ok(OrderStatus::Expired),
+            "REJECTED" => Ok(OrderStatus::Rejected),
+            other => Err(format!("Invalid order status: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: i64, // 0 until stored
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,      // limit price
+    pub stop_price: Option<Decimal>, // trigger of stop orders
+    pub filled_quantity: Decimal,
+    pub avg_fill_price: Option<Decimal>,
+    pub triggered: bool,
+    pub status: OrderStatus,
+    // id assigned by Binance, only orders sent to real exchange have one
+    pub exchange_order_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    // market time up to which order was matched, candles closing later can fill it
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Order {
+    fn new(symbol: &str, side: OrderSide, order_type: OrderType, quantity: Decimal) -> Self {
+        Order {
+            id: 0,
+            symbol: symbol.to_string(),
+            side,
+            order_type,
+            time_in_force: TimeInForce::Gtc,
+            quantity,
+            price: None,
+            stop_price: None,
+            filled_quantity: Decimal::ZERO,
+            avg_fill_price: None,
+            triggered: false,
+            status: OrderStatus::New,
+            exchange_order_id: None,
+            created_at: DateTime::UNIX_EPOCH,
+            updated_at: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    pub fn market(symbol: &str, side: OrderSide, quantity: Decimal) -> Self {
+        Order::new(symbol, side, OrderType::Market, quantity)
+    }
+
+    // strategy places market orders only atm, other types are available to paper exchange
+    #[allow(dead_code)]
+    pub fn limit(symbol: &str, side: OrderSide, quantity: Decimal, price: Decimal) -> Self {
+        Order {
+            price: Some(price),
+            ..Order::new(symbol, side, OrderType::Limit, quantity)
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn stop_market(symbol: &str, side: OrderSide, quantity: Decimal, stop: Decimal) -> Self {
+        Order {
+            stop_price: Some(stop),
+            ..Order::new(symbol, side, OrderType::StopMarket, quantity)
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn stop_limit(
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        stop: Decimal,
+        price: Decimal,
+    ) -> Self {
+        Order {
+            price: Some(price),
+            stop_price: Some(stop),
+            ..Order::new(symbol, side, OrderType::StopLimit, quantity)
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn remaining(&self) -> Decimal {
+        self.quantity - self.filled_quantity
+    }
+
+    // `newClientOrderId` on exchange, ties exchange order back to `orders` row
+    pub fn client_order_id(&self) -> String {
+        format!("bot-{}", self.id)
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} {} {} {} {}",
+            self.id,
+            self.order_type.as_str(),
+            self.side.as_str(),
+            self.quantity,
+            self.symbol,
+            self.time_in_force.as_str()
+        )?;
+        if let Some(stop) = self.stop_price {
+            write!(f, " stop {}", stop)?;
+        }
+        if let Some(price) = self.price {
+            write!(f, " @ {}", price)?;
+        }
+        write!(f, " [{}]", self.status.as_str())
+    }
+}
+
+fn decode<T: FromStr<Err = String>>(row: &PgRow, column: &str) -> Result<T, sqlx::Error> {
+    row.try_get::<String, _>(column)?
+        .parse()
+        .map_err(|e: String| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: e.into(),
+        })
+}
+
+impl<'r> FromRow<'r, PgRow> for Order {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Order {
+            id: row.try_get::<i32, _>("id")? as i64,
+            symbol: row.try_get("symbol")?,
+            side: decode(row, "side")?,
+            order_type: decode(row, "order_type")?,
+            time_in_force: decode(row, "time_in_force")?,
+            quantity: row.try_get("quantity")?,
+            price: row.try_get("price")?,
+            stop_price: row.try_get("stop_price")?,
+            filled_quantity: row.try_get("filled_quantity")?,
+            avg_fill_price: row.try_get("avg_fill_price")?,
+            triggered: row.try_get("triggered")?,
+            status: decode(row, "status")?,
+            exchange_order_id: row.try_get("exchange_order_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+// single execution against market data, turned into `trades` rows by the ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: i64,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub time: DateTime<Utc>,
+}
@@ -0,0 +1,367 @@
+use crate::trading_simulation::database::store::{Store, TradeStore};
+use crate::trading_simulation::exchange::executor::{ExecutionError, Executor};
+use crate::trading_simulation::exchange::ledger::record_fill;
+use crate::trading_simulation::exchange::order::{
+    Fill, Order, OrderSide, OrderStatus, TimeInForce,
+};
+use crate::trading_simulation::network::api::exchange_info::SymbolFilters;
+use crate::utils::clock::Clock;
+use crate::utils::objects::CandleStick;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+
+// candle with exact prices, what the paper exchange matches orders against
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub volume: Decimal,
+    pub open_time: DateTime<Utc>,
+    pub close_time: DateTime<Utc>,
+}
+
+// matches one order against one candle, order is updated in place.
+// intra-candle path is unknown, so fills are conservative:
+// - market orders fill at candle open
+// - limit orders fill at open when it is already better than limit, otherwise at limit
+//   price once low (buy) / high (sell) touches it
+// - stop orders trigger when high (buy) / low (sell) touches stop, gaps trigger at open;
+//   stop-limit fills on trigger candle only if trigger price satisfies its limit
+// `liquidity` caps filled quantity per candle (participation in candle volume)
+pub fn match_order(order: &mut Order, bar: &Bar, liquidity: Option<Decimal>) -> Option<Fill> {
+    // candle must close after the order reached the exchange, and each candle is used once
+    if !order.status.is_working() || bar.close_time <= order.updated_at {
+        return None;
+    }
+    let time = bar.open_time.max(order.updated_at);
+    order.updated_at = bar.close_time;
+
+    // stop orders wait for the trigger, time in force applies from the moment they trigger
+    let mut reference = bar.open;
+    let mut just_triggered = false;
+    if let Some(stop) = order.stop_price.filter(|_| !order.triggered) {
+        let touched = match order.side {
+            OrderSide::Buy => bar.high >= stop,
+            OrderSide::Sell => bar.low <= stop,
+        };
+        if !touched {
+            return None;
+        }
+        order.triggered = true;
+        just_triggered = true;
+        reference = match order.side {
+            OrderSide::Buy => bar.open.max(stop),
+            OrderSide::Sell => bar.open.min(stop),
+        };
+    }
+
+    let price = match order.price {
+        None => Some(reference),
+        Some(limit) => {
+            let marketable = match order.side {
+                OrderSide::Buy => reference <= limit,
+                OrderSide::Sell => reference >= limit,
+            };
+            let touched = match order.side {
+                OrderSide::Buy => bar.low <= limit,
+                OrderSide::Sell => bar.high >= limit,
+            };
+            if marketable {
+                Some(reference)
+            } else if touched && !just_triggered {
+                Some(limit)
+            } else {
+                None
+            }
+        }
+    };
+
+    let remaining = order.remaining();
+    let quantity = match price {
+        Some(_) => liquidity.map_or(remaining, |l| l.min(remaining)),
+        None => Decimal::ZERO,
+    };
+    let fill = match (price, order.time_in_force) {
+        (Some(_), TimeInForce::Fok) if quantity < remaining => None,
+        (Some(price), _) if quantity > Decimal::ZERO => Some(Fill {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            quantity,
+            time,
+        }),
+        _ => None,
+    };
+
+    if let Some(fill) = &fill {
+        let filled = order.filled_quantity + fill.quantity;
+        let notional = order.avg_fill_price.unwrap_or_default() * order.filled_quantity
+            + fill.price * fill.quantity;
+        order.avg_fill_price = Some(notional / filled);
+        order.filled_quantity = filled;
+        order.status = if order.remaining().is_zero() {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+    }
+    // IOC and FOK get a single chance, whatever is left expires
+    if order.status.is_working() && order.time_in_force != TimeInForce::Gtc {
+        order.status = OrderStatus::Expired;
+    }
+
+    fill
+}
+
+// in-process exchange for paper trading: orders live in `orders` table of the store,
+// working ones are matched against each new candle and fills are booked into `trades`
+pub struct PaperExchange {
+    store: Store,
+    filters: SymbolFilters,
+    interval_ms: i64,
+    // max share of candle volume one order can take, unlimited when not set
+    participation_rate: Option<Decimal>,
+}
+
+impl PaperExchange {
+    pub fn new(
+        store: Store,
+        filters: SymbolFilters,
+        interval_ms: i64,
+        participation_rate: Option<Decimal>,
+    ) -> Self {
+        PaperExchange {
+            store,
+            filters,
+            interval_ms,
+            participation_rate,
+        }
+    }
+
+    fn bar(&self, candle: &CandleStick) -> Option<Bar> {
+        let price = |p: f64| Decimal::from_f64(p).map(|d| self.filters.round_price(d));
+        let open_time = DateTime::from_timestamp_millis(candle.timestamp)?;
+        Some(Bar {
+            open: price(candle.open)?,
+            high: price(candle.high)?,
+            low: price(candle.low)?,
+            volume: Decimal::from_f64(candle.volume)?,
+            open_time,
+            close_time: open_time + chrono::Duration::milliseconds(self.interval_ms),
+        })
+    }
+}
+
+impl Executor for PaperExchange {
+    fn filters(&self) -> &SymbolFilters {
+        &self.filters
+    }
+
+    async fn submit(&self, mut order: Order, clock: &dyn Clock) -> Result<Order, ExecutionError> {
+        order.created_at = clock.now();
+        order.updated_at = order.created_at;
+        order.id = self.store.insert_order(&order).await?;
+        println!("[ORDER] Submitted {}", order);
+        Ok(order)
+    }
+
+    async fn cancel(&self, mut order: Order, clock: &dyn Clock) -> Result<Order, ExecutionError> {
+        order.status = OrderStatus::Canceled;
+        order.updated_at = order.updated_at.max(clock.now());
+        self.store.update_order(&order).await?;
+        println!("[ORDER] Canceled {}", order);
+        Ok(order)
+    }
+
+    async fn working_orders(&self, symbol: &str) -> Result<Vec<Order>, ExecutionError> {
+        Ok(self.store.get_working_orders(symbol).await?)
+    }
+
+    // matches working orders against candles closed since they were last matched,
+    // fills are booked into `trades` before they are returned
+    async fn on_candles(
+        &self,
+        symbol: &str,
+        candles: &[CandleStick],
+        _clock: &dyn Clock,
+    ) -> Result<Vec<Fill>, ExecutionError> {
+        let mut fills = Vec::new();
+
+        for mut order in self.working_orders(symbol).await? {
+            let before = order.clone();
+            for bar in candles.iter().filter_map(|c| self.bar(c)) {
+                let liquidity = self
+                    .participation_rate
+                    .map(|rate| self.filters.floor_qty(bar.volume * rate));
+                if let Some(fill) = match_order(&mut order, &bar, liquidity) {
+                    record_fill(&self.store, &fill).await?;
+                    fills.push(fill);
+                }
+                if !order.status.is_working() {
+                    break;
+                }
+            }
+
+            if order != before {
+                self.store.update_order(&order).await?;
+                if order.status != before.status {
+                    println!("[ORDER] {}", order);
+                }
+            }
+        }
+
+        Ok(fills)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal::dec;
+
+    fn bar(minute: i64, open: Decimal, high: Decimal, low: Decimal) -> Bar {
+        let open_time =
+            DateTime::from_timestamp_millis(1_735_689_600_000).unwrap() + Duration::minutes(minute);
+        Bar {
+            open,
+            high,
+            low,
+            volume: dec!(10),
+            open_time,
+            close_time: open_time + Duration::minutes(1),
+        }
+    }
+
+    // order submitted at close of the first (minute 0) candle
+    fn submitted(order: Order) -> Order {
+        let at = bar(0, dec!(0), dec!(0), dec!(0)).close_time;
+        Order {
+            id: 1,
+            created_at: at,
+            updated_at: at,
+            ..order
+        }
+    }
+
+    #[test]
+    fn test_market_order_fills_at_next_open() {
+        let mut order = submitted(Order::market("BTCUSDT", OrderSide::Buy, dec!(2)));
+
+        // candle that closed before submission can't fill it
+        assert!(match_order(&mut order, &bar(0, dec!(99), dec!(101), dec!(98)), None).is_none());
+
+        let fill = match_order(&mut order, &bar(1, dec!(100), dec!(102), dec!(99)), None).unwrap();
+        assert_eq!((fill.price, fill.quantity), (dec!(100), dec!(2)));
+        assert_eq!(fill.time, bar(1, dec!(0), dec!(0), dec!(0)).open_time);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.avg_fill_price, Some(dec!(100)));
+    }
+
+    #[test]
+    fn test_limit_order_rests_until_touched() {
+        let mut buy = submitted(Order::limit("BTCUSDT", OrderSide::Buy, dec!(1), dec!(95)));
+        assert!(match_order(&mut buy, &bar(1, dec!(100), dec!(101), dec!(96)), None).is_none());
+        assert_eq!(buy.status, OrderStatus::New);
+
+        let fill = match_order(&mut buy, &bar(2, dec!(97), dec!(98), dec!(94)), None).unwrap();
+        assert_eq!(fill.price, dec!(95));
+
+        // price gapping through the limit fills at better open
+        let mut sell = submitted(Order::limit("BTCUSDT", OrderSide::Sell, dec!(1), dec!(105)));
+        let fill = match_order(&mut sell, &bar(1, dec!(107), dec!(108), dec!(104)), None).unwrap();
+        assert_eq!(fill.price, dec!(107));
+    }
+
+    #[test]
+    fn test_stop_orders_trigger() {
+        let mut stop = submitted(Order::stop_market(
+            "BTCUSDT",
+            OrderSide::Sell,
+            dec!(1),
+            dec!(90),
+        ));
+        assert!(match_order(&mut stop, &bar(1, dec!(95), dec!(96), dec!(91)), None).is_none());
+        assert!(!stop.triggered);
+
+        // gap below stop fills at open, not at stop price
+        let fill = match_order(&mut stop, &bar(2, dec!(88), dec!(89), dec!(85)), None).unwrap();
+        assert_eq!(fill.price, dec!(88));
+        assert_eq!(stop.status, OrderStatus::Filled);
+
+        // triggered at 105 above 104 limit: rests as limit, fills on next candle
+        let mut stop_limit = submitted(Order::stop_limit(
+            "BTCUSDT",
+            OrderSide::Buy,
+            dec!(1),
+            dec!(105),
+            dec!(104),
+        ));
+        assert!(
+            match_order(
+                &mut stop_limit,
+                &bar(1, dec!(103), dec!(106), dec!(102)),
+                None
+            )
+            .is_none()
+        );
+        assert!(stop_limit.triggered);
+        let fill = match_order(
+            &mut stop_limit,
+            &bar(2, dec!(106), dec!(107), dec!(103)),
+            None,
+        )
+        .unwrap();
+        assert_eq!(fill.price, dec!(104));
+    }
+
+    #[test]
+    fn test_partial_fills_and_time_in_force() {
+        let mut gtc = submitted(Order::market("BTCUSDT", OrderSide::Buy, dec!(3)));
+        let first = match_order(
+            &mut gtc,
+            &bar(1, dec!(100), dec!(101), dec!(99)),
+            Some(dec!(2)),
+        );
+        assert_eq!(first.unwrap().quantity, dec!(2));
+        assert_eq!(gtc.status, OrderStatus::PartiallyFilled);
+        let second = match_order(
+            &mut gtc,
+            &bar(2, dec!(103), dec!(104), dec!(102)),
+            Some(dec!(2)),
+        );
+        assert_eq!(second.unwrap().quantity, dec!(1));
+        assert_eq!(gtc.status, OrderStatus::Filled);
+        assert_eq!(gtc.avg_fill_price, Some(dec!(101)));
+
+        let mut ioc = submitted(
+            Order::market("BTCUSDT", OrderSide::Buy, dec!(3)).with_time_in_force(TimeInForce::Ioc),
+        );
+        let fill = match_order(
+            &mut ioc,
+            &bar(1, dec!(100), dec!(101), dec!(99)),
+            Some(dec!(2)),
+        );
+        assert_eq!(fill.unwrap().quantity, dec!(2));
+        assert_eq!(ioc.status, OrderStatus::Expired);
+        assert_eq!(ioc.filled_quantity, dec!(2));
+
+        let mut fok = submitted(
+            Order::market("BTCUSDT", OrderSide::Buy, dec!(3)).with_time_in_force(TimeInForce::Fok),
+        );
+        assert!(
+            match_order(
+                &mut fok,
+                &bar(1, dec!(100), dec!(101), dec!(99)),
+                Some(dec!(2))
+            )
+            .is_none()
+        );
+        assert_eq!(fok.status, OrderStatus::Expired);
+        assert_eq!(fok.filled_quantity, dec!(0));
+    }
+}
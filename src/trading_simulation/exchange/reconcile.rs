@@ -0,0 +1,493 @@
+use crate::trading_simulation::database::store::TradeStore;
+use crate::trading_simulation::exchange::binance::{BinanceExecutor, OrderReport};
+use crate::trading_simulation::exchange::executor::{ExecutionError, Executor};
+use crate::trading_simulation::exchange::order::OrderStatus;
+use crate::trading_simulation::network::api::exchange_info::{SymbolFilters, parse_number};
+use crate::trading_simulation::network::websocket::user_stream::{
+    AccountPosition, ExecutionReport, LISTEN_KEY_KEEPALIVE, UserEvent, create_listen_key,
+    renew_listen_key, stream_user_data,
+};
+use crate::utils::clock::Clock;
+
+use rust_decimal::Decimal;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// difference between exchange account and local `orders`/`trades` records
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    // order not placed by the bot, e.g. manual trade on the same account
+    UnknownOrder {
+        client_order_id: String,
+        side: String,
+        execution: String,
+        quantity: String,
+    },
+    // exchange reports less filled than already booked
+    FillRegression {
+        order_id: i64,
+        booked: Decimal,
+        exchange: Decimal,
+    },
+    // order already closed locally, exchange changed it afterwards
+    StatusConflict {
+        order_id: i64,
+        local: OrderStatus,
+        exchange: String,
+    },
+    // exchange refused an order bot considered working
+    Rejected {
+        order_id: i64,
+        reason: String,
+    },
+    // account holds less of base asset than open position in `trades`
+    BalanceShortfall {
+        asset: String,
+        held: Decimal,
+        position: Decimal,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::UnknownOrder {
+                client_order_id,
+                side,
+                execution,
+                quantity,
+            } => write!(
+                f,
+                "order {} ({} {}) not placed by bot: {}",
+                client_order_id, side, quantity, execution
+            ),
+            Mismatch::FillRegression {
+                order_id,
+                booked,
+                exchange,
+            } => write!(
+                f,
+                "order #{} booked {} filled, exchange reports {}",
+                order_id, booked, exchange
+            ),
+            Mismatch::StatusConflict {
+                order_id,
+                local,
+                exchange,
+            } => write!(
+                f,
+                "order #{} is {} locally, exchange reports {}",
+                order_id,
+                local.as_str(),
+                exchange
+            ),
+            Mismatch::Rejected { order_id, reason } => {
+                write!(f, "order #{} rejected by exchange: {}", order_id, reason)
+            }
+            Mismatch::BalanceShortfall {
+                asset,
+                held,
+                position,
+            } => write!(
+                f,
+                "account holds {} {}, open position is {}",
+                held, asset, position
+            ),
+        }
+    }
+}
+
+// `orders` row id from client order id `bot-<id>`
+fn local_order_id(client_order_id: &str) -> Option<i64> {
+    client_order_id.strip_prefix("bot-")?.parse().ok()
+}
+
+// books what the report says happened to a bot order, same way as status polling,
+// and reports everything that does not add up
+pub async fn reconcile_execution(
+    executor: &BinanceExecutor,
+    report: &ExecutionReport,
+    clock: &dyn Clock,
+) -> Result<Vec<Mismatch>, ExecutionError> {
+    if report.symbol != executor.filters().symbol {
+        return Ok(Vec::new());
+    }
+
+    let order_report = OrderReport {
+        order_id: report.order_id,
+        status: report.order_status.clone(),
+        executed_qty: report.cumulative_quantity.clone(),
+        cumulative_quote_qty: report.cumulative_quote_quantity.clone(),
+        transact_time: Some(report.transaction_time),
+        update_time: None,
+    };
+    let synced = match local_order_id(report.placed_client_order_id()) {
+        Some(id) => executor.sync_order(id, &order_report, clock).await?,
+        None => None,
+    };
+    let Some((before, after)) = synced else {
+        return Ok(vec![Mismatch::UnknownOrder {
+            client_order_id: report.placed_client_order_id().to_string(),
+            side: report.side.clone(),
+            execution: report.execution_type.clone(),
+            quantity: report.quantity.clone(),
+        }]);
+    };
+
+    let mut mismatches = Vec::new();
+    let executed = parse_number("z", &report.cumulative_quantity)?;
+    if executed < before.filled_quantity {
+        mismatches.push(Mismatch::FillRegression {
+            order_id: before.id,
+            booked: before.filled_quantity,
+            exchange: executed,
+        });
+    }
+    if !before.status.is_working() && after.status != before.status {
+        mismatches.push(Mismatch::StatusConflict {
+            order_id: before.id,
+            local: before.status,
+            exchange: report.order_status.clone(),
+        });
+    }
+    if report.execution_type == "REJECTED" && before.status.is_working() {
+        mismatches.push(Mismatch::Rejected {
+            order_id: before.id,
+            reason: report.reject_reason.clone(),
+        });
+    }
+
+    Ok(mismatches)
+}
+
+// base asset on account (free + locked) has to cover open position,
+// holding more is fine, account may have other funds
+pub async fn reconcile_balances(
+    store: &impl TradeStore,
+    filters: &SymbolFilters,
+    position: &AccountPosition,
+) -> Result<Vec<Mismatch>, ExecutionError> {
+    let Some(balance) = position
+        .balances
+        .iter()
+        .find(|b| b.asset == filters.base_asset)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let held = parse_number("f", &balance.free)? + parse_number("l", &balance.locked)?;
+    let open = store
+        .get_open_trade_info(&filters.symbol)
+        .await?
+        .map_or(Decimal::ZERO, |trade| trade.trade_size);
+
+    if held < open {
+        return Ok(vec![Mismatch::BalanceShortfall {
+            asset: balance.asset.clone(),
+            held,
+            position: open,
+        }]);
+    }
+    Ok(Vec::new())
+}
+
+async fn process_event(executor: &BinanceExecutor, event: &UserEvent, clock: &dyn Clock) {
+    let mismatches = match event {
+        UserEvent::Execution(report) => reconcile_execution(executor, report, clock).await,
+        UserEvent::AccountPosition(position) => {
+            reconcile_balances(executor.store(), executor.filters(), position).await
+        }
+    };
+
+    match mismatches {
+        Ok(mismatches) => {
+            for mismatch in mismatches {
+                eprintln!("[ALERT] {}", mismatch);
+            }
+        }
+        Err(e) => eprintln!("[RECONCILE] Failed to process {:?}: {}", event, e),
+    }
+}
+
+// one listen key session, returns when stream ends
+async fn run_user_stream(
+    executor: &BinanceExecutor,
+    ws_url: &str,
+    clock: &dyn Clock,
+) -> Result<(), ExecutionError> {
+    let listen_key = create_listen_key(executor.api_url(), executor.api_key()).await?;
+
+    let (event_tx, mut event_rx) = mpsc::channel::<UserEvent>(100);
+    let (url, key) = (ws_url.to_string(), listen_key.clone());
+    let stream = tokio::spawn(async move { stream_user_data(&url, &key, event_tx).await });
+    println!("[USER STREAM] Listening for account events");
+
+    let mut keepalive = tokio::time::interval(LISTEN_KEY_KEEPALIVE);
+    keepalive.tick().await; // first tick is immediate
+
+    loop {
+        tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => process_event(executor, &event, clock).await,
+                None => break, // stream task is gone
+            },
+            _ = keepalive.tick() => {
+                if let Err(e) = renew_listen_key(executor.api_url(), executor.api_key(), &listen_key).await {
+                    eprintln!("[USER STREAM] Failed to renew listen key: {}", e);
+                }
+            }
+        }
+    }
+
+    match stream.await {
+        Ok(result) => Ok(result?),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// consumes user data stream of live account for the whole session, reconnecting
+// with a fresh listen key whenever it drops; events missed in between are
+// caught up by status polling of working orders
+pub fn spawn_reconciliation(
+    executor: BinanceExecutor,
+    ws_url: String,
+    clock: Arc<dyn Clock>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match run_user_stream(&executor, &ws_url, clock.as_ref()).await {
+                Ok(()) => eprintln!("[USER STREAM] Stream ended, reconnecting"),
+                Err(e) => eprintln!("[USER STREAM] {}, reconnecting", e),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_binance::{MOCK_API_KEY, MOCK_SECRET_KEY, MockBinance};
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::trading_simulation::database::store::Store;
+    use crate::trading_simulation::exchange::order::{Order, OrderSide};
+    use crate::trading_simulation::network::api::exchange_info::parse_exchange_info;
+    use crate::trading_simulation::network::websocket::user_stream::AssetBalance;
+    use crate::utils::clock::SystemClock;
+    use chrono::Utc;
+    use rust_decimal::dec;
+
+    fn setup(mock: &MockBinance, symbol: &str) -> BinanceExecutor {
+        // rules of made up symbol are borrowed from BTCUSDT
+        let body = std::fs::read_to_string("tests/fixtures/binance/exchange_info_btcusdt.json")
+            .expect("Missing fixture");
+        let filters = SymbolFilters {
+            symbol: symbol.into(),
+            ..parse_exchange_info("BTCUSDT", &body).unwrap()
+        };
+        BinanceExecutor::new(
+            &mock.http_url(),
+            MOCK_API_KEY,
+            MOCK_SECRET_KEY,
+            Store::Memory(MemoryStore::default()),
+            filters,
+        )
+    }
+
+    fn report(
+        symbol: &str,
+        client_order_id: &str,
+        execution: &str,
+        status: &str,
+    ) -> ExecutionReport {
+        ExecutionReport {
+            event_time: 0,
+            symbol: symbol.into(),
+            client_order_id: client_order_id.into(),
+            orig_client_order_id: String::new(),
+            side: "BUY".into(),
+            order_type: "LIMIT".into(),
+            quantity: "0.01".into(),
+            execution_type: execution.into(),
+            order_status: status.into(),
+            reject_reason: "NONE".into(),
+            order_id: 1,
+            last_quantity: "0".into(),
+            cumulative_quantity: "0".into(),
+            last_price: "0".into(),
+            transaction_time: Utc::now().timestamp_millis(),
+            cumulative_quote_quantity: "0".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_books_executions() {
+        let mock = MockBinance::start().await;
+        let symbol = "RECONSTREAMUSDT";
+        let executor = setup(&mock, symbol);
+
+        let reconciliation =
+            spawn_reconciliation(executor.clone(), mock.ws_url(), Arc::new(SystemClock));
+        mock.wait_for_user_stream().await;
+
+        let order = executor
+            .submit(
+                Order::limit(symbol, OrderSide::Buy, dec!(0.01), dec!(90000)),
+                &SystemClock,
+            )
+            .await
+            .unwrap();
+        mock.execute_order(&order.client_order_id(), dec!(0.004), dec!(90000));
+
+        // booked from executionReport alone, working orders are never polled here
+        let mut open = Vec::new();
+        for _ in 0..100 {
+            open = executor.store().get_open_trades(symbol).await.unwrap();
+            if !open.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        reconciliation.abort();
+
+        assert_eq!(open.len(), 1);
+        assert_eq!(
+            (open[0].trade_size, open[0].entry_price),
+            (dec!(0.004), dec!(90000))
+        );
+        let order = executor.working_orders(symbol).await.unwrap().remove(0);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled_quantity, dec!(0.004));
+
+        // polling afterwards finds nothing new to book
+        assert!(
+            executor
+                .on_candles(symbol, &[], &SystemClock)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mismatches_raised() {
+        let mock = MockBinance::start().await;
+        let symbol = "RECONALERTUSDT";
+        let executor = setup(&mock, symbol);
+        let store = executor.store().clone();
+
+        // trade made by hand on the same account
+        let manual = report(symbol, "web_8f2c", "TRADE", "FILLED");
+        assert!(matches!(
+            reconcile_execution(&executor, &manual, &SystemClock)
+                .await
+                .unwrap()[..],
+            [Mismatch::UnknownOrder { .. }]
+        ));
+        // other symbols are not ours to check
+        let other = report("ETHUSDT", "web_8f2c", "TRADE", "FILLED");
+        assert!(
+            reconcile_execution(&executor, &other, &SystemClock)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // order considered refused by exchange shows up as placed
+        let mut refused = Order::limit(symbol, OrderSide::Buy, dec!(0.01), dec!(90000));
+        refused.status = OrderStatus::Rejected;
+        refused.id = store.insert_order(&refused).await.unwrap();
+        let placed = report(symbol, &refused.client_order_id(), "NEW", "NEW");
+        assert_eq!(
+            reconcile_execution(&executor, &placed, &SystemClock)
+                .await
+                .unwrap(),
+            [Mismatch::StatusConflict {
+                order_id: refused.id,
+                local: OrderStatus::Rejected,
+                exchange: "NEW".into(),
+            }]
+        );
+
+        // working order refused by exchange
+        let mut working = Order::limit(symbol, OrderSide::Buy, dec!(0.01), dec!(90000));
+        working.id = store.insert_order(&working).await.unwrap();
+        let rejected = ExecutionReport {
+            reject_reason: "INSUFFICIENT_BALANCE".into(),
+            ..report(symbol, &working.client_order_id(), "REJECTED", "REJECTED")
+        };
+        assert!(matches!(
+            reconcile_execution(&executor, &rejected, &SystemClock)
+                .await
+                .unwrap()[..],
+            [Mismatch::Rejected { .. }]
+        ));
+
+        // exchange reports less than already booked
+        let mut filled = Order::limit(symbol, OrderSide::Buy, dec!(0.01), dec!(90000));
+        filled.id = store.insert_order(&filled).await.unwrap();
+        let trade = ExecutionReport {
+            cumulative_quantity: "0.004".into(),
+            cumulative_quote_quantity: "360".into(),
+            ..report(
+                symbol,
+                &filled.client_order_id(),
+                "TRADE",
+                "PARTIALLY_FILLED",
+            )
+        };
+        assert!(
+            reconcile_execution(&executor, &trade, &SystemClock)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let stale = ExecutionReport {
+            cumulative_quantity: "0.002".into(),
+            cumulative_quote_quantity: "180".into(),
+            ..report(
+                symbol,
+                &filled.client_order_id(),
+                "TRADE",
+                "PARTIALLY_FILLED",
+            )
+        };
+        assert!(matches!(
+            reconcile_execution(&executor, &stale, &SystemClock)
+                .await
+                .unwrap()[..],
+            [Mismatch::FillRegression { .. }]
+        ));
+
+        // 0.004 open position, account has to hold at least that much BTC
+        let position = |free: &str| AccountPosition {
+            event_time: 0,
+            balances: vec![AssetBalance {
+                asset: "BTC".into(),
+                free: free.into(),
+                locked: "0".into(),
+            }],
+        };
+        assert_eq!(
+            reconcile_balances(&store, executor.filters(), &position("0.001"))
+                .await
+                .unwrap(),
+            [Mismatch::BalanceShortfall {
+                asset: "BTC".into(),
+                held: dec!(0.001),
+                position: dec!(0.004),
+            }]
+        );
+        assert!(
+            reconcile_balances(&store, executor.filters(), &position("0.5"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
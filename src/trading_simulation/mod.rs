@@ -1,5 +1,9 @@
 pub mod database;
+pub mod exchange;
+pub mod monitoring;
 pub mod network;
+pub mod replay;
+pub mod report_query;
 pub mod run_simulation;
 pub mod strategy;
 pub mod trade_analysis_report;
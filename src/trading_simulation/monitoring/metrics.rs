@@ -0,0 +1,208 @@
+use crate::trading_simulation::exchange::order::OrderSide;
+use crate::trading_simulation::trade_analysis_report::{AnalysisReport, float};
+
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// state of a running trading loop exposed to Prometheus, updated by the loop
+// and the market feed, rendered on every scrape of `/metrics`
+#[derive(Debug)]
+pub struct BotMetrics {
+    symbol: String,
+    values: Mutex<MetricValues>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MetricValues {
+    equity: f64,
+    balance: f64,
+    realized_pnl: f64,
+    unrealized_pnl: f64,
+    exposure: f64,
+    open_positions: usize,
+    last_candle_close: Option<DateTime<Utc>>,
+    last_fetch_seconds: f64,
+    fetch_seconds_sum: f64,
+    fetches: u64,
+    fetch_errors: u64,
+    buy_signals: u64,
+    sell_signals: u64,
+    db_errors: u64,
+}
+
+impl BotMetrics {
+    pub fn new(symbol: &str) -> Self {
+        BotMetrics {
+            symbol: symbol.to_string(),
+            values: Mutex::new(MetricValues::default()),
+        }
+    }
+
+    // account state of the latest report
+    pub fn record_report(&self, report: &AnalysisReport) {
+        let current = report.current();
+        let mut v = self.values.lock().unwrap();
+        v.equity = float(current.equity);
+        v.balance = float(current.balance);
+        v.realized_pnl = float(report.pnl_stats.gross_pnl);
+        v.unrealized_pnl = float(current.unrealized_pnl);
+        v.exposure = float(current.exposure);
+        v.open_positions = report.open_positions.len();
+    }
+
+    // close time of the latest candle the strategy ran on
+    pub fn record_candle(&self, close_time: DateTime<Utc>) {
+        self.values.lock().unwrap().last_candle_close = Some(close_time);
+    }
+
+    // one klines request of the market feed, retries included
+    pub fn record_fetch(&self, elapsed: Duration, ok: bool) {
+        let mut v = self.values.lock().unwrap();
+        v.last_fetch_seconds = elapsed.as_secs_f64();
+        v.fetch_seconds_sum += elapsed.as_secs_f64();
+        v.fetches += 1;
+        if !ok {
+            v.fetch_errors += 1;
+        }
+    }
+
+    // market order submitted by the strategy
+    pub fn record_signal(&self, side: OrderSide) {
+        let mut v = self.values.lock().unwrap();
+        match side {
+            OrderSide::Buy => v.buy_signals += 1,
+            OrderSide::Sell => v.sell_signals += 1,
+        }
+    }
+
+    pub fn record_db_error(&self) {
+        self.values.lock().unwrap().db_errors += 1;
+    }
+
+    // Prometheus text exposition format, candle age is measured against `now`
+    pub fn render(&self, now: DateTime<Utc>) -> String {
+        let v = self.values.lock().unwrap().clone();
+        let label = format!("symbol=\"{}\"", self.symbol);
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, f64)]| {
+            let _ = writeln!(out, "# HELP trading_bot_{name} {help}");
+            let _ = writeln!(out, "# TYPE trading_bot_{name} {kind}");
+            for (suffix, value) in samples {
+                let _ = writeln!(out, "trading_bot_{name}{suffix} {value}");
+            }
+        };
+
+        let all = format!("{{{label}}}");
+        metric(
+            "equity",
+            "gauge",
+            "Balance plus unrealized PnL in quote currency.",
+            &[(&all, v.equity)],
+        );
+        metric(
+            "balance",
+            "gauge",
+            "Initial balance plus realized PnL in quote currency.",
+            &[(&all, v.balance)],
+        );
+        metric(
+            "realized_pnl",
+            "gauge",
+            "PnL of closed trades in quote currency.",
+            &[(&all, v.realized_pnl)],
+        );
+        metric(
+            "unrealized_pnl",
+            "gauge",
+            "PnL of open positions marked to the latest close.",
+            &[(&all, v.unrealized_pnl)],
+        );
+        metric(
+            "exposure",
+            "gauge",
+            "Market value of open positions in quote currency.",
+            &[(&all, v.exposure)],
+        );
+        metric(
+            "open_positions",
+            "gauge",
+            "Number of open trades.",
+            &[(&all, v.open_positions as f64)],
+        );
+        if let Some(close) = v.last_candle_close {
+            let age = (now - close).num_milliseconds() as f64 / 1000.0;
+            metric(
+                "last_candle_age_seconds",
+                "gauge",
+                "Time since close of the latest processed candle.",
+                &[(&all, age)],
+            );
+        }
+        metric(
+            "last_fetch_duration_seconds",
+            "gauge",
+            "Duration of the latest market data request.",
+            &[(&all, v.last_fetch_seconds)],
+        );
+        let sum = format!("_sum{all}");
+        let count = format!("_count{all}");
+        metric(
+            "fetch_duration_seconds",
+            "summary",
+            "Duration of market data requests.",
+            &[(&sum, v.fetch_seconds_sum), (&count, v.fetches as f64)],
+        );
+        metric(
+            "fetch_errors_total",
+            "counter",
+            "Market data requests that failed after retries.",
+            &[(&all, v.fetch_errors as f64)],
+        );
+        let buy = format!("{{{label},side=\"buy\"}}");
+        let sell = format!("{{{label},side=\"sell\"}}");
+        metric(
+            "signals_total",
+            "counter",
+            "Market orders submitted by the strategy.",
+            &[(&buy, v.buy_signals as f64), (&sell, v.sell_signals as f64)],
+        );
+        metric(
+            "db_errors_total",
+            "counter",
+            "Failed store operations of the trading loop.",
+            &[(&all, v.db_errors as f64)],
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_exposition() {
+        let metrics = BotMetrics::new("BTCUSDT");
+        let close = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        metrics.record_candle(close);
+        metrics.record_fetch(Duration::from_millis(250), true);
+        metrics.record_fetch(Duration::from_millis(750), false);
+        metrics.record_signal(OrderSide::Buy);
+        metrics.record_db_error();
+
+        let text = metrics.render(close + chrono::Duration::seconds(90));
+        assert!(text.contains("# TYPE trading_bot_equity gauge\n"));
+        assert!(text.contains("trading_bot_last_candle_age_seconds{symbol=\"BTCUSDT\"} 90\n"));
+        assert!(
+            text.contains("trading_bot_last_fetch_duration_seconds{symbol=\"BTCUSDT\"} 0.75\n")
+        );
+        assert!(text.contains("trading_bot_fetch_duration_seconds_sum{symbol=\"BTCUSDT\"} 1\n"));
+        assert!(text.contains("trading_bot_fetch_duration_seconds_count{symbol=\"BTCUSDT\"} 2\n"));
+        assert!(text.contains("trading_bot_fetch_errors_total{symbol=\"BTCUSDT\"} 1\n"));
+        assert!(text.contains("trading_bot_signals_total{symbol=\"BTCUSDT\",side=\"buy\"} 1\n"));
+        assert!(text.contains("trading_bot_signals_total{symbol=\"BTCUSDT\",side=\"sell\"} 0\n"));
+        assert!(text.contains("trading_bot_db_errors_total{symbol=\"BTCUSDT\"} 1\n"));
+    }
+}
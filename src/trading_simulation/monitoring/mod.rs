@@ -0,0 +1,252 @@
+pub mod metrics;
+pub mod status;
+
+use crate::trading_simulation::monitoring::metrics::BotMetrics;
+use crate::trading_simulation::monitoring::status::{BotStatus, Indicators, RunConfig};
+use crate::trading_simulation::trade_analysis_report::AnalysisReport;
+use crate::utils::objects::Trade;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+const DEFAULT_TRADES_LIMIT: usize = 20;
+
+// shared by the trading loop (writer) and request handlers (readers)
+#[derive(Debug, Clone)]
+pub struct MonitorState {
+    pub metrics: Arc<BotMetrics>,
+    pub status: Arc<BotStatus>,
+}
+
+// embedded HTTP server of `bot trade`: `/metrics` is scraped by Prometheus,
+// read-only JSON under `/api` is for dashboards
+pub fn router(state: MonitorState) -> Router {
+    Router::new()
+        .route("/metrics", get(prometheus_metrics))
+        .route("/api/config", get(config))
+        .route("/api/position", get(position))
+        .route("/api/balance", get(balance))
+        .route("/api/trades", get(recent_trades))
+        .route("/api/indicators", get(indicators))
+        .route("/api/report", get(report))
+        .with_state(state)
+}
+
+// serves on `listener` the caller bound before starting the run, so a taken port
+// fails it right away; server lives until the returned task is aborted
+pub fn spawn_http_server(listener: TcpListener, state: MonitorState) -> JoinHandle<()> {
+    let app = router(state);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("[HTTP] Server stopped: {e}");
+        }
+    })
+}
+
+async fn prometheus_metrics(State(state): State<MonitorState>) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(Utc::now()),
+    )
+}
+
+type Rejection = (StatusCode, Json<serde_json::Value>);
+type ApiResult<T> = Result<Json<T>, Rejection>;
+
+// snapshots exist only once the loop processed its first bar; handlers serialize
+// parts of the shared report in place instead of copying them out
+fn latest_report(state: &MonitorState) -> Result<Arc<AnalysisReport>, Rejection> {
+    state.status.report().ok_or_else(not_ready)
+}
+
+fn not_ready() -> Rejection {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({ "error": "No candle processed yet" })),
+    )
+}
+
+async fn config(State(state): State<MonitorState>) -> Json<RunConfig> {
+    Json(state.status.config().clone())
+}
+
+async fn position(State(state): State<MonitorState>) -> Result<Response, Rejection> {
+    Ok(Json(&latest_report(&state)?.open_positions).into_response())
+}
+
+async fn balance(State(state): State<MonitorState>) -> Result<Response, Rejection> {
+    Ok(Json(latest_report(&state)?.current()).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct TradesQuery {
+    limit: Option<usize>,
+}
+
+// newest first: open trades, then closed ones by exit time
+async fn recent_trades(
+    State(state): State<MonitorState>,
+    Query(query): Query<TradesQuery>,
+) -> Result<Response, Rejection> {
+    let report = latest_report(&state)?;
+    let limit = query.limit.unwrap_or(DEFAULT_TRADES_LIMIT);
+    let trades: Vec<&Trade> = report.trades.iter().rev().take(limit).collect();
+    Ok(Json(trades).into_response())
+}
+
+async fn indicators(State(state): State<MonitorState>) -> ApiResult<Indicators> {
+    state.status.indicators().map(Json).ok_or_else(not_ready)
+}
+
+async fn report(State(state): State<MonitorState>) -> Result<Response, Rejection> {
+    Ok(Json(&*latest_report(&state)?).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixtures::trading_simulation;
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::trading_simulation::database::store::TradeStore;
+    use crate::trading_simulation::trade_analysis_report::generate_report;
+    use crate::utils::clock::SimulatedClock;
+    use chrono::DateTime;
+    use rust_decimal::dec;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    // raw HTTP/1.1 request, returns status line and body
+    async fn get(addr: SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request =
+            format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    fn state() -> MonitorState {
+        let sim = trading_simulation("BTCUSDT", 3, 8);
+        MonitorState {
+            metrics: Arc::new(BotMetrics::new("BTCUSDT")),
+            status: Arc::new(BotStatus::new(7, &sim)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let state = state();
+        state.metrics.record_db_error();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, router(state)).await.unwrap();
+        });
+
+        let (status, body) = get(addr, "/metrics").await;
+        server.abort();
+
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("trading_bot_db_errors_total{symbol=\"BTCUSDT\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_status_api() {
+        let state = state();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn({
+            let state = state.clone();
+            async move {
+                axum::serve(listener, router(state)).await.unwrap();
+            }
+        });
+
+        let (status, body) = get(addr, "/api/config").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let config: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(config["session_id"], 7);
+        assert_eq!(config["trading_simulation"]["symbol"], "BTCUSDT");
+        assert_eq!(config["trading_simulation"]["mode"], "paper");
+        // nothing to report before the first bar
+        let (status, _) = get(addr, "/api/position").await;
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let store = MemoryStore::default();
+        let session = store
+            .start_session("BTCUSDT", "paper", dec!(500), t0)
+            .await
+            .unwrap();
+        store
+            .record_open_trade("BTCUSDT", dec!(100), dec!(1), dec!(100), t0)
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(110), dec!(10), t0 + chrono::Duration::minutes(2))
+            .await
+            .unwrap();
+        store
+            .record_open_trade(
+                "BTCUSDT",
+                dec!(105),
+                dec!(2),
+                dec!(210),
+                t0 + chrono::Duration::minutes(3),
+            )
+            .await
+            .unwrap();
+        let report = generate_report(
+            &store,
+            session,
+            "BTCUSDT",
+            Some(dec!(108)),
+            525_600.0,
+            &SimulatedClock::new(t0 + chrono::Duration::minutes(5)),
+        )
+        .await
+        .unwrap();
+        state.status.record_report(Arc::new(report));
+        state.status.record_indicators(Indicators {
+            time: t0,
+            close: 108.0,
+            fast_sma: 107.0,
+            slow_sma: 104.5,
+            bullish: true,
+        });
+
+        let (_, body) = get(addr, "/api/position").await;
+        let positions: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(positions[0]["trade_id"], 2);
+        assert_eq!(positions[0]["unrealized_pnl"], "6");
+        let (_, body) = get(addr, "/api/balance").await;
+        let balance: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(balance["balance"], "510");
+        assert_eq!(balance["equity"], "516");
+        // newest first
+        let (_, body) = get(addr, "/api/trades?limit=1").await;
+        let trades: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(trades.as_array().unwrap().len(), 1);
+        assert_eq!(trades[0]["status"], "OPEN");
+        let (_, body) = get(addr, "/api/indicators").await;
+        let indicators: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(indicators["bullish"], true);
+        let (status, body) = get(addr, "/api/report").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(report["pnl_stats"]["total_trades"], 1);
+        server.abort();
+    }
+}
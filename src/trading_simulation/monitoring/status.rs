@@ -0,0 +1,70 @@
+use crate::trading_simulation::trade_analysis_report::AnalysisReport;
+use crate::utils::config::TradingSimulation;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+// strategy inputs of the latest bar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Indicators {
+    pub time: DateTime<Utc>, // close of the latest candle
+    pub close: f64,
+    pub fast_sma: f64,
+    pub slow_sma: f64,
+    pub bullish: bool, // fast SMA above slow SMA
+}
+
+// what the status API serves: config of the run plus snapshots the trading loop
+// leaves after every bar, so requests never touch the database
+#[derive(Debug)]
+pub struct BotStatus {
+    config: RunConfig,
+    latest: Mutex<Latest>,
+}
+
+#[derive(Debug, Default)]
+struct Latest {
+    report: Option<Arc<AnalysisReport>>,
+    indicators: Option<Indicators>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunConfig {
+    pub session_id: i64,
+    pub trading_simulation: serde_json::Value,
+}
+
+impl BotStatus {
+    pub fn new(session_id: i64, sim: &TradingSimulation) -> Self {
+        BotStatus {
+            config: RunConfig {
+                session_id,
+                trading_simulation: serde_json::to_value(sim).unwrap_or_default(),
+            },
+            latest: Mutex::new(Latest::default()),
+        }
+    }
+
+    pub fn config(&self) -> &RunConfig {
+        &self.config
+    }
+
+    // shared with the trading loop, requests serialize it without copying
+    pub fn record_report(&self, report: Arc<AnalysisReport>) {
+        self.latest.lock().unwrap().report = Some(report);
+    }
+
+    pub fn record_indicators(&self, indicators: Indicators) {
+        self.latest.lock().unwrap().indicators = Some(indicators);
+    }
+
+    // None until the first bar was processed
+    pub fn report(&self) -> Option<Arc<AnalysisReport>> {
+        self.latest.lock().unwrap().report.clone()
+    }
+
+    pub fn indicators(&self) -> Option<Indicators> {
+        self.latest.lock().unwrap().indicators.clone()
+    }
+}
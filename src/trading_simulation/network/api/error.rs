@@ -0,0 +1,161 @@
+use binance_spot_connector_rust::http::error::ClientError;
+use binance_spot_connector_rust::hyper::Error as ConnectorError;
+use serde::Deserialize;
+use std::fmt;
+
+// Binance error codes worth retrying, everything else is caller's mistake
+// https://developers.binance.com/docs/binance-spot-api-docs/errors
+const TRANSIENT_CODES: [i64; 6] = [
+    -1000, // UNKNOWN
+    -1001, // DISCONNECTED
+    -1003, // TOO_MANY_REQUESTS
+    -1006, // UNEXPECTED_RESP
+    -1007, // TIMEOUT
+    -1008, // SERVER_BUSY
+];
+
+// error payload Binance returns instead of requested data
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceErrorBody {
+    pub code: i64,
+    pub msg: String,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    // request never got a response (connection refused, invalid url, ...)
+    Transport(String),
+    // structured `{"code": .., "msg": ..}` error from Binance
+    Binance {
+        status: u16,
+        code: i64,
+        message: String,
+    },
+    // error response not complying with Binance schema (e.g. 5xx from gateway)
+    Http { status: u16, body: String },
+    // response body does not have expected shape
+    Decode(String),
+    // numeric field could not be parsed
+    InvalidField { field: &'static str, value: String },
+}
+
+impl ApiError {
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Transport(_) => true,
+            ApiError::Binance { status, code, .. } => {
+                *status == 429 || *status >= 500 || TRANSIENT_CODES.contains(code)
+            }
+            ApiError::Http { status, .. } => *status == 429 || *status >= 500,
+            ApiError::Decode(_) | ApiError::InvalidField { .. } => false,
+        }
+    }
+
+    // successful status with error payload, e.g. `{"code":-1121,"msg":"Invalid symbol."}`
+    pub fn from_body(status: u16, body: &str) -> Option<Self> {
+        serde_json::from_str::<BinanceErrorBody>(body)
+            .ok()
+            .map(|e| ApiError::Binance {
+                status,
+                code: e.code,
+                message: e.msg,
+            })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Transport(e) => write!(f, "request failed: {}", e),
+            ApiError::Binance {
+                status,
+                code,
+                message,
+            } => write!(f, "Binance error {} (HTTP {}): {}", code, status, message),
+            ApiError::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+            ApiError::Decode(e) => write!(f, "unexpected response: {}", e),
+            ApiError::InvalidField { field, value } => {
+                write!(f, "invalid value {:?} for field `{}`", value, field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<ConnectorError> for ApiError {
+    fn from(err: ConnectorError) -> Self {
+        match err {
+            ConnectorError::Client(ClientError::Structured(e)) => ApiError::Binance {
+                status: e.status_code,
+                code: e.data.code as i64,
+                message: e.data.message,
+            },
+            ConnectorError::Client(ClientError::Raw(e)) | ConnectorError::Server(e) => {
+                ApiError::Http {
+                    status: e.status_code,
+                    body: e.data,
+                }
+            }
+            ConnectorError::InvalidApiSecret => ApiError::Transport("invalid API secret".into()),
+            ConnectorError::Parse(e) => ApiError::Transport(e.to_string()),
+            ConnectorError::Send(e) => ApiError::Transport(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::Decode(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binance_spot_connector_rust::http::error::{BinanceApiError, HttpError};
+    use std::collections::HashMap;
+
+    fn structured(status: u16, code: i16) -> ApiError {
+        ConnectorError::Client(ClientError::Structured(HttpError::new(
+            status,
+            BinanceApiError {
+                code,
+                message: "test".into(),
+            },
+            HashMap::new(),
+        )))
+        .into()
+    }
+
+    #[test]
+    fn test_transient_classification() {
+        assert!(structured(429, -1003).is_transient());
+        assert!(structured(400, -1001).is_transient());
+        assert!(!structured(400, -1121).is_transient());
+
+        let gateway: ApiError = ConnectorError::Server(HttpError::new(
+            502,
+            "Bad Gateway".into(),
+            HashMap::new(),
+        ))
+        .into();
+        assert!(gateway.is_transient());
+        assert!(!ApiError::Decode("oops".into()).is_transient());
+    }
+
+    #[test]
+    fn test_error_from_body() {
+        let body = std::fs::read_to_string("tests/fixtures/binance/error_invalid_symbol.json")
+            .expect("Missing fixture");
+
+        match ApiError::from_body(200, &body) {
+            Some(ApiError::Binance { code, message, .. }) => {
+                assert_eq!(code, -1121);
+                assert_eq!(message, "Invalid symbol.");
+            }
+            other => panic!("Expected Binance error, got {:?}", other),
+        }
+        assert!(ApiError::from_body(200, "[]").is_none());
+    }
+}
@@ -0,0 +1,370 @@
+use binance_spot_connector_rust::{hyper::BinanceHttpClient, market};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+use crate::trading_simulation::network::api::error::ApiError;
+
+// trading rules of a symbol as returned by `/api/v3/exchangeInfo`,
+// only filters relevant to order sizing are kept, zero means filter is disabled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolFilters {
+    pub symbol: String,
+    // asset bought and sold, e.g. BTC of BTCUSDT
+    pub base_asset: String,
+    // PRICE_FILTER
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    pub tick_size: Decimal,
+    // LOT_SIZE
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub step_size: Decimal,
+    // MIN_NOTIONAL (legacy) or NOTIONAL, price * quantity
+    pub min_notional: Decimal,
+}
+
+// reason why exchange would refuse the order
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderRejection {
+    PriceOutOfRange {
+        price: Decimal,
+        min: Decimal,
+        max: Decimal,
+    },
+    QtyBelowMin {
+        qty: Decimal,
+        min: Decimal,
+    },
+    QtyAboveMax {
+        qty: Decimal,
+        max: Decimal,
+    },
+    NotionalBelowMin {
+        notional: Decimal,
+        min: Decimal,
+    },
+}
+
+impl fmt::Display for OrderRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderRejection::PriceOutOfRange { price, min, max } => {
+                write!(f, "price {} outside of [{}, {}]", price, min, max)
+            }
+            OrderRejection::QtyBelowMin { qty, min } => {
+                write!(f, "quantity {} below LOT_SIZE minimum {}", qty, min)
+            }
+            OrderRejection::QtyAboveMax { qty, max } => {
+                write!(f, "quantity {} above LOT_SIZE maximum {}", qty, max)
+            }
+            OrderRejection::NotionalBelowMin { notional, min } => {
+                write!(f, "notional {:.2} below minimum {:.2}", notional, min)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "filterType")]
+enum RawFilter {
+    #[serde(rename = "PRICE_FILTER", rename_all = "camelCase")]
+    Price {
+        min_price: String,
+        max_price: String,
+        tick_size: String,
+    },
+    #[serde(rename = "LOT_SIZE", rename_all = "camelCase")]
+    LotSize {
+        min_qty: String,
+        max_qty: String,
+        step_size: String,
+    },
+    #[serde(rename = "MIN_NOTIONAL", rename_all = "camelCase")]
+    MinNotional { min_notional: String },
+    #[serde(rename = "NOTIONAL", rename_all = "camelCase")]
+    Notional { min_notional: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSymbol {
+    symbol: String,
+    #[serde(default)]
+    base_asset: String,
+    filters: Vec<RawFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawExchangeInfo {
+    symbols: Vec<RawSymbol>,
+}
+
+// filter values are decimal strings, parsed exactly ("0.00001000" stays 0.00001)
+pub fn parse_number(field: &'static str, value: &str) -> Result<Decimal, ApiError> {
+    value
+        .parse::<Decimal>()
+        .map(|d| d.normalize())
+        .map_err(|_| ApiError::InvalidField {
+            field,
+            value: value.to_string(),
+        })
+}
+
+// body of exchangeInfo response -> filters of `symbol`
+pub fn parse_exchange_info(symbol: &str, body: &str) -> Result<SymbolFilters, ApiError> {
+    if let Some(err) = ApiError::from_body(200, body) {
+        return Err(err);
+    }
+
+    let info: RawExchangeInfo = serde_json::from_str(body)?;
+    let raw = info
+        .symbols
+        .into_iter()
+        .find(|s| s.symbol == symbol)
+        .ok_or_else(|| ApiError::Decode(format!("symbol {} not in exchange info", symbol)))?;
+
+    let mut filters = SymbolFilters {
+        symbol: raw.symbol,
+        base_asset: raw.base_asset,
+        ..Default::default()
+    };
+    for filter in raw.filters {
+        match filter {
+            RawFilter::Price {
+                min_price,
+                max_price,
+                tick_size,
+            } => {
+                filters.min_price = parse_number("minPrice", &min_price)?;
+                filters.max_price = parse_number("maxPrice", &max_price)?;
+                filters.tick_size = parse_number("tickSize", &tick_size)?;
+            }
+            RawFilter::LotSize {
+                min_qty,
+                max_qty,
+                step_size,
+            } => {
+                filters.min_qty = parse_number("minQty", &min_qty)?;
+                filters.max_qty = parse_number("maxQty", &max_qty)?;
+                filters.step_size = parse_number("stepSize", &step_size)?;
+            }
+            RawFilter::MinNotional { min_notional } | RawFilter::Notional { min_notional } => {
+                filters.min_notional = parse_number("minNotional", &min_notional)?;
+            }
+            RawFilter::Other => {}
+        }
+    }
+
+    Ok(filters)
+}
+
+impl SymbolFilters {
+    // nearest valid price
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        if self.tick_size.is_zero() {
+            return price;
+        }
+        ((price / self.tick_size).round() * self.tick_size).normalize()
+    }
+
+    // quantity is always rounded down, order never spends more than available
+    pub fn floor_qty(&self, qty: Decimal) -> Decimal {
+        if self.step_size.is_zero() {
+            return qty;
+        }
+        ((qty / self.step_size).floor() * self.step_size).normalize()
+    }
+
+    pub fn check_order(&self, price: Decimal, qty: Decimal) -> Result<(), OrderRejection> {
+        if price < self.min_price || (!self.max_price.is_zero() && price > self.max_price) {
+            return Err(OrderRejection::PriceOutOfRange {
+                price,
+                min: self.min_price,
+                max: self.max_price,
+            });
+        }
+        if qty <= Decimal::ZERO || qty < self.min_qty {
+            return Err(OrderRejection::QtyBelowMin {
+                qty,
+                min: self.min_qty,
+            });
+        }
+        if !self.max_qty.is_zero() && qty > self.max_qty {
+            return Err(OrderRejection::QtyAboveMax {
+                qty,
+                max: self.max_qty,
+            });
+        }
+        if price * qty < self.min_notional {
+            return Err(OrderRejection::NotionalBelowMin {
+                notional: price * qty,
+                min: self.min_notional,
+            });
+        }
+        Ok(())
+    }
+
+    // rounds price to tick and quantity to step, then validates the result
+    pub fn prepare_order(
+        &self,
+        price: Decimal,
+        qty: Decimal,
+    ) -> Result<(Decimal, Decimal), OrderRejection> {
+        let (price, qty) = (self.round_price(price), self.floor_qty(qty));
+        self.check_order(price, qty)?;
+        Ok((price, qty))
+    }
+}
+
+pub async fn fetch_exchange_info(api_url: &str, symbol: &str) -> Result<String, ApiError> {
+    let client = BinanceHttpClient::with_url(api_url);
+    let response = client.send(market::exchange_info().symbol(symbol)).await?;
+    let body = response.into_body_str().await?;
+
+    // surface error payload before anything is cached
+    match ApiError::from_body(200, &body) {
+        Some(err) => Err(err),
+        None => Ok(body),
+    }
+}
+
+// filters rarely change, so exchangeInfo response is cached in `cache_path`;
+// a fixture file placed there is used as is, exchange is only asked when the file
+// is missing or does not contain `symbol`
+pub async fn load_symbol_filters(
+    api_url: &str,
+    symbol: &str,
+    cache_path: &str,
+) -> Result<SymbolFilters, ApiError> {
+    if let Ok(body) = std::fs::read_to_string(cache_path) {
+        match parse_exchange_info(symbol, &body) {
+            Ok(filters) => {
+                println!(
+                    "[EXCHANGE INFO] {} filters loaded from {}",
+                    symbol, cache_path
+                );
+                return Ok(filters);
+            }
+            Err(e) => eprintln!("[EXCHANGE INFO] Ignoring cache {}: {}", cache_path, e),
+        }
+    }
+
+    let body = fetch_exchange_info(api_url, symbol).await?;
+    let filters = parse_exchange_info(symbol, &body)?;
+
+    let path = Path::new(cache_path);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match std::fs::write(path, &body) {
+        Ok(()) => println!(
+            "[EXCHANGE INFO] {} filters cached in {}",
+            symbol, cache_path
+        ),
+        Err(e) => eprintln!("[EXCHANGE INFO] Failed to cache {}: {}", cache_path, e),
+    }
+
+    Ok(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_binance::MockBinance;
+    use rust_decimal::dec;
+
+    const FIXTURE: &str = "tests/fixtures/binance/exchange_info_btcusdt.json";
+
+    fn btcusdt() -> SymbolFilters {
+        parse_exchange_info("BTCUSDT", &std::fs::read_to_string(FIXTURE).unwrap())
+            .expect("Failed to parse exchange info")
+    }
+
+    #[test]
+    fn test_parse_exchange_info_fixture() {
+        let filters = btcusdt();
+        assert_eq!(filters.base_asset, "BTC");
+        assert_eq!(filters.tick_size, dec!(0.01));
+        assert_eq!(filters.step_size, dec!(0.00001));
+        assert_eq!(filters.min_qty, dec!(0.00001));
+        assert_eq!(filters.max_qty, dec!(9000));
+        assert_eq!(filters.min_notional, dec!(5));
+
+        // legacy MIN_NOTIONAL filter is understood as well
+        let legacy = r#"{"symbols": [{"symbol": "ETHBTC", "filters": [
+            {"filterType": "MIN_NOTIONAL", "minNotional": "0.00010000", "avgPriceMins": 5}
+        ]}]}"#;
+        assert_eq!(
+            parse_exchange_info("ETHBTC", legacy).unwrap().min_notional,
+            dec!(0.0001)
+        );
+        assert!(matches!(
+            parse_exchange_info("ETHUSDT", legacy),
+            Err(ApiError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_prepare_order_rounding() {
+        let filters = btcusdt();
+        assert_eq!(filters.round_price(dec!(93561.234567)), dec!(93561.23));
+        assert_eq!(filters.floor_qty(dec!(500) / dec!(93561.23)), dec!(0.00534));
+        assert_eq!(filters.floor_qty(dec!(0.3)), dec!(0.3));
+
+        assert_eq!(
+            filters.prepare_order(dec!(93561.234567), dec!(500) / dec!(93561.23)),
+            Ok((dec!(93561.23), dec!(0.00534)))
+        );
+    }
+
+    #[test]
+    fn test_prepare_order_rejections() {
+        let filters = btcusdt();
+        assert!(matches!(
+            filters.prepare_order(dec!(93561.23), dec!(0.000004)),
+            Err(OrderRejection::QtyBelowMin { .. })
+        ));
+        assert!(matches!(
+            filters.prepare_order(dec!(93561.23), dec!(0.00004)),
+            Err(OrderRejection::NotionalBelowMin { .. })
+        ));
+        assert!(matches!(
+            filters.prepare_order(dec!(93561.23), dec!(10000)),
+            Err(OrderRejection::QtyAboveMax { .. })
+        ));
+        assert!(matches!(
+            filters.prepare_order(dec!(0.001), dec!(1)),
+            Err(OrderRejection::PriceOutOfRange { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_symbol_filters_cache() {
+        let mock = MockBinance::start().await;
+        let cache = std::env::temp_dir().join(format!("exchange_info_{}.json", std::process::id()));
+        let cache_path = cache.to_str().unwrap();
+        let _ = std::fs::remove_file(&cache);
+
+        // first load asks the exchange and writes cache, second one reads the file
+        let fetched = load_symbol_filters(&mock.http_url(), "BTCUSDT", cache_path)
+            .await
+            .expect("Failed to load filters");
+        let cached = load_symbol_filters(&mock.http_url(), "BTCUSDT", cache_path)
+            .await
+            .expect("Failed to load cached filters");
+        assert_eq!(fetched, btcusdt());
+        assert_eq!(cached, fetched);
+        assert_eq!(mock.request_count(), 1);
+
+        match load_symbol_filters(&mock.http_url(), "NOPEUSDT", cache_path).await {
+            Err(ApiError::Binance { code, .. }) => assert_eq!(code, -1121),
+            other => panic!("Expected invalid symbol, got {:?}", other),
+        }
+
+        let _ = std::fs::remove_file(&cache);
+    }
+}
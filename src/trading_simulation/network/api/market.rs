@@ -1,75 +1,163 @@
 use binance_spot_connector_rust::{
-    hyper::{BinanceHttpClient, Error},
+    hyper::BinanceHttpClient,
     market::{self, klines::KlineInterval},
 };
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::Sender;
-use tokio::time::{Duration, sleep};
+use tokio::time::{Duration, Instant, sleep};
 use tokio_cron_scheduler::{Job, JobScheduler};
 
+use crate::trading_simulation::monitoring::metrics::BotMetrics;
+use crate::trading_simulation::network::api::error::ApiError;
 use crate::utils::objects::CandleStick;
 
-const CRON_EXPRESSION: &str = "1 * * * * *"; // each minute at first second
+pub const CRON_EXPRESSION: &str = "1 * * * * *"; // each minute at first second
 const REQUEST_DELAY_MS: u64 = 250; // Binance API constrain
+const MAX_RETRIES: u32 = 3; // for transient errors only (rate limit, server busy, ...)
+const RETRY_BACKOFF_MS: u64 = 500; // doubled with each retry
 
-pub async fn fetch_market_data(
-    symbol: String,
-    lookback: u32,
+// kline as returned by `/api/v3/klines`:
+// [open time, open, high, low, close, volume, close time, quote asset volume,
+//  number of trades, taker buy base volume, taker buy quote volume, ignore]
+#[derive(Debug, Deserialize)]
+pub struct RawKline(
+    pub i64,
+    pub String,
+    pub String,
+    pub String,
+    pub String,
+    pub String,
+    pub i64,
+    pub String,
+    pub u64,
+    pub String,
+    pub String,
+    pub String,
+);
+
+fn parse_price(field: &'static str, value: &str) -> Result<f64, ApiError> {
+    value.parse::<f64>().map_err(|_| ApiError::InvalidField {
+        field,
+        value: value.to_string(),
+    })
+}
+
+impl RawKline {
+    pub fn into_candlestick(self, symbol: &str) -> Result<CandleStick, ApiError> {
+        Ok(CandleStick {
+            symbol: symbol.to_string(),
+            open: parse_price("open", &self.1)?,
+            high: parse_price("high", &self.2)?,
+            low: parse_price("low", &self.3)?,
+            close: parse_price("close", &self.4)?,
+            volume: parse_price("volume", &self.5)?,
+            timestamp: self.0,
+        })
+    }
+}
+
+// body of klines response -> candlesticks, Binance error payload -> typed error
+pub fn parse_klines(symbol: &str, body: &str) -> Result<Vec<CandleStick>, ApiError> {
+    if let Some(err) = ApiError::from_body(200, body) {
+        return Err(err);
+    }
+
+    serde_json::from_str::<Vec<RawKline>>(body)?
+        .into_iter()
+        .map(|k| k.into_candlestick(symbol))
+        .collect()
+}
+
+// most klines Binance returns for one request
+const MAX_KLINES: u32 = 1000;
+
+// klines request with retries, latest kline is dropped since its not closed yet
+async fn request_klines(
+    api_url: &str,
+    symbol: &str,
     timeframe: KlineInterval,
-) -> Result<Vec<CandleStick>, Error> {
-    let client = BinanceHttpClient::default();
-    let mut candlesticks = Vec::new();
+    start_time: Option<i64>,
+    limit: u32,
+) -> Result<Vec<CandleStick>, ApiError> {
+    let client = BinanceHttpClient::with_url(api_url);
 
-    // request one extra candlestick because the latest one (candlestick with index 0) is still open
-    // for discrete trading strategies, only closed candles are reliable
-    let lookback = lookback + 1;
+    let request_klines = || async {
+        let mut request = market::klines(symbol, timeframe).limit(limit);
+        if let Some(start_time) = start_time {
+            request = request.start_time(start_time as u64);
+        }
+        let response = client.send(request).await?;
+        let data = response.into_body_str().await?; // read JSON object from http response body
 
-    // sending http request asynchronously
-    match client
-        .send(market::klines(&symbol, timeframe).limit(lookback))
-        .await
-    {
-        Ok(response) => {
-            let data = response.into_body_str().await?; // read JSON object from http response body
-
-            // try to parse JSON object to serde object
-            if let Ok(klines) = serde_json::from_str::<Vec<serde_json::Value>>(&data) {
-                for k in klines.iter().take(lookback as usize) {
-                    candlesticks.push(CandleStick {
-                        symbol: symbol.to_string(),
-                        open: k[1].as_str().unwrap().parse::<f64>().unwrap(),
-                        high: k[2].as_str().unwrap().parse::<f64>().unwrap(),
-                        low: k[3].as_str().unwrap().parse::<f64>().unwrap(),
-                        close: k[4].as_str().unwrap().parse::<f64>().unwrap(),
-                        volume: k[5].as_str().unwrap().parse::<f64>().unwrap(),
-                        timestamp: k[0].as_i64().unwrap(),
-                    });
-                }
+        parse_klines(symbol, &data)
+    };
 
-                // most recent candlestick is removed since its not closed yet
-                candlesticks.pop();
+    // sending http request asynchronously, transient failures are retried with backoff
+    let mut attempt = 0;
+    let mut candlesticks = loop {
+        match request_klines().await {
+            Ok(candlesticks) => break candlesticks,
+            Err(e) if e.is_transient() && attempt < MAX_RETRIES => {
+                attempt += 1;
+                eprintln!(
+                    "[RETRY {}/{}] {} {}: {}",
+                    attempt, MAX_RETRIES, symbol, timeframe, e
+                );
+                sleep(Duration::from_millis(RETRY_BACKOFF_MS << (attempt - 1))).await;
             }
+            Err(e) => return Err(e),
         }
+    };
 
-        Err(e) => {
-            println!("{}, {}, {}, {:?}", symbol, lookback, timeframe, e);
-            return Err(e);
-        }
-    }
+    // most recent candlestick is removed since its not closed yet
+    candlesticks.pop();
 
     sleep(Duration::from_millis(REQUEST_DELAY_MS)).await; // Binance API constraint
 
     Ok(candlesticks)
 }
 
+// latest `lookback` closed candles
+pub async fn fetch_market_data(
+    api_url: &str,
+    symbol: String,
+    lookback: u32,
+    timeframe: KlineInterval,
+) -> Result<Vec<CandleStick>, ApiError> {
+    // request one extra candlestick because the latest one (candlestick with index 0) is still open
+    // for discrete trading strategies, only closed candles are reliable
+    request_klines(api_url, &symbol, timeframe, None, lookback + 1).await
+}
+
+// closed candles opened after `last_open_time`, oldest first; after a long outage
+// only first `MAX_KLINES - 1` are returned, following calls pick up the rest
+pub async fn fetch_market_delta(
+    api_url: &str,
+    symbol: &str,
+    timeframe: KlineInterval,
+    last_open_time: i64,
+) -> Result<Vec<CandleStick>, ApiError> {
+    request_klines(api_url, symbol, timeframe, Some(last_open_time + 1), MAX_KLINES).await
+}
 
 // periodically fetch market candlestick data and send it to async channel 
-// to be consumed by main trading async task
+// to be consumed by main trading async task, returned scheduler stops the feed;
+// first batch holds `lookback` candles, later ones only candles closed since
+// previous batch (nothing is sent while no new candle closed); every fetch is
+// timed into `metrics`, retries and the rate limit pause included
 pub async fn spawn_cron_market_feed(
+    api_url: String,
     symbol: String,
     lookback: u32,
     timeframe: KlineInterval,
+    schedule: &str,
     tx: Sender<Vec<CandleStick>>,
-) {
+    metrics: Arc<BotMetrics>,
+) -> JobScheduler {
+    // open time of latest candle sent, shared by all cron executions
+    let last_sent: Arc<Mutex<Option<i64>>> = Arc::new(Mutex::new(None));
+
     // cron scheduler
     let scheduler = JobScheduler::new()
         .await
@@ -80,7 +168,7 @@ pub async fn spawn_cron_market_feed(
         .add(
             // create cron job
             Job::new_async(
-            CRON_EXPRESSION,
+            schedule,
             {
 
                 // lifetime: until scheduler is not terminated
@@ -89,7 +177,10 @@ pub async fn spawn_cron_market_feed(
                     // lifetime: one cron execution
                     // fresh ownership for each closure execution (tokio stuff)
                     let tx = tx.clone();
+                    let api_url = api_url.clone();
                     let symbol = symbol.clone();
+                    let last_sent = last_sent.clone();
+                    let metrics = metrics.clone();
 
                     // keeping consistent adress in virtual memory 
                     // pinning prevents movement during .await suspension (tokio stuff)
@@ -99,15 +190,29 @@ pub async fn spawn_cron_market_feed(
                         // with local vars and current state, future trait is implemented
                         // pool enum tells executor when data is ready or not, to proceed
                         async move {
-                            match fetch_market_data(symbol, lookback, timeframe).await {
+                            let since = *last_sent.lock().unwrap();
+                            let started = Instant::now();
+                            let fetched = match since {
+                                None => fetch_market_data(&api_url, symbol, lookback, timeframe).await,
+                                Some(open_time) => {
+                                    fetch_market_delta(&api_url, &symbol, timeframe, open_time).await
+                                }
+                            };
+                            metrics.record_fetch(started.elapsed(), fetched.is_ok());
+
+                            match fetched {
 
                                 // send data to channel
                                 Ok(candlesticks) => {
-                                    if let Err(err) = tx.send(candlesticks).await {
-                                        eprintln!("Failed to send candlesticks: {}", err);
+                                    let Some(last) = candlesticks.last().map(|c| c.timestamp) else {
+                                        return; // no candle closed since previous batch
+                                    };
+                                    match tx.send(candlesticks).await {
+                                        Ok(()) => *last_sent.lock().unwrap() = Some(last),
+                                        Err(err) => eprintln!("Failed to send candlesticks: {}", err),
                                     }
                                 }
-                                Err(e) => eprintln!("Error fetching market data: {:?}", e),
+                                Err(e) => eprintln!("Error fetching market data: {}", e),
                             }
                         }
                     )
@@ -120,44 +225,159 @@ pub async fn spawn_cron_market_feed(
         .unwrap();
 
     // spawn task
+    let handle = scheduler.clone();
     tokio::spawn(async move {
         scheduler.start().await.unwrap();
     });
+
+    handle
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::mock_binance::{Fault, MockBinance};
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!("tests/fixtures/binance/{}", name))
+            .expect("Missing fixture")
+    }
+
+    #[test]
+    fn test_parse_klines_fixture() {
+        let candles = parse_klines("BTCUSDT", &fixture("klines_btcusdt_1m.json"))
+            .expect("Failed to parse klines");
+
+        assert_eq!(candles.len(), 4);
+        assert_eq!(candles[0].symbol, "BTCUSDT");
+        assert_eq!(candles[0].timestamp, 1_735_689_600_000);
+        assert_eq!(candles[0].high, 93610.93);
+        assert_eq!(candles[3].close, 93544.52);
+        assert_eq!(candles[3].volume, 4.5871);
+    }
+
+    #[test]
+    fn test_parse_klines_binance_error() {
+        match parse_klines("BTCUSDTX", &fixture("error_invalid_symbol.json")) {
+            Err(ApiError::Binance { code, .. }) => assert_eq!(code, -1121),
+            other => panic!("Expected Binance error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_klines_malformed() {
+        match parse_klines("BTCUSDT", &fixture("klines_malformed.json")) {
+            Err(ApiError::InvalidField { field, value }) => {
+                assert_eq!(field, "close");
+                assert_eq!(value, "not-a-price");
+            }
+            other => panic!("Expected invalid field, got {:?}", other),
+        }
+        assert!(matches!(
+            parse_klines("BTCUSDT", "{\"unexpected\": true}"),
+            Err(ApiError::Decode(_))
+        ));
+    }
 
     #[tokio::test]
     async fn test_hist_data_fetch() {
+        let mock = MockBinance::start().await;
         let symbol: String = "BTCUSDT".to_string();
         let timeframes: Vec<KlineInterval> = vec![KlineInterval::Minutes1, KlineInterval::Minutes3];
         let lookback: u32 = 2;
 
         for i in timeframes {
-            let result = fetch_market_data(symbol.clone(), lookback, i).await;
-            println!("{:?}", result.unwrap());
+            let result = fetch_market_data(&mock.http_url(), symbol.clone(), lookback, i).await;
+            let candles = result.expect("Failed to fetch klines from mock");
+            println!("{:?}", candles);
+            assert_eq!(candles.len(), lookback as usize);
         }
     }
 
+    #[tokio::test]
+    async fn test_fetch_retries_transient_errors() {
+        let mock = MockBinance::start().await;
+        mock.fail_next(Fault::binance(429, -1003, "Too many requests."));
+        mock.fail_next(Fault::http(502, "Bad Gateway"));
+
+        let candles = fetch_market_data(&mock.http_url(), "BTCUSDT".into(), 5, KlineInterval::Minutes1)
+            .await
+            .expect("Transient errors should be retried");
+
+        assert_eq!(candles.len(), 5);
+        assert_eq!(mock.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_propagates_permanent_errors() {
+        let mock = MockBinance::start().await;
+        mock.fail_next(Fault::binance(400, -1121, "Invalid symbol."));
+
+        let result =
+            fetch_market_data(&mock.http_url(), "BTCUSDTX".into(), 5, KlineInterval::Minutes1).await;
+
+        assert!(matches!(result, Err(ApiError::Binance { code: -1121, .. })));
+        assert_eq!(mock.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_market_delta() {
+        let mock = MockBinance::start().await;
+        let klines = mock.klines();
+        let since = klines[50][0].as_i64().unwrap();
+
+        let candles = fetch_market_delta(&mock.http_url(), "BTCUSDT", KlineInterval::Minutes1, since)
+            .await
+            .expect("Failed to fetch delta from mock");
+
+        // 51..=58 closed, 59 is still open
+        assert_eq!(candles.len(), 8);
+        assert_eq!(candles[0].timestamp, klines[51][0].as_i64().unwrap());
+        assert_eq!(candles[7].timestamp, klines[58][0].as_i64().unwrap());
+    }
+
     #[tokio::test]
     async fn test_scheduled_task_with_output() {
+        let mock = MockBinance::start().await;
         let symbol: String = "BTCUSDT".to_string();
         let timeframe: KlineInterval = KlineInterval::Minutes1;
         let lookback: u32 = 3;
         let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<CandleStick>>(10);
 
-        spawn_cron_market_feed(symbol.clone(), lookback, timeframe, tx).await;
+        // every second instead of every minute
+        let mut scheduler = spawn_cron_market_feed(
+            mock.http_url(),
+            symbol.clone(),
+            lookback,
+            timeframe,
+            "* * * * * *",
+            tx,
+            Arc::new(BotMetrics::new(&symbol)),
+        )
+        .await;
+
+        let klines = mock.klines();
+        let open_time = |i: usize| klines[i][0].as_i64().unwrap();
+        let mut receive = async || {
+            tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("No candlesticks received in time")
+                .expect("Channel closed")
+        };
 
-        tokio::spawn(async move {
-            while let Some(candles) = rx.recv().await {
-                println!("Received candlesticks: {:?}", candles);
-            }
-        });
+        // full window first, latest fixture kline counts as still open
+        let candles = receive().await;
+        println!("Received candlesticks: {:?}", candles);
+        assert_eq!(candles.len(), lookback as usize);
+        assert_eq!(candles[2].timestamp, open_time(58));
 
-        tokio::signal::ctrl_c()
-            .await
-            .expect("Failed to listen for Ctrl+C signal");
+        // later batches only carry candles closed since, none until market moves on
+        mock.push_kline("93600.5");
+        let candles = receive().await;
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].timestamp, open_time(59));
+        assert!(rx.try_recv().is_err());
+
+        scheduler.shutdown().await.expect("Failed to stop scheduler");
     }
 }
@@ -1 +1,3 @@
+pub mod error;
+pub mod exchange_info;
 pub mod market;
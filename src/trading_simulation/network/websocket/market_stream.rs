@@ -1,15 +1,14 @@
-/// UNUSED: 
-/// websocket implementataion is not used, in trading simulation production
-/// since we choose to implement discrete strategy, with market order placing,
-/// so constant stream of data  became redundant
-/// preserve is case we decide to implement tradig bot on exchange for order 
-/// placement optimization
+//! UNUSED: 
+//! websocket implementataion is not used, in trading simulation production
+//! since we choose to implement discrete strategy, with market order placing,
+//! so constant stream of data  became redundant
+//! preserve is case we decide to implement tradig bot on exchange for order 
+//! placement optimization
 
 use anyhow::{anyhow, Result};
 use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio_tungstenite::{
@@ -189,29 +188,44 @@ async fn manage_connection(url: String, candle_tx: mpsc::Sender<Candle>) -> Resu
     Ok(())
 }
 
-async fn process_candles(mut candle_rx: mpsc::Receiver<Candle>) {
-    let mut candle_store: HashMap<String, Vec<Candle>> = HashMap::new();
+// subscribe to kline streams of `symbol` and forward parsed candles into channel
+pub async fn stream_klines(
+    base_url: &str,
+    symbol: &str,
+    timeframes: &[&str],
+    candle_tx: mpsc::Sender<Candle>,
+) -> Result<()> {
+    manage_connection(get_ws_klinedata_url(base_url, symbol, timeframes), candle_tx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_binance::MockBinance;
 
-    while let Some(candle) = candle_rx.recv().await {
-        let timeframe_candles = candle_store.entry(candle.timeframe.clone()).or_default();
-        timeframe_candles.push(candle.clone());
+    #[tokio::test]
+    async fn test_stream_klines_from_mock() {
+        let mock = MockBinance::start().await;
+        let (candle_tx, mut candle_rx) = mpsc::channel::<Candle>(100);
 
-        if timeframe_candles.len() > 1000 {
-            timeframe_candles.remove(0);
+        let url = mock.ws_url();
+        let stream = tokio::spawn(async move {
+            stream_klines(&url, "BTCUSDT", &["1m"], candle_tx).await
+        });
+
+        for kline in mock.klines().iter().take(3) {
+            let candle = tokio::time::timeout(Duration::from_secs(5), candle_rx.recv())
+                .await
+                .expect("No candle received in time")
+                .expect("Stream closed");
+
+            assert_eq!(candle.symbol, "BTCUSDT");
+            assert_eq!(candle.timeframe, "1m");
+            assert!(candle.is_closed);
+            assert_eq!(candle.open_time, kline[0].as_i64().unwrap());
+            assert_eq!(candle.close, kline[4].as_str().unwrap().parse::<f64>().unwrap());
         }
 
-        println!(
-            "[{}] {} | O: {:.2} | H: {:.2} | L: {:.2} | C: {:.2} | V: {:.4} | QV: {:.2} | Trades: {} | {}",
-            candle.timeframe,
-            candle.symbol,
-            candle.open,
-            candle.high,
-            candle.low,
-            candle.close,
-            candle.volume,
-            candle.quote_volume,
-            candle.number_of_trades,
-            if candle.is_closed { "CLOSED" } else { "OPEN" }
-        );
+        stream.abort();
     }
 }
@@ -1 +1,2 @@
 pub mod market_stream;
+pub mod user_stream;
@@ -0,0 +1,230 @@
+// account events of API key owner (order updates, balances), pushed by Binance
+// over websocket once a `listenKey` is obtained from REST API
+
+use anyhow::{Result, anyhow};
+use binance_spot_connector_rust::http::Credentials;
+use binance_spot_connector_rust::hyper::BinanceHttpClient;
+use binance_spot_connector_rust::stream;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message, http::Uri},
+};
+
+use crate::trading_simulation::network::api::error::ApiError;
+
+const PING_INTERVAL: Duration = Duration::from_secs(180);
+
+// listen key expires after 60 minutes without keepalive
+pub const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+
+// order update, sent on every state change and every trade of an order
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionReport {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    // id of canceled order, `c` is then id of the cancel request
+    #[serde(rename = "C", default)]
+    pub orig_client_order_id: String,
+    #[serde(rename = "S")]
+    pub side: String,
+    #[serde(rename = "o")]
+    pub order_type: String,
+    #[serde(rename = "q")]
+    pub quantity: String,
+    // NEW, CANCELED, REPLACED, REJECTED, TRADE, EXPIRED, TRADE_PREVENTION
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    #[serde(rename = "X")]
+    pub order_status: String,
+    #[serde(rename = "r")]
+    pub reject_reason: String,
+    #[serde(rename = "i")]
+    pub order_id: i64,
+    #[serde(rename = "l")]
+    pub last_quantity: String,
+    #[serde(rename = "z")]
+    pub cumulative_quantity: String,
+    #[serde(rename = "L")]
+    pub last_price: String,
+    #[serde(rename = "T")]
+    pub transaction_time: i64,
+    #[serde(rename = "Z")]
+    pub cumulative_quote_quantity: String,
+}
+
+impl ExecutionReport {
+    // client order id the order was placed with
+    pub fn placed_client_order_id(&self) -> &str {
+        if self.orig_client_order_id.is_empty() {
+            &self.client_order_id
+        } else {
+            &self.orig_client_order_id
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetBalance {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "f")]
+    pub free: String,
+    #[serde(rename = "l")]
+    pub locked: String,
+}
+
+// balances of assets changed by an account event
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountPosition {
+    #[serde(rename = "E")]
+    pub event_time: i64,
+    #[serde(rename = "B")]
+    pub balances: Vec<AssetBalance>,
+}
+
+#[derive(Debug, Clone)]
+pub enum UserEvent {
+    Execution(Box<ExecutionReport>),
+    AccountPosition(AccountPosition),
+}
+
+pub async fn create_listen_key(api_url: &str, api_key: &str) -> Result<String, ApiError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ListenKey {
+        listen_key: String,
+    }
+
+    // listen key endpoints need API key only, no signature
+    let credentials = Credentials::from_hmac(api_key, "");
+    let client = BinanceHttpClient::with_url(api_url).credentials(credentials);
+    let body = client
+        .send(stream::new_listen_key())
+        .await?
+        .into_body_str()
+        .await?;
+    if let Some(err) = ApiError::from_body(200, &body) {
+        return Err(err);
+    }
+    Ok(serde_json::from_str::<ListenKey>(&body)?.listen_key)
+}
+
+pub async fn renew_listen_key(
+    api_url: &str,
+    api_key: &str,
+    listen_key: &str,
+) -> Result<(), ApiError> {
+    let credentials = Credentials::from_hmac(api_key, "");
+    let client = BinanceHttpClient::with_url(api_url).credentials(credentials);
+    client
+        .send(stream::renew_listen_key(listen_key))
+        .await?
+        .into_body_str()
+        .await?;
+    Ok(())
+}
+
+fn parse_event(text: &str) -> Result<Option<UserEvent>> {
+    let value: Value = serde_json::from_str(text)?;
+
+    match value.get("e").and_then(|e| e.as_str()).unwrap_or_default() {
+        "executionReport" => Ok(Some(UserEvent::Execution(serde_json::from_value(value)?))),
+        "outboundAccountPosition" => Ok(Some(UserEvent::AccountPosition(serde_json::from_value(
+            value,
+        )?))),
+        // stream is dead from here on, caller has to create new listen key
+        "listenKeyExpired" => Err(anyhow!("Listen key expired")),
+        // balanceUpdate (deposits, withdrawals) and other events are not needed
+        _ => Ok(None),
+    }
+}
+
+async fn process_message(message: Message, event_tx: &mpsc::Sender<UserEvent>) -> Result<()> {
+    match message {
+        Message::Text(text) => {
+            if let Some(event) = parse_event(&text)? {
+                event_tx
+                    .send(event)
+                    .await
+                    .map_err(|e| anyhow!("Failed to send user event: {}", e))?;
+            }
+        }
+
+        Message::Close(_) => {
+            return Err(anyhow!("Connection closed by server"));
+        }
+
+        // ignoring other messages
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// connect to user data stream of `listen_key` and forward parsed events into channel,
+// returns once connection is closed
+pub async fn stream_user_data(
+    base_url: &str,
+    listen_key: &str,
+    event_tx: mpsc::Sender<UserEvent>,
+) -> Result<()> {
+    let uri = format!("{}/ws/{}", base_url, listen_key).parse::<Uri>()?;
+    let (ws_stream, _response) = connect_async(uri).await?;
+    let (mut write, mut read) = ws_stream.split();
+    let mut last_ping = Instant::now();
+
+    while let Some(message) = read.next().await.transpose()? {
+        if last_ping.elapsed() > PING_INTERVAL {
+            write.send(Message::Pong(vec![].into())).await?;
+            last_ping = Instant::now();
+        }
+
+        process_message(message, &event_tx).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_user_events() {
+        let execution = r#"{"e":"executionReport","E":1735689600100,"s":"BTCUSDT",
+            "c":"cancel-1","S":"BUY","o":"LIMIT","f":"GTC","q":"0.01","p":"90000","P":"0",
+            "F":"0","g":-1,"C":"bot-7","x":"CANCELED","X":"CANCELED","r":"NONE","i":42,
+            "l":"0","z":"0.004","L":"0","n":"0","N":null,"T":1735689600099,"t":-1,"I":1,
+            "w":false,"m":false,"M":false,"O":1735689500000,"Z":"360","Y":"0","Q":"0"}"#;
+        match parse_event(execution).unwrap() {
+            Some(UserEvent::Execution(report)) => {
+                assert_eq!(report.placed_client_order_id(), "bot-7");
+                assert_eq!(report.order_status, "CANCELED");
+                assert_eq!(report.cumulative_quantity, "0.004");
+            }
+            other => panic!("Expected execution report, got {:?}", other),
+        }
+
+        let position = r#"{"e":"outboundAccountPosition","E":1735689600100,"u":1735689600099,
+            "B":[{"a":"BTC","f":"0.004","l":"0"},{"a":"USDT","f":"140","l":"0"}]}"#;
+        match parse_event(position).unwrap() {
+            Some(UserEvent::AccountPosition(position)) => {
+                assert_eq!(position.balances[0].asset, "BTC");
+            }
+            other => panic!("Expected account position, got {:?}", other),
+        }
+
+        let balance = r#"{"e":"balanceUpdate","E":1,"a":"BTC","d":"0.1","T":1}"#;
+        assert!(parse_event(balance).unwrap().is_none());
+        assert!(parse_event(r#"{"e":"listenKeyExpired","E":1,"listenKey":"k"}"#).is_err());
+    }
+}
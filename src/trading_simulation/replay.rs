@@ -0,0 +1,125 @@
+use crate::utils::data_io::{ColumnMapping, frame_to_candles, read_market_data, resample_ohlcv};
+use crate::utils::data_quality::{QualityPolicy, validate_frame};
+use crate::utils::objects::CandleStick;
+
+use polars::prelude::PolarsResult;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, sleep};
+
+// historical candles for replay go through the same loaders and checks as backtest,
+// and are resampled into timeframe of the live configuration; also tells whether
+// source had volume, candles of sources without it carry volume 0
+pub fn load_replay_candles(
+    path: &str,
+    columns: &ColumnMapping,
+    symbol: &str,
+    timeframe: &str,
+    data_quality: QualityPolicy,
+) -> PolarsResult<(Vec<CandleStick>, bool)> {
+    let (df, quality) = validate_frame(
+        read_market_data(path, columns, Some(symbol), timeframe)?,
+        data_quality,
+    )?;
+    if !quality.is_clean() {
+        println!("[DATA QUALITY] {} -> {:?}", quality, data_quality);
+    }
+
+    let has_volume = df.column("volume").is_ok();
+    let candles = frame_to_candles(&resample_ohlcv(df, timeframe)?, symbol)?;
+    Ok((candles, has_volume))
+}
+
+// replays recorded candles through the same channel as `spawn_cron_market_feed`,
+// each message is a window of `lookback` closed candles ending at the replayed bar;
+// `speed` is multiple of real time, 0 replays as fast as consumer keeps up
+pub fn spawn_replay_feed(
+    candles: Vec<CandleStick>,
+    lookback: u32,
+    interval: Duration,
+    speed: f64,
+    tx: Sender<Vec<CandleStick>>,
+) -> JoinHandle<()> {
+    let lookback = lookback as usize;
+    let pause = (speed > 0.0).then(|| interval.div_f64(speed));
+
+    tokio::spawn(async move {
+        println!(
+            "[REPLAY] {} candles, window of {}, {}",
+            candles.len(),
+            lookback,
+            match pause {
+                Some(p) => format!("one candle each {:?}", p),
+                None => "as fast as possible".to_string(),
+            }
+        );
+
+        for end in lookback..=candles.len() {
+            // channel holds a single batch, so send waits for consumer to finish previous one
+            if tx
+                .send(candles[end - lookback..end].to_vec())
+                .await
+                .is_err()
+            {
+                break;
+            }
+            if let Some(pause) = pause {
+                sleep(pause).await;
+            }
+        }
+
+        println!("[REPLAY] Finished");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn candles(n: i64) -> Vec<CandleStick> {
+        (0..n)
+            .map(|i| CandleStick {
+                symbol: "BTCUSDT".into(),
+                open: 100.0,
+                high: 101.0,
+                low: 99.0,
+                close: 100.0 + i as f64,
+                volume: 1.0,
+                timestamp: i * 60_000,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_replay_feed_windows() {
+        let (tx, mut rx) = mpsc::channel::<Vec<CandleStick>>(1);
+        spawn_replay_feed(candles(6), 3, Duration::from_secs(60), 0.0, tx);
+
+        let mut last_closes = Vec::new();
+        while let Some(window) = rx.recv().await {
+            assert_eq!(window.len(), 3);
+            last_closes.push(window.last().unwrap().close);
+        }
+        // feed closes channel once all candles are replayed
+        assert_eq!(last_closes, [102.0, 103.0, 104.0, 105.0]);
+    }
+
+    #[test]
+    fn test_load_replay_candles() {
+        let (candles, has_volume) = load_replay_candles(
+            "tests/fixtures/BTCUSDT-1m-2025-01.zip",
+            &ColumnMapping::default(),
+            "BTCUSDT",
+            "1m",
+            QualityPolicy::Reject,
+        )
+        .expect("Failed to load replay candles");
+
+        assert_eq!(candles.len(), 5);
+        assert!(has_volume);
+        assert_eq!(candles[0].symbol, "BTCUSDT");
+        assert_eq!(candles[0].timestamp, 1_735_689_600_000);
+        assert_eq!(candles[4].close, 93560.32);
+    }
+}
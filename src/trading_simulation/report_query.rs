@@ -0,0 +1,833 @@
+use crate::trading_simulation::database::store::{Store, TradeStore};
+use crate::trading_simulation::trade_analysis_report::{
+    AnalysisReport, SessionBalance, benchmark_curve, benchmark_metrics, candle_equity_curve,
+    equity_metrics, excursion_stats, float, holding_time_stats, open_positions, pnl_stats,
+};
+use crate::utils::clock::{Clock, SystemClock};
+use crate::utils::config::{Settings, TradingSimulation, timeframe_duration};
+use crate::utils::export::Export;
+use crate::utils::html_report::{ChartTrade, HtmlReport, sma_series};
+use crate::utils::metrics::{bars_per_year_ms, performance_metrics};
+use crate::utils::objects::{CandleStick, EquitySnapshot, Trade};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use dotenv::dotenv;
+use polars::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::path::Path;
+
+// latest candles of the range drawn by `report --html`
+const MAX_BARS: i64 = 100_000;
+
+// period of `report --group-by`
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    // first day of the period `time` falls into, weeks start on Monday (UTC)
+    pub fn start(&self, time: DateTime<Utc>) -> NaiveDate {
+        let day = time.date_naive();
+        match self {
+            Period::Day => day,
+            Period::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+            Period::Month => day.with_day(1).expect("Every month has a first day"),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+}
+
+// slice of the ledger reported by `bot report`, bounds are `[from, to)`;
+// a session narrows them to its own start and end
+#[derive(Debug, Clone, Default)]
+pub struct ReportQuery {
+    pub symbol: Option<String>, // symbol of the session or the config if not set
+    pub session: Option<i64>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// trades and candles of a query. Trades closed before the slice are part of its
+// starting balance, trades closed after it are still open in it
+#[derive(Debug, Clone)]
+pub struct ReportSlice {
+    pub symbol: String,
+    pub timeframe: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: DateTime<Utc>,
+    pub initial_balance: Decimal, // balance at `from`
+    pub candles: Vec<CandleStick>,
+    pub closed: Vec<Trade>, // closed within the slice
+    pub open: Vec<Trade>,   // open at `to`
+}
+
+// `YYYY-MM-DD` (midnight UTC) or RFC 3339 time
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(day.and_hms_opt(0, 0, 0).expect("Midnight exists").and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            format!(
+                "Invalid time `{}` ({}), use YYYY-MM-DD or RFC 3339",
+                value, e
+            )
+        })
+}
+
+// reads trades of the symbol and the latest `max_bars` candles of the range,
+// an open range ends at `now`
+pub async fn load_slice(
+    store: &impl TradeStore,
+    sim: &TradingSimulation,
+    query: &ReportQuery,
+    max_bars: i64,
+    now: DateTime<Utc>,
+) -> Result<ReportSlice, Box<dyn std::error::Error>> {
+    let session = match query.session {
+        Some(id) => Some(
+            store
+                .get_session(id)
+                .await?
+                .ok_or_else(|| format!("Session #{} not found", id))?,
+        ),
+        None => None,
+    };
+    let symbol = match (&query.symbol, &session) {
+        (Some(symbol), Some(s)) if *symbol != s.symbol => {
+            return Err(format!("Session #{} traded {}, not {}", s.id, s.symbol, symbol).into());
+        }
+        (Some(symbol), _) => symbol.clone(),
+        (None, Some(s)) => s.symbol.clone(),
+        (None, None) => sim.symbol.clone(),
+    };
+
+    let from = query
+        .from
+        .into_iter()
+        .chain(session.as_ref().map(|s| s.started_at))
+        .max();
+    let to = query
+        .to
+        .into_iter()
+        .chain(session.as_ref().and_then(|s| s.ended_at))
+        .chain(std::iter::once(now))
+        .min()
+        .expect("Range always ends by now");
+    if from.is_some_and(|from| from >= to) {
+        return Err("Report range is empty".into());
+    }
+
+    // balance of the account carries over between sessions, a session opens with
+    // the balance the bot traded it with
+    let ledger = store.get_closed_trades(&symbol).await?;
+    let mut initial_balance = match &session {
+        Some(s) => SessionBalance::new(s, &ledger).opening,
+        None => sim.initial_balance,
+    };
+    let opened = session.as_ref().map(|s| s.started_at);
+    let mut closed = Vec::new();
+    let mut open = Vec::new();
+    let ledger = ledger
+        .into_iter()
+        .chain(store.get_open_trades(&symbol).await?);
+    for mut trade in ledger {
+        match trade.exit_time {
+            // part of the opening balance of the session
+            Some(exit) if opened.is_some_and(|opened| exit < opened) => {}
+            Some(exit) if from.is_some_and(|from| exit < from) => {
+                initial_balance += trade.pnl.unwrap_or_default();
+            }
+            Some(exit) if exit < to => closed.push(trade),
+            _ if trade.entry_time < to => {
+                trade.exit_time = None;
+                trade.exit_price = None;
+                trade.pnl = None;
+                trade.mae = None;
+                trade.mfe = None;
+                trade.status = "OPEN".to_string();
+                open.push(trade);
+            }
+            _ => {}
+        }
+    }
+
+    // candles that closed within the range
+    let interval = timeframe_duration(&sim.timeframe)?.num_milliseconds();
+    let candles: Vec<CandleStick> = store
+        .get_last_candles(
+            &symbol,
+            &sim.timeframe,
+            to.timestamp_millis() - interval,
+            max_bars,
+        )
+        .await?
+        .into_iter()
+        .filter(|c| from.is_none_or(|from| c.timestamp >= from.timestamp_millis()))
+        .collect();
+
+    Ok(ReportSlice {
+        symbol,
+        timeframe: sim.timeframe.clone(),
+        from,
+        to,
+        initial_balance,
+        candles,
+        closed,
+        open,
+    })
+}
+
+impl ReportSlice {
+    fn interval_ms(&self) -> Result<i64, String> {
+        Ok(timeframe_duration(&self.timeframe)?.num_milliseconds())
+    }
+
+    // report as of `to`, its equity curve is marked to every candle close
+    pub fn report(&self) -> Result<AnalysisReport, String> {
+        let interval = self.interval_ms()?;
+        let ledger: Vec<Trade> = self.closed.iter().chain(&self.open).cloned().collect();
+        let last_price = self.candles.last().and_then(|c| Decimal::from_f64(c.close));
+        let positions = open_positions(&self.open, last_price);
+
+        let mut equity_curve =
+            candle_equity_curve(self.initial_balance, &ledger, &self.candles, interval);
+        if equity_curve.is_empty() {
+            let balance =
+                self.initial_balance + self.closed.iter().filter_map(|t| t.pnl).sum::<Decimal>();
+            let unrealized_pnl: Decimal = positions.iter().map(|p| p.unrealized_pnl).sum();
+            equity_curve.push(EquitySnapshot {
+                time: self.to,
+                balance,
+                unrealized_pnl,
+                equity: balance + unrealized_pnl,
+                exposure: positions.iter().map(|p| p.market_value).sum(),
+                price: last_price,
+            });
+        }
+        let bars_per_year = bars_per_year_ms(interval);
+        let benchmark = benchmark_curve(&equity_curve);
+
+        Ok(AnalysisReport {
+            symbol: self.symbol.clone(),
+            generated_at: self.to,
+            metrics: equity_metrics(&equity_curve, &self.closed, bars_per_year),
+            relative: benchmark_metrics(&equity_curve, &benchmark, bars_per_year),
+            benchmark,
+            equity_curve,
+            pnl_stats: pnl_stats(&self.closed),
+            last_price,
+            open_positions: positions,
+            holding_time: holding_time_stats(&self.closed, &self.open, self.to),
+            excursions: excursion_stats(&self.closed),
+            trades: ledger,
+        })
+    }
+
+    // charts of the slice with SMAs of the strategy and buy & hold of the report
+    pub fn html(
+        &self,
+        report: &AnalysisReport,
+        fast_period: usize,
+        slow_period: usize,
+    ) -> Result<HtmlReport, String> {
+        let interval = self.interval_ms()?;
+        let times: Vec<i64> = self
+            .candles
+            .iter()
+            .map(|c| c.timestamp + interval)
+            .collect();
+        let close: Vec<f64> = self.candles.iter().map(|c| c.close).collect();
+        let initial_balance = float(self.initial_balance);
+        let benchmark: Vec<f64> = report
+            .benchmark
+            .iter()
+            .take(self.candles.len())
+            .map(|b| b.map(float).unwrap_or(initial_balance))
+            .collect();
+        let buy_and_hold = performance_metrics(
+            &benchmark,
+            &vec![true; benchmark.len()],
+            &[],
+            bars_per_year_ms(interval),
+        );
+
+        let last_close = close.last().copied();
+        let trades: Vec<ChartTrade> = report
+            .trades
+            .iter()
+            .map(|t| ChartTrade {
+                entry_time: t.entry_time.timestamp_millis(),
+                entry_price: float(t.entry_price),
+                exit_time: t.exit_time.map(|e| e.timestamp_millis()),
+                exit_price: t.exit_price.map(float),
+                pnl: match (t.pnl, last_close) {
+                    (Some(pnl), _) => float(pnl),
+                    (None, Some(price)) => (price - float(t.entry_price)) * float(t.trade_size),
+                    (None, None) => 0.0,
+                },
+            })
+            .collect();
+
+        let stats = &report.pnl_stats;
+        let range = |time: Option<DateTime<Utc>>| {
+            time.map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "start".to_string())
+        };
+        let summary = vec![
+            ("Source".to_string(), "trades table".to_string()),
+            ("Symbol".to_string(), self.symbol.clone()),
+            ("Timeframe".to_string(), self.timeframe.clone()),
+            (
+                "Range".to_string(),
+                format!("{} - {}", range(self.from), range(Some(self.to))),
+            ),
+            (
+                "Fast / Slow SMA".to_string(),
+                format!("{} / {}", fast_period, slow_period),
+            ),
+            ("Bars".to_string(), self.candles.len().to_string()),
+            (
+                "Initial Balance".to_string(),
+                format!("{:.2}", self.initial_balance),
+            ),
+            (
+                "Final Equity".to_string(),
+                format!("{:.2}", report.current().equity),
+            ),
+            ("Closed Trades".to_string(), stats.total_trades.to_string()),
+            (
+                "Winning Rate".to_string(),
+                format!("{:.1}%", stats.win_rate * 100.0),
+            ),
+            (
+                "Profit Factor".to_string(),
+                format!("{:.2}", stats.profit_factor),
+            ),
+            (
+                "Open Positions".to_string(),
+                report.open_positions.len().to_string(),
+            ),
+        ];
+
+        Ok(HtmlReport {
+            title: format!("Trading Performance Report: {}", self.symbol),
+            summary,
+            fast_sma: sma_series(&close, fast_period),
+            slow_sma: sma_series(&close, slow_period),
+            times,
+            close,
+            equity: report
+                .equity_curve
+                .iter()
+                .take(self.candles.len())
+                .map(|p| float(p.equity))
+                .collect(),
+            benchmark,
+            trades,
+            metrics: vec![
+                ("strategy".to_string(), report.metrics.clone()),
+                ("buy & hold".to_string(), buy_and_hold),
+            ],
+        })
+    }
+}
+
+// one row of `report --group-by`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodStats {
+    pub start: NaiveDate,
+    pub trades: usize, // closed within the period
+    pub winners: usize,
+    pub realized_pnl: Decimal,
+    pub start_equity: Decimal, // equity at the end of previous period
+    pub end_equity: Decimal,
+    pub period_return: f64,
+    pub max_drawdown: f64, // within the period, from its start equity
+}
+
+// closed trades (by exit time) and equity curve of `report` split into periods,
+// the first one starts from `initial_balance`
+pub fn period_stats(
+    report: &AnalysisReport,
+    initial_balance: Decimal,
+    period: Period,
+) -> Vec<PeriodStats> {
+    let mut groups: BTreeMap<NaiveDate, (Vec<Decimal>, Vec<Decimal>)> = BTreeMap::new();
+    for point in &report.equity_curve {
+        groups
+            .entry(period.start(point.time))
+            .or_default()
+            .0
+            .push(point.equity);
+    }
+    for trade in &report.trades {
+        if let (Some(exit), Some(pnl)) = (trade.exit_time, trade.pnl) {
+            groups.entry(period.start(exit)).or_default().1.push(pnl);
+        }
+    }
+
+    let mut start_equity = initial_balance;
+    groups
+        .into_iter()
+        .map(|(start, (equity, pnls))| {
+            let end_equity = equity.last().copied().unwrap_or(start_equity);
+            let curve: Vec<f64> = std::iter::once(start_equity)
+                .chain(equity)
+                .map(float)
+                .collect();
+            let stats = PeriodStats {
+                start,
+                trades: pnls.len(),
+                winners: pnls.iter().filter(|pnl| **pnl >= Decimal::ZERO).count(),
+                realized_pnl: pnls.iter().sum(),
+                start_equity,
+                end_equity,
+                period_return: if start_equity.is_zero() {
+                    0.0
+                } else {
+                    float(end_equity / start_equity) - 1.0
+                },
+                max_drawdown: performance_metrics(&curve, &[], &[], 1.0).max_drawdown,
+            };
+            start_equity = end_equity;
+            stats
+        })
+        .collect()
+}
+
+pub fn format_periods(symbol: &str, period: Period, rows: &[PeriodStats]) -> String {
+    let mut text = format!(
+        "=== {} by {} ===\n{:<12}{:>8}{:>10}{:>14}{:>14}{:>14}{:>10}{:>10}\n",
+        symbol,
+        period.as_str(),
+        "Period",
+        "Trades",
+        "Win Rate",
+        "Realized PnL",
+        "Start Equity",
+        "End Equity",
+        "Return",
+        "Max DD"
+    );
+    for row in rows {
+        let win_rate = if row.trades == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", row.winners as f64 / row.trades as f64 * 100.0)
+        };
+        let _ = writeln!(
+            text,
+            "{:<12}{:>8}{:>10}{:>14}{:>14}{:>14}{:>10}{:>10}",
+            row.start.to_string(),
+            row.trades,
+            win_rate,
+            format!("${:.2}", row.realized_pnl),
+            format!("${:.2}", row.start_equity),
+            format!("${:.2}", row.end_equity),
+            format!("{:.2}%", row.period_return * 100.0),
+            format!("{:.2}%", row.max_drawdown * 100.0),
+        );
+    }
+    text
+}
+
+// `<symbol>_<period>s` table for `--output`
+pub fn periods_frame(rows: &[PeriodStats]) -> PolarsResult<DataFrame> {
+    df!(
+        "period" => rows.iter().map(|r| r.start).collect::<Vec<_>>(),
+        "trades" => rows.iter().map(|r| r.trades as u64).collect::<Vec<_>>(),
+        "winners" => rows.iter().map(|r| r.winners as u64).collect::<Vec<_>>(),
+        "realized_pnl" => rows.iter().map(|r| float(r.realized_pnl)).collect::<Vec<_>>(),
+        "start_equity" => rows.iter().map(|r| float(r.start_equity)).collect::<Vec<_>>(),
+        "end_equity" => rows.iter().map(|r| float(r.end_equity)).collect::<Vec<_>>(),
+        "return" => rows.iter().map(|r| r.period_return).collect::<Vec<_>>(),
+        "max_drawdown" => rows.iter().map(|r| r.max_drawdown).collect::<Vec<_>>(),
+    )
+}
+
+// `bot report`: prints the report of `query` (and its periods), optionally writes
+// it with `export` and as HTML page to `html`
+pub async fn run_report(
+    query: ReportQuery,
+    group_by: Option<Period>,
+    html: Option<&Path>,
+    export: Option<Export>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sim = Settings::load()?.trading_simulation;
+
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set inside .env file");
+    let store = Store::connect(&database_url).await?;
+
+    let slice = load_slice(&store, &sim, &query, MAX_BARS, SystemClock.now()).await?;
+    let report = slice.report()?;
+    println!("{}", report.format_text());
+
+    let mut tables = report.to_frames()?;
+    if let Some(period) = group_by {
+        let rows = period_stats(&report, slice.initial_balance, period);
+        println!("{}", format_periods(&slice.symbol, period, &rows));
+        tables.push((
+            format!("{}_{}s", slice.symbol, period.as_str()),
+            periods_frame(&rows)?,
+        ));
+    }
+    if let Some(export) = export {
+        for path in export.write_all(tables)? {
+            println!("[EXPORT] {}", path.display());
+        }
+    }
+    if let Some(path) = html {
+        slice
+            .html(&report, sim.fast_period as usize, sim.slow_period as usize)?
+            .write(path)?;
+        println!("[REPORT] {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixtures::trading_simulation;
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::trading_simulation::trade_analysis_report::generate_report;
+    use crate::utils::clock::SimulatedClock;
+    use rust_decimal::dec;
+
+    const T0: i64 = 1_735_689_600_000; // 2025-01-01 00:00 UTC
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(T0 + minute * 60_000).unwrap()
+    }
+
+    // 6 one minute candles closing at 100..105, bought at close of bar 1, sold at
+    // close of bar 3, bought again at close of bar 4
+    async fn store_with_trades() -> MemoryStore {
+        let store = MemoryStore::default();
+        let candles: Vec<CandleStick> = (0..6)
+            .map(|i| CandleStick {
+                symbol: "BTCUSDT".into(),
+                open: 100.0 + i as f64,
+                high: 101.0 + i as f64,
+                low: 99.0 + i as f64,
+                close: 100.0 + i as f64,
+                volume: 1.0,
+                timestamp: T0 + i * 60_000,
+            })
+            .collect();
+        store.upsert_candles("1m", &candles).await.unwrap();
+        store
+            .record_open_trade("BTCUSDT", dec!(101), dec!(1), dec!(101), at(2))
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(103), dec!(2), at(4))
+            .await
+            .unwrap();
+        store
+            .record_open_trade("BTCUSDT", dec!(104), dec!(2), dec!(208), at(5))
+            .await
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_parse_time_and_period_start() {
+        assert_eq!(parse_time("2025-01-01").unwrap(), at(0));
+        assert_eq!(parse_time("2025-01-01T00:02:00Z").unwrap(), at(2));
+        assert!(parse_time("01/01/2025").is_err());
+
+        // 2025-01-15 is a Wednesday
+        let time = parse_time("2025-01-15T13:00:00+00:00").unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        assert_eq!(Period::Day.start(time), day(15));
+        assert_eq!(Period::Week.start(time), day(13));
+        assert_eq!(Period::Month.start(time), day(1));
+    }
+
+    #[tokio::test]
+    async fn test_report_of_whole_history() {
+        let store = store_with_trades().await;
+        let sim = trading_simulation("BTCUSDT", 2, 3);
+        let slice = load_slice(&store, &sim, &ReportQuery::default(), 1000, at(60))
+            .await
+            .unwrap();
+        let report = slice.report().unwrap();
+
+        let equity: Vec<Decimal> = report.equity_curve.iter().map(|p| p.equity).collect();
+        assert_eq!(
+            equity,
+            [500, 500, 501, 502, 502, 504].map(Decimal::from).to_vec()
+        );
+        assert_eq!(report.pnl_stats.total_trades, 1);
+        assert_eq!(report.open_positions[0].unrealized_pnl, dec!(2));
+        assert_eq!(report.metrics.exposure, 4.0 / 6.0);
+
+        let html = slice.html(&report, 2, 3).unwrap();
+        assert_eq!(html.times[0], T0 + 60_000);
+        assert_eq!(html.equity, vec![500.0, 500.0, 501.0, 502.0, 502.0, 504.0]);
+        assert_eq!(html.benchmark[5], 525.0);
+        assert_eq!(html.slow_sma[1], None);
+        assert_eq!(html.slow_sma[2], Some(101.0));
+        assert_eq!(html.trades.len(), 2);
+        assert_eq!(html.trades[1].pnl, 2.0);
+        assert!(html.render().contains("<td>Open Positions</td><td>1</td>"));
+    }
+
+    #[tokio::test]
+    async fn test_report_of_range_and_session() {
+        let store = store_with_trades().await;
+        let sim = trading_simulation("BTCUSDT", 2, 3);
+
+        // first trade still open at the end of the range
+        let query = ReportQuery {
+            to: Some(at(3)),
+            ..Default::default()
+        };
+        let slice = load_slice(&store, &sim, &query, 1000, at(60))
+            .await
+            .unwrap();
+        assert_eq!(slice.candles.len(), 3);
+        assert!(slice.closed.is_empty());
+        assert_eq!(slice.open.len(), 1);
+        assert_eq!(slice.open[0].exit_time, None);
+        assert_eq!(slice.report().unwrap().current().unrealized_pnl, dec!(1));
+
+        // realized pnl before the range is part of its starting balance
+        let session = store
+            .start_session("BTCUSDT", "paper", dec!(500), at(5))
+            .await
+            .unwrap();
+        store.end_session(session, at(6)).await.unwrap();
+        let query = ReportQuery {
+            session: Some(session),
+            ..Default::default()
+        };
+        let slice = load_slice(&store, &sim, &query, 1000, at(60))
+            .await
+            .unwrap();
+        assert_eq!(slice.initial_balance, dec!(502));
+        assert_eq!(slice.from, Some(at(5)));
+        assert_eq!(slice.to, at(6));
+        assert_eq!(slice.candles.len(), 1);
+        assert_eq!(slice.open.len(), 1);
+
+        let query = ReportQuery {
+            symbol: Some("ETHUSDT".into()),
+            session: Some(session),
+            ..Default::default()
+        };
+        assert!(
+            load_slice(&store, &sim, &query, 1000, at(60))
+                .await
+                .is_err()
+        );
+        let query = ReportQuery {
+            from: Some(at(5)),
+            to: Some(at(5)),
+            ..Default::default()
+        };
+        assert!(
+            load_slice(&store, &sim, &query, 1000, at(60))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_report_matches_live_report() {
+        let store = MemoryStore::default();
+        let sim = trading_simulation("BTCUSDT", 2, 3);
+        let candles: Vec<CandleStick> = (0..20)
+            .map(|i| CandleStick {
+                symbol: "BTCUSDT".into(),
+                open: 100.0 + i as f64,
+                high: 101.0 + i as f64,
+                low: 99.0 + i as f64,
+                close: 100.0 + i as f64,
+                volume: 1.0,
+                timestamp: T0 + i * 60_000,
+            })
+            .collect();
+        store.upsert_candles("1m", &candles).await.unwrap();
+
+        // first session closes a winner
+        let first = store
+            .start_session("BTCUSDT", "paper", dec!(500), at(0))
+            .await
+            .unwrap();
+        store
+            .record_open_trade("BTCUSDT", dec!(101), dec!(1), dec!(101), at(2))
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(103), dec!(2), at(4))
+            .await
+            .unwrap();
+        store.end_session(first, at(6)).await.unwrap();
+
+        // second session as the trading loop runs it: a report and a snapshot per bar
+        let second = store
+            .start_session("BTCUSDT", "paper", dec!(500), at(10))
+            .await
+            .unwrap();
+        let clock = SimulatedClock::new(at(10));
+        let mut live = None;
+        for bar in 10..16 {
+            clock.advance_to(at(bar + 1));
+            let close = Decimal::from(100 + bar);
+            match bar {
+                11 => {
+                    store
+                        .record_open_trade("BTCUSDT", close, dec!(1), close, at(bar + 1))
+                        .await
+                        .unwrap();
+                }
+                13 => {
+                    store
+                        .record_close_trade(2, close, dec!(2), at(bar + 1))
+                        .await
+                        .unwrap();
+                }
+                14 => {
+                    store
+                        .record_open_trade("BTCUSDT", close, dec!(2), close * dec!(2), at(bar + 1))
+                        .await
+                        .unwrap();
+                }
+                _ => {}
+            }
+            let report = generate_report(
+                &store,
+                second,
+                "BTCUSDT",
+                Some(close),
+                bars_per_year_ms(60_000),
+                &clock,
+            )
+            .await
+            .unwrap();
+            store
+                .insert_equity_snapshot(second, "BTCUSDT", report.current())
+                .await
+                .unwrap();
+            live = Some(report);
+        }
+        store.end_session(second, clock.now()).await.unwrap();
+        let live = live.unwrap();
+
+        // `bot report --session 2`
+        let query = ReportQuery {
+            session: Some(second),
+            ..Default::default()
+        };
+        let slice = load_slice(&store, &sim, &query, 1000, at(60))
+            .await
+            .unwrap();
+        let report = slice.report().unwrap();
+
+        assert_eq!(slice.initial_balance, dec!(502));
+        assert_eq!(live.equity_curve[0].equity, slice.initial_balance);
+        assert_eq!(report.current(), live.current());
+        let equity = |curve: &[EquitySnapshot]| curve.iter().map(|p| p.equity).collect::<Vec<_>>();
+        assert_eq!(
+            equity(&report.equity_curve),
+            equity(&live.equity_curve[1..])
+        );
+        assert_eq!(report.pnl_stats.total_trades, 1);
+        assert_eq!(report.pnl_stats.total_trades, live.pnl_stats.total_trades);
+        assert_eq!(report.pnl_stats.gross_pnl, live.pnl_stats.gross_pnl);
+        assert_eq!(report.open_positions.len(), live.open_positions.len());
+        assert_eq!(
+            report.open_positions[0].unrealized_pnl,
+            live.open_positions[0].unrealized_pnl
+        );
+    }
+
+    #[tokio::test]
+    async fn test_period_stats() {
+        let store = MemoryStore::default();
+        let sim = trading_simulation("BTCUSDT", 2, 3);
+        let day = 24 * 60;
+        // candle every 6 hours for 3 days, loss on day 1, win on day 3
+        let candles: Vec<CandleStick> = (0..12)
+            .map(|i| CandleStick {
+                symbol: "BTCUSDT".into(),
+                open: 100.0,
+                high: 100.0,
+                low: 100.0,
+                close: 100.0,
+                volume: 1.0,
+                timestamp: T0 + i * 360 * 60_000,
+            })
+            .collect();
+        store.upsert_candles("1m", &candles).await.unwrap();
+        for (id, (entry, pnl)) in [(10, dec!(-5)), (2 * day + 10, dec!(15))]
+            .into_iter()
+            .enumerate()
+        {
+            store
+                .record_open_trade("BTCUSDT", dec!(100), dec!(1), dec!(100), at(entry))
+                .await
+                .unwrap();
+            store
+                .record_close_trade(id as i64 + 1, dec!(100) + pnl, pnl, at(entry + 10))
+                .await
+                .unwrap();
+        }
+        let slice = load_slice(&store, &sim, &ReportQuery::default(), 1000, at(3 * day))
+            .await
+            .unwrap();
+        let report = slice.report().unwrap();
+
+        let rows = period_stats(&report, slice.initial_balance, Period::Day);
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        assert_eq!(
+            rows.iter().map(|r| r.start).collect::<Vec<_>>(),
+            [day(1), day(2), day(3)]
+        );
+        assert_eq!(rows[0].trades, 1);
+        assert_eq!(rows[0].winners, 0);
+        assert_eq!(rows[0].realized_pnl, dec!(-5));
+        assert_eq!(rows[0].end_equity, dec!(495));
+        assert!((rows[0].max_drawdown - 0.01).abs() < 1e-12);
+        assert_eq!(rows[1].trades, 0);
+        assert_eq!(rows[1].start_equity, dec!(495));
+        assert_eq!(rows[1].period_return, 0.0);
+        assert_eq!(rows[2].end_equity, dec!(510));
+
+        // 2025-01-01 is a Wednesday, the whole slice falls into one week
+        let rows = period_stats(&report, slice.initial_balance, Period::Week);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].start,
+            NaiveDate::from_ymd_opt(2024, 12, 30).unwrap()
+        );
+        assert_eq!(rows[0].trades, 2);
+        assert_eq!(rows[0].realized_pnl, dec!(10));
+        assert!((rows[0].period_return - 0.02).abs() < 1e-12);
+
+        let text = format_periods("BTCUSDT", Period::Week, &rows);
+        assert!(text.starts_with("=== BTCUSDT by week ==="));
+        assert!(text.contains("2024-12-30"));
+        assert!(text.contains("50.0%"));
+        assert_eq!(periods_frame(&rows).unwrap().height(), 1);
+    }
+}
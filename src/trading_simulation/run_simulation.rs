@@ -1,22 +1,147 @@
-use crate::trading_simulation::database::connection;
-use crate::trading_simulation::network::api::market::spawn_cron_market_feed;
-use crate::trading_simulation::strategy::sma_crossover::execute_trade_strategy;
-use crate::trading_simulation::trade_analysis_report::generate_report;
-use crate::utils::config::Settings;
+use crate::trading_simulation::database::store::{Store, TradeStore};
+use crate::trading_simulation::exchange::binance::BinanceExecutor;
+use crate::trading_simulation::exchange::executor::Executor;
+use crate::trading_simulation::exchange::paper::PaperExchange;
+use crate::trading_simulation::exchange::reconcile::spawn_reconciliation;
+use crate::trading_simulation::monitoring::metrics::BotMetrics;
+use crate::trading_simulation::monitoring::status::{BotStatus, Indicators};
+use crate::trading_simulation::monitoring::{MonitorState, spawn_http_server};
+use crate::trading_simulation::network::api::exchange_info::load_symbol_filters;
+use crate::trading_simulation::network::api::market::{CRON_EXPRESSION, spawn_cron_market_feed};
+use crate::trading_simulation::replay::{load_replay_candles, spawn_replay_feed};
+use crate::trading_simulation::strategy::sma_crossover::{execute_trade_strategy, sma_crossover};
+use crate::trading_simulation::trade_analysis_report::{
+    SessionBalance, generate_report, record_excursions,
+};
+use crate::utils::clock::{Clock, SimulatedClock, SystemClock};
+use crate::utils::config::{ExecutionMode, Settings, TradingSimulation, timeframe_duration};
+use crate::utils::data_quality::apply_quality_policy;
+use crate::utils::export::Export;
+use crate::utils::metrics::bars_per_year_ms;
 use crate::utils::objects::CandleStick;
 
+use chrono::DateTime;
 use dotenv::dotenv;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
 use std::env;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 
-pub async fn run_trading_simulation() -> Result<(), Box<dyn std::error::Error>> {
+// where candle batches for the trading loop come from
+pub enum FeedSource {
+    // Binance klines fetched on cron `schedule`
+    Live {
+        schedule: String,
+    },
+    // recorded candles, `speed` x real time or as fast as possible when 0;
+    // zero volume is only checked when source had a volume column
+    Replay {
+        candles: Vec<CandleStick>,
+        speed: f64,
+        has_volume: bool,
+    },
+}
+
+// `replay`: path to recorded market data (parquet, csv or zip) to replay instead of live feed
+pub async fn run_trading_simulation(
+    replay: Option<String>,
+    speed: f64,
+    export: Option<Export>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // config load
-    let sim = Settings::load()
-        .expect("Failed to load settings")
-        .trading_simulation;
+    let settings = Settings::load().expect("Failed to load settings");
+    let sim = settings.trading_simulation;
 
     sim.print_trading_simulation_params();
 
+    // real orders only ever follow real market
+    if sim.mode == ExecutionMode::Live && replay.is_some() {
+        return Err("Live mode cannot be combined with --replay".into());
+    }
+
+    let feed = match replay {
+        Some(path) => {
+            println!("[REPLAY] Reading data from: {}", path);
+            let (candles, has_volume) = load_replay_candles(
+                &path,
+                &settings.backtest.columns,
+                &sim.symbol,
+                &sim.timeframe,
+                sim.data_quality,
+            )?;
+            FeedSource::Replay {
+                candles,
+                speed,
+                has_volume,
+            }
+        }
+        None => FeedSource::Live {
+            schedule: CRON_EXPRESSION.to_string(),
+        },
+    };
+
+    dotenv().ok(); // load env variables
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set inside .env file");
+
+    // backend follows url scheme, schema is brought up to date on connect
+    let store = Store::connect(&database_url).await?;
+
+    // live trades are stamped with wall clock, replayed ones with candle time
+    let clock: Arc<dyn Clock> = match &feed {
+        FeedSource::Live { .. } => Arc::new(SystemClock),
+        FeedSource::Replay { candles, .. } => Arc::new(SimulatedClock::new(
+            candles
+                .first()
+                .and_then(|c| DateTime::from_timestamp_millis(c.timestamp))
+                .unwrap_or_default(),
+        )),
+    };
+
+    // order sizing follows exchange rules of the traded symbol
+    let filters = load_symbol_filters(&sim.api_url, &sim.symbol, &sim.exchange_info_path).await?;
+    let shutdown = tokio::signal::ctrl_c();
+
+    match sim.mode {
+        ExecutionMode::Paper => {
+            // orders submitted by strategy are filled on following candles
+            let interval_ms = timeframe_duration(&sim.timeframe)?.num_milliseconds();
+            let exchange =
+                PaperExchange::new(store.clone(), filters, interval_ms, sim.participation_rate);
+            run_trading_loop(sim, store, feed, clock, exchange, export, shutdown).await
+        }
+        ExecutionMode::Live => {
+            let exchange = BinanceExecutor::from_env(&sim.api_url, store.clone(), filters)?;
+            println!(
+                "[LIVE] Real orders for {} are placed on {}",
+                sim.symbol, sim.api_url
+            );
+            // user data stream books executions as they happen and flags manual trades
+            let reconciliation =
+                spawn_reconciliation(exchange.clone(), sim.ws_url.clone(), clock.clone());
+            let result =
+                run_trading_loop(sim, store, feed, clock, exchange, export, shutdown).await;
+            reconciliation.abort();
+            result
+        }
+    }
+}
+
+// market feed -> channel -> strategy pipeline, runs until `shutdown` resolves
+// or until feed runs out of candles (replay); `clock` stamps orders and reports,
+// `exchange` executes orders of the strategy, `export` rewrites report files each bar
+pub async fn run_trading_loop(
+    sim: TradingSimulation,
+    store: Store,
+    feed: FeedSource,
+    clock: Arc<dyn Clock>,
+    exchange: impl Executor + 'static,
+    export: Option<Export>,
+    shutdown: impl Future<Output = std::io::Result<()>>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // config constants
     let timeframe = sim
         .timeframe_as_binance()
@@ -25,85 +150,521 @@ pub async fn run_trading_simulation() -> Result<(), Box<dyn std::error::Error>>
     let initial_balance = sim.initial_balance;
     let fast_period = sim.fast_period;
     let slow_period = sim.slow_period;
+    let data_quality = sim.data_quality;
+    let interval_ms = timeframe_duration(&sim.timeframe)?.num_milliseconds();
+    let timeframe_name = sim.timeframe.clone();
 
-    dotenv().ok(); // load env variables
-
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set inside .env file");
-
-    let pool = connection::create_db_connection(&database_url)
-        .await
-        .expect("Connection to database failed");
-
-    // atm market prices in db are not used in trading simulation since `lookback` is small
-    // in case we would implement other trading strategies, that would rely on ML or some heavy stat anaysis
-    // having those prices stored in db would come handy
-
-    // start from a clean slate on each run
-    // crud::clear_prices_table(&pool)
-    //     .await
-    //     .expect("Failed to clear prices table");
-
-    // required data for initial input into `price` table
-    // crud::insert_prices(
-    //     &pool,
-    //     fetch_market_data(symbol, slow_period, timeframe)
-    //         .await
-    //         .expect("Failed to fetch historical market data"),
-    // )
-    // .await
-    // .expect("Failed to insert historical prices");
+    // loop state for Prometheus, market feed records its fetches too
+    let metrics = Arc::new(BotMetrics::new(&symbol));
 
     // multi-producer single-consumer channel - multiple transmitors, only one receiver
     // channel capacity: one batch of candlestics (only vector can wait in channel)
     let (tx, mut rx) = mpsc::channel::<Vec<CandleStick>>(1);
 
+    // status API port is bound first: a taken port fails the run before the feed
+    // and the session start, nothing is left to clean up
+    let listener = match &sim.http_addr {
+        Some(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            println!("[HTTP] Serving on http://{}", listener.local_addr()?);
+            Some(listener)
+        }
+        None => None,
+    };
+
+    let session_id = store
+        .start_session(&symbol, sim.mode.as_str(), initial_balance, clock.now())
+        .await?;
+    println!("[SESSION] #{} started", session_id);
+    let session = store
+        .get_session(session_id)
+        .await?
+        .ok_or("Started session not found")?;
+
+    // metrics and status API, served only when `http_addr` is configured
+    let status = Arc::new(BotStatus::new(session_id, &sim));
+    let server = match listener {
+        Some(listener) => {
+            let state = MonitorState {
+                metrics: metrics.clone(),
+                status: status.clone(),
+            };
+            Some(spawn_http_server(listener, state))
+        }
+        None => None,
+    };
+
     // periodically (each minute) fetch market data, aka cron process as tokio task
-    // send batch candlesticks into channel
-    spawn_cron_market_feed(symbol.clone(), slow_period, timeframe, tx).await;
+    // or replay recorded candles, either way batch candlesticks are sent into channel
+    let has_volume = match &feed {
+        FeedSource::Live { .. } => true,
+        FeedSource::Replay { has_volume, .. } => *has_volume,
+    };
+    let mut scheduler = match feed {
+        FeedSource::Live { schedule } => Some(
+            spawn_cron_market_feed(
+                sim.api_url.clone(),
+                symbol.clone(),
+                slow_period,
+                timeframe,
+                &schedule,
+                tx,
+                metrics.clone(),
+            )
+            .await,
+        ),
+        FeedSource::Replay { candles, speed, .. } => {
+            let interval = std::time::Duration::from_millis(interval_ms as u64);
+            spawn_replay_feed(candles, slow_period, interval, speed, tx);
+            None
+        }
+    };
+    let session_store = store.clone();
+    let session_clock = clock.clone();
 
     // trading execution task
-    tokio::spawn(async move {
+    let trading = tokio::spawn(async move {
+        let mut measured_until = None; // close of the last batch trades were measured at
+
         // main processing lopp:
         // wait for incoming batch of candles from channel
         // and do trading part of simulation
         while let Some(candlesticks) = rx.recv().await {
-            let mut current_balance = initial_balance;
-
             if candlesticks.is_empty() {
                 continue;
             } // no trade if batch is empty
 
-            // let last_candle = candlesticks.last().unwrap();
-            // println!("Last candle: {:?}", last_candle);
+            // every received candle is kept in the store, live feed sends only
+            // new candles and refetched ones replace what is stored
+            let last_open_time = candlesticks.last().unwrap().timestamp;
+            if let Err(e) = store.upsert_candles(&timeframe_name, &candlesticks).await {
+                eprintln!("Failed to store candles: {e}");
+                metrics.record_db_error();
+                continue;
+            }
+
+            // strategy window is read back from the store, bounded by the latest
+            // received candle so replay never sees candles stored by other sessions
+            let candlesticks = match store
+                .get_last_candles(&symbol, &timeframe_name, last_open_time, slow_period as i64)
+                .await
+            {
+                Ok(candlesticks) => candlesticks,
+                Err(e) => {
+                    eprintln!("Failed to read candles: {e}");
+                    metrics.record_db_error();
+                    continue;
+                }
+            };
+
+            // strategy never sees a corrupt window: batch is repaired or skipped
+            let candlesticks =
+                match apply_quality_policy(candlesticks, interval_ms, data_quality, has_volume) {
+                    Ok((candlesticks, report)) => {
+                        if !report.is_clean() {
+                            println!("[DATA QUALITY] {} -> {:?}", report, data_quality);
+                        }
+                        candlesticks
+                    }
+                    Err(report) => {
+                        eprintln!("[DATA QUALITY] Rejected candle batch, {}", report);
+                        continue;
+                    }
+                };
+            if candlesticks.len() < slow_period as usize {
+                eprintln!(
+                    "[DATA QUALITY] Only {} valid candles, {} needed for slow SMA",
+                    candlesticks.len(),
+                    slow_period
+                );
+                continue;
+            }
+
+            // market time is close of the latest candle, simulated clock follows it
+            // so replayed sessions look exactly like the recorded market
+            let last_candle = candlesticks.last().unwrap();
+            if let Some(close_time) =
+                DateTime::from_timestamp_millis(last_candle.timestamp + interval_ms)
+            {
+                clock.advance_to(close_time);
+                metrics.record_candle(close_time);
+            }
+
+            // orders working since previous batch are matched against new candles first
+            if let Err(e) = exchange
+                .on_candles(&symbol, &candlesticks, clock.as_ref())
+                .await
+            {
+                eprintln!("Failed to match orders: {e}");
+                continue;
+            }
 
-            // for the  current tradim simulation configuration not needed
-            // since current trading config strategy is not so computationally heavy
-            // crud::add_price(&pool, last_candle.clone(), 100 as i32)
-            //     .await
-            //     .expect("Failed to insert price");
+            // balance is derived from the ledger on every batch, so it compounds
+            // across trades and survives restarts, same balance the report shows
+            let current_balance = match store.get_closed_trades(&symbol).await {
+                Ok(closed) => SessionBalance::new(&session, &closed).current(),
+                Err(e) => {
+                    eprintln!("Failed to read closed trades: {e}");
+                    metrics.record_db_error();
+                    continue;
+                }
+            };
 
             // decision engine with db log, (buy/hold/sell)
-            execute_trade_strategy(
-                &pool,
+            let crossover = sma_crossover(&candlesticks, fast_period, slow_period);
+            let signal = match execute_trade_strategy(
+                &store,
                 &candlesticks,
-                &mut current_balance,
+                current_balance,
                 &symbol,
-                fast_period,
-                slow_period,
+                &crossover,
+                clock.as_ref(),
+                &exchange,
             )
             .await
-            .expect("Failed to evaluate decision");
+            {
+                Ok(signal) => signal,
+                Err(e) => {
+                    eprintln!("Failed to evaluate decision: {e}");
+                    continue;
+                }
+            };
+            if let Some(side) = signal {
+                metrics.record_signal(side);
+            }
+            status.record_indicators(Indicators {
+                time: clock.now(),
+                close: last_candle.close,
+                fast_sma: crossover.fast_sma,
+                slow_sma: crossover.slow_sma,
+                bullish: crossover.bullish,
+            });
+
+            // trades closed by this batch are measured against the stored candles
+            // they were held through, the first batch also catches up on earlier ones
+            match record_excursions(
+                &store,
+                &symbol,
+                &timeframe_name,
+                interval_ms,
+                measured_until,
+            )
+            .await
+            {
+                Ok(_) => measured_until = Some(clock.now()),
+                Err(e) => {
+                    eprintln!("Failed to record trade excursions: {e}");
+                    metrics.record_db_error();
+                }
+            }
 
-            match generate_report(&pool, &symbol, initial_balance).await {
+            // open positions are marked to the close of the latest candle, every
+            // bar leaves an equity snapshot so the curve includes unrealized moves
+            let last_price = Decimal::from_f64(candlesticks.last().unwrap().close);
+            match generate_report(
+                &store,
+                session_id,
+                &symbol,
+                last_price,
+                bars_per_year_ms(interval_ms),
+                clock.as_ref(),
+            )
+            .await
+            {
                 Ok(report) => {
+                    let report = Arc::new(report);
+                    metrics.record_report(&report);
+                    status.record_report(report.clone());
+                    if let Err(e) = store
+                        .insert_equity_snapshot(session_id, &symbol, report.current())
+                        .await
+                    {
+                        eprintln!("Failed to store equity snapshot: {e}");
+                        metrics.record_db_error();
+                    }
                     println!("{}", report.format_text());
+                    if let Some(export) = &export
+                        && let Err(e) = report.to_frames().and_then(|t| export.write_all(t))
+                    {
+                        eprintln!("Failed to export report: {e}");
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to generate report: {e}");
+                    metrics.record_db_error();
                 }
-                Err(e) => eprintln!("Failed to generate report: {e}"),
             }
-            
         }
     });
 
-    tokio::signal::ctrl_c().await?;
-    Ok(())
+    // live feed never runs dry, replay ends once all candles are processed;
+    // a panicking trading task fails the run instead of ending it quietly
+    let stopped: Result<(), Box<dyn std::error::Error>> = tokio::select! {
+        result = shutdown => result.map_err(Into::into),
+        result = trading => result.map_err(|e| format!("Trading task failed: {e}").into()),
+    };
+    if let Some(scheduler) = scheduler.as_mut() {
+        scheduler.shutdown().await?;
+    }
+    if let Some(server) = server {
+        server.abort();
+    }
+    session_store
+        .end_session(session_id, session_clock.now())
+        .await?;
+    println!("[SESSION] #{} ended", session_id);
+    stopped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixtures::trading_simulation;
+    use crate::test_support::mock_binance::MockBinance;
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::trading_simulation::exchange::order::{OrderSide, OrderStatus};
+    use crate::trading_simulation::network::api::exchange_info::{
+        SymbolFilters, parse_exchange_info,
+    };
+    use crate::trading_simulation::network::api::market::parse_klines;
+    use crate::utils::data_io::ColumnMapping;
+    use crate::utils::data_quality::QualityPolicy;
+    use crate::utils::export::OutputFormat;
+    use polars::prelude::{ParquetReader, SerReader};
+    use rust_decimal::dec;
+    use std::time::Duration;
+
+    // test symbols are made up to keep their trades apart, rules are borrowed from BTCUSDT
+    fn btcusdt_filters() -> SymbolFilters {
+        let body = std::fs::read_to_string("tests/fixtures/binance/exchange_info_btcusdt.json")
+            .expect("Missing fixture");
+        parse_exchange_info("BTCUSDT", &body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trading_loop_against_mock() {
+        let mock = MockBinance::start().await;
+        let symbol = "MOCKE2EUSDT";
+        // neither market nor database needs a server
+        let store = Store::Memory(MemoryStore::default());
+
+        let sim = TradingSimulation {
+            api_url: mock.http_url(),
+            ws_url: mock.ws_url(),
+            ..trading_simulation(symbol, 10, 25)
+        };
+
+        // feed fires every second, recorded klines end in an uptrend -> buy order
+        let feed = FeedSource::Live {
+            schedule: "* * * * * *".into(),
+        };
+        run_trading_loop(
+            sim,
+            store.clone(),
+            feed,
+            Arc::new(SystemClock),
+            PaperExchange::new(store.clone(), btcusdt_filters(), 60_000, None),
+            None,
+            async {
+                tokio::time::sleep(Duration::from_secs(4)).await;
+                Ok(())
+            },
+        )
+        .await
+        .expect("Trading loop failed");
+
+        // mock keeps serving the same candles, none of them closes after the order
+        // was submitted, so it stays working and is not duplicated by later batches
+        let working = store.get_working_orders(symbol).await.unwrap();
+        assert_eq!(working.len(), 1, "Exactly one buy order should be working");
+        assert_eq!(working[0].side, OrderSide::Buy);
+        assert!(store.get_open_trades(symbol).await.unwrap().is_empty());
+        assert!(mock.request_count() >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_replay_records_candle_timestamps() {
+        let store = Store::connect("sqlite::memory:")
+            .await
+            .expect("Failed to open database");
+        let symbol = "MOCKREPLAYUSDT";
+
+        let body = std::fs::read_to_string("tests/fixtures/binance/klines_btcusdt_1m_60.json")
+            .expect("Missing fixture");
+        let candles = parse_klines(symbol, &body).unwrap();
+        let (first, last) = (candles[0].timestamp, candles[59].timestamp);
+        let clock = Arc::new(SimulatedClock::new(
+            DateTime::from_timestamp_millis(first).unwrap(),
+        ));
+
+        let sim = trading_simulation(symbol, 3, 8);
+
+        // replay ends on its own, shutdown signal never fires
+        let feed = FeedSource::Replay {
+            candles,
+            speed: 0.0,
+            has_volume: true,
+        };
+        run_trading_loop(
+            sim,
+            store.clone(),
+            feed,
+            clock,
+            PaperExchange::new(store.clone(), btcusdt_filters(), 60_000, None),
+            None,
+            std::future::pending(),
+        )
+        .await
+        .expect("Replay failed");
+
+        // recorded klines end in an uptrend, buy order submitted at candle close is
+        // filled at open of the next candle, trade carries that candle time
+        let order = store.get_order(1).await.unwrap().expect("Order not stored");
+        assert!(store.get_order(2).await.unwrap().is_none());
+        assert_eq!(order.status, OrderStatus::Filled);
+
+        let open = store.get_open_trades(symbol).await.unwrap();
+        assert_eq!(open.len(), 1, "Replay should leave one open long");
+        // 500 USDT at ~93k on 0.00001 lot step
+        assert_eq!(open[0].trade_size, dec!(0.00535));
+        assert_eq!(Some(open[0].entry_price), order.avg_fill_price);
+        let entry = open[0].entry_time.timestamp_millis();
+        assert_eq!(entry, order.created_at.timestamp_millis());
+        assert!(first < entry && entry <= last);
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_volume() {
+        let symbol = "MOCKREPLAYUSDT";
+        // same klines as above, recorded without volume column
+        let (candles, has_volume) = load_replay_candles(
+            "tests/fixtures/klines_btcusdt_1m_no_volume.csv",
+            &ColumnMapping::default(),
+            symbol,
+            "1m",
+            QualityPolicy::Reject,
+        )
+        .expect("Failed to load replay candles");
+        assert!(!has_volume);
+        let clock = Arc::new(SimulatedClock::new(
+            DateTime::from_timestamp_millis(candles[0].timestamp).unwrap(),
+        ));
+
+        let store = Store::Memory(MemoryStore::default());
+        let feed = FeedSource::Replay {
+            candles,
+            speed: 0.0,
+            has_volume,
+        };
+        run_trading_loop(
+            trading_simulation(symbol, 3, 8),
+            store.clone(),
+            feed,
+            clock,
+            PaperExchange::new(store.clone(), btcusdt_filters(), 60_000, None),
+            None,
+            std::future::pending(),
+        )
+        .await
+        .expect("Replay failed");
+
+        // zero volume of every candle does not reject the windows
+        let open = store.get_open_trades(symbol).await.unwrap();
+        assert_eq!(open.len(), 1, "Replay should leave one open long");
+        assert_eq!(open[0].trade_size, dec!(0.00535));
+    }
+
+    #[tokio::test]
+    async fn test_replay_on_memory_store() {
+        let symbol = "MOCKREPLAYUSDT";
+        let body = std::fs::read_to_string("tests/fixtures/binance/klines_btcusdt_1m_60.json")
+            .expect("Missing fixture");
+        let candles = parse_klines(symbol, &body).unwrap();
+        let clock = Arc::new(SimulatedClock::new(
+            DateTime::from_timestamp_millis(candles[0].timestamp).unwrap(),
+        ));
+
+        let sim = trading_simulation(symbol, 3, 8);
+
+        // same replay as above, no database server involved
+        let store = Store::Memory(MemoryStore::default());
+        let export = Export {
+            format: OutputFormat::Parquet,
+            dir: std::env::temp_dir().join(format!("replay_export_{}", std::process::id())),
+        };
+        let feed = FeedSource::Replay {
+            candles,
+            speed: 0.0,
+            has_volume: true,
+        };
+        run_trading_loop(
+            sim,
+            store.clone(),
+            feed,
+            clock,
+            PaperExchange::new(store.clone(), btcusdt_filters(), 60_000, None),
+            Some(export.clone()),
+            std::future::pending(),
+        )
+        .await
+        .expect("Replay failed");
+
+        let open = store.get_open_trades(symbol).await.unwrap();
+        assert_eq!(open.len(), 1, "Replay should leave one open long");
+        assert_eq!(open[0].trade_size, dec!(0.00535));
+        assert!(store.get_working_orders(symbol).await.unwrap().is_empty());
+        // every replayed candle went through the strategy window once
+        let stored = store
+            .get_last_candles(symbol, "1m", i64::MAX, 100)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 60);
+        // one equity snapshot per evaluated window, open long marked to last close
+        let curve = store.get_equity_snapshots(1).await.unwrap();
+        assert_eq!(curve.len(), 60 - 8 + 1);
+        let last = curve.last().unwrap();
+        let realized = store.get_realized_pnl(symbol).await.unwrap();
+        assert_eq!(last.balance, dec!(500) + realized);
+        assert_eq!(last.equity, last.balance + last.unrealized_pnl);
+        assert!(last.exposure > Decimal::ZERO);
+
+        // files of the last bar match stored state
+        let read = |name: &str| {
+            let file = std::fs::File::open(export.dir.join(name)).unwrap();
+            ParquetReader::new(file).finish().unwrap()
+        };
+        // report curve starts with opening balance
+        assert_eq!(
+            read("MOCKREPLAYUSDT_equity.parquet").height(),
+            curve.len() + 1
+        );
+        assert_eq!(read("MOCKREPLAYUSDT_trades.parquet").height(), 1);
+        assert_eq!(read("MOCKREPLAYUSDT_report.parquet").height(), 1);
+        std::fs::remove_dir_all(&export.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_taken_http_port_starts_no_session() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sim = trading_simulation("BTCUSDT", 3, 8);
+        sim.http_addr = Some(taken.local_addr().unwrap().to_string());
+        let store = Store::Memory(MemoryStore::default());
+        let feed = FeedSource::Replay {
+            candles: Vec::new(),
+            speed: 0.0,
+            has_volume: true,
+        };
+        let result = run_trading_loop(
+            sim,
+            store.clone(),
+            feed,
+            Arc::new(SimulatedClock::new(DateTime::UNIX_EPOCH)),
+            PaperExchange::new(store.clone(), btcusdt_filters(), 60_000, None),
+            None,
+            std::future::pending(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(store.get_session(1).await.unwrap().is_none());
+    }
 }
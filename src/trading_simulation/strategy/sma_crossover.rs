@@ -1,10 +1,12 @@
-use crate::trading_simulation::database::crud::{
-    get_open_trade_info, is_position_open, record_close_trade, record_open_trade,
-};
+use crate::trading_simulation::database::store::TradeStore;
+use crate::trading_simulation::exchange::executor::Executor;
+use crate::trading_simulation::exchange::order::{Order, OrderSide};
+use crate::utils::clock::Clock;
 use crate::utils::objects::CandleStick;
 use crate::utils::objects::TradeAction;
 
-use sqlx::PgPool;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
 
 // simple moving average
 pub fn sma(candlesticks: &[CandleStick], lookback: u32) -> f64 {
@@ -17,8 +19,20 @@ pub fn sma(candlesticks: &[CandleStick], lookback: u32) -> f64 {
         / lookback as f64
 }
 
+// moving averages of the latest bar and signal derived from them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossover {
+    pub fast_sma: f64,
+    pub slow_sma: f64,
+    pub bullish: bool, // fast SMA above slow SMA
+}
+
 // generating signals in trading strategy
-pub fn sma_crossover(candlesticks: &[CandleStick], fast_lookback: u32, slow_lookback: u32) -> bool {
+pub fn sma_crossover(
+    candlesticks: &[CandleStick],
+    fast_lookback: u32,
+    slow_lookback: u32,
+) -> Crossover {
     // println!("{:?}", candlesticks.iter().map(|c| c.close).collect::<Vec<f64>>());
     // println!{"Number of candlesticks: {}", candlesticks.len()};
 
@@ -27,21 +41,21 @@ pub fn sma_crossover(candlesticks: &[CandleStick], fast_lookback: u32, slow_look
 
     println!("Fast SMA: {:.2}, Slow SMA: {:.2}", fast_ma, slow_ma);
 
-    fast_ma > slow_ma
+    Crossover {
+        fast_sma: fast_ma,
+        slow_sma: slow_ma,
+        bullish: fast_ma > slow_ma,
+    }
 }
 
 // determine action on each new candlestick based on configured `timeframe`
 pub async fn get_trade_action(
-    pool: &sqlx::PgPool,
-    candlesticks: &[CandleStick],
-    fast_period: u32,
-    slow_period: u32,
+    store: &impl TradeStore,
+    is_bullish_signal: bool,
     symbol: &str,
 ) -> Result<TradeAction, Box<dyn std::error::Error + Send + Sync>> {
     // query table `trades` in db for current trading position status
-    let has_open_position: bool = is_position_open(pool, symbol).await?;
-
-    let is_bullish_signal: bool = sma_crossover(candlesticks, fast_period, slow_period);
+    let has_open_position: bool = store.is_position_open(symbol).await?;
 
     // Formulation for sma-crossover strategy, can be modeled with;
     // -> Mealy machine, aka finite automata, deterministic FSM
@@ -78,66 +92,83 @@ pub async fn get_trade_action(
     }
 }
 
+// strategy decides, exchange executes: orders are submitted here and
+// filled by the exchange on following candles, fills end up in `trades`;
+// returns side of the submitted market order, None when nothing was submitted
 pub async fn execute_trade_strategy(
-    pool: &PgPool,
+    store: &impl TradeStore,
     candlesticks: &[CandleStick],
-    current_balance: &mut f64,
+    current_balance: Decimal,
     symbol: &str,
-    fast_period: u32,
-    slow_period: u32,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    crossover: &Crossover,    // signal of the latest bar
+    clock: &dyn Clock,        // timestamps of submitted orders
+    exchange: &impl Executor, // order execution (paper or live) and exchange rules
+) -> Result<Option<OrderSide>, Box<dyn std::error::Error + Send + Sync>> {
     let last_candle = candlesticks.last().ok_or("No candlesticks available")?;
+    // indicators run on f64, accounting from here on is exact decimal
+    let last_price = Decimal::from_f64(last_candle.close).ok_or("Invalid candle close price")?;
+    let filters = exchange.filters();
+
+    let is_bullish_signal = crossover.bullish;
+
+    // order in flight: wait for it while signal agrees, cancel it once signal flips
+    for order in exchange.working_orders(symbol).await? {
+        if (order.side == OrderSide::Buy) == is_bullish_signal {
+            println!("[PENDING] Waiting for {}", order);
+            return Ok(None);
+        }
+        let order = exchange.cancel(order, clock).await?;
+        if order.status.is_working() {
+            return Ok(None); // retried on next batch
+        }
+    }
 
-    match get_trade_action(pool, candlesticks, fast_period, slow_period, symbol).await? {
+    match get_trade_action(store, is_bullish_signal, symbol).await? {
         TradeAction::EnterLong => {
-            let trade_size = *current_balance / last_candle.close;
-
-            // db insert log
-            record_open_trade(
-                pool,
-                symbol,
-                last_candle.close,
-                trade_size,
-                *current_balance,
-            )
-            .await?;
-
-            // assuming that there is no slippage or network latency, so
-            // execution of trade happened at new candle open (current candle close)
-            println!(
-                "[BUY] Long trade open for {} at price {}",
-                symbol, last_candle.close
-            );
+            // order is shaped as exchange would accept it: quantity floored
+            // to lot step, undersized orders are not placed at all
+            let (_, quantity) =
+                match filters.prepare_order(last_price, current_balance / last_price) {
+                    Ok(order) => order,
+                    Err(reason) => {
+                        println!("[REJECTED] Long entry for {}: {}", symbol, reason);
+                        return Ok(None);
+                    }
+                };
+
+            // market order, filled at open of the next candle
+            exchange
+                .submit(Order::market(symbol, OrderSide::Buy, quantity), clock)
+                .await?;
+            Ok(Some(OrderSide::Buy))
         }
 
         TradeAction::ExitLong => {
             // closing trade is possible only when position is open
-            if let Some(open_trade) = get_open_trade_info(pool, symbol).await? {
-                // assuming no slippage and no network latency
-                let exit_price = last_candle.close;
-
-                // asumming no fees
-                let pnl = (exit_price - open_trade.entry_price) * open_trade.trade_size;
-
-                // db insert log
-                record_close_trade(pool, open_trade.id, exit_price, pnl, last_candle.timestamp)
+            if let Some(open_trade) = store.get_open_trade_info(symbol).await? {
+                if let Err(reason) =
+                    filters.check_order(filters.round_price(last_price), open_trade.trade_size)
+                {
+                    println!("[REJECTED] Long exit for {}: {}", symbol, reason);
+                    return Ok(None);
+                }
+
+                exchange
+                    .submit(
+                        Order::market(symbol, OrderSide::Sell, open_trade.trade_size),
+                        clock,
+                    )
                     .await?;
-
-                println!(
-                    "[SOLD] Closed long trade for {} at price {}, PnL: {:.2}",
-                    symbol, exit_price, pnl
-                );
-
-                *current_balance += pnl;
+                Ok(Some(OrderSide::Sell))
             } else {
                 println!("No open trade to close");
+                Ok(None)
             }
         }
 
         TradeAction::Hold => {
             println!("[NO ACTION] Holding position for {}", symbol);
+            Ok(None)
         }
     }
-
-    Ok(())
 }
@@ -1,51 +1,206 @@
-use crate::trading_simulation::database::crud::{get_closed_trades, get_open_trades};
-use crate::utils::objects::Trade;
+use crate::trading_simulation::database::store::TradeStore;
+use crate::utils::clock::Clock;
+use crate::utils::metrics::{
+    PerformanceMetrics, RelativeMetrics, metrics_frame, performance_metrics, relative_metrics,
+};
+use crate::utils::objects::{CandleStick, EquitySnapshot, Session, Trade};
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use polars::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::Serialize;
 
 // point on the realized equity curve
 // balance updated after each closed trade
 #[derive(Debug, Clone)]
 pub struct EquityPoint {
     pub time: DateTime<Utc>,
-    pub equity: f64,
+    pub equity: Decimal,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PnlStats {
     pub total_trades: usize,
     pub winners: usize,
     pub losers: usize,
-    pub win_rate: f64,  // [0, 1]
-    pub gross_pnl: f64, // sum of realized pnl over CLOSED trades
-    pub avg_win: f64,
-    pub avg_loss: f64,      // negative number (or 0 if none)
+    pub win_rate: f64,      // [0, 1]
+    pub gross_pnl: Decimal, // sum of realized pnl over CLOSED trades
+    pub avg_win: Decimal,
+    pub avg_loss: Decimal,  // negative number (or 0 if none)
     pub profit_factor: f64, // sum(wins) / abs(sum(losses))
-    pub best_trade: f64,
-    pub worst_trade: f64,
+    pub best_trade: Decimal,
+    pub worst_trade: Decimal,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct HoldingTimeStats {
     pub avg_minutes: f64,
     pub median_minutes: f64,
+    pub open_minutes: f64, // age of the oldest open position at report time
 }
 
-#[derive(Debug, Clone)]
+// how far CLOSED trades went against and in favour of the position while open,
+// averages are over trades that have excursions recorded
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExcursionStats {
+    pub measured: usize,
+    pub avg_mae: Decimal,
+    pub avg_mfe: Decimal,
+    pub worst_mae: Decimal,
+    pub best_mfe: Decimal,
+    pub winners_avg_mae: Decimal, // heat winners took, a stop tighter than this cuts them
+    pub losers_avg_mfe: Decimal,  // open profit losers gave back, reach of a target
+    pub edge_ratio: f64,          // avg MFE / avg MAE relative to position size, > 1 is an edge
+}
+
+// OPEN trade marked to the last price
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenPosition {
+    pub trade_id: i64,
+    pub entry_time: DateTime<Utc>,
+    pub entry_price: Decimal,
+    pub quantity: Decimal,
+    pub market_value: Decimal,
+    pub unrealized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct AnalysisReport {
     pub symbol: String,
-    pub equity_curve: Vec<EquityPoint>,
+    pub generated_at: DateTime<Utc>,
+    // mark-to-market equity of the session bar by bar, last point is `generated_at`
+    pub equity_curve: Vec<EquitySnapshot>,
     pub pnl_stats: PnlStats,
-    // pub unrealized_pnl: f64,
-    pub open_positions: usize,
+    pub last_price: Option<Decimal>,
+    pub open_positions: Vec<OpenPosition>,
     pub holding_time: HoldingTimeStats,
+    pub excursions: ExcursionStats,
+    // risk-adjusted stats of the equity curve
+    pub metrics: PerformanceMetrics,
+    // buy & hold from the start of the curve, one value per point of `equity_curve`
+    pub benchmark: Vec<Option<Decimal>>,
+    // strategy against that benchmark
+    pub relative: RelativeMetrics,
+    // ledger of the symbol: CLOSED trades by exit time, then OPEN ones
+    pub trades: Vec<Trade>,
 }
 
 impl AnalysisReport {
+    // account state at report time
+    pub fn current(&self) -> &EquitySnapshot {
+        self.equity_curve
+            .last()
+            .expect("Equity curve always ends with current snapshot")
+    }
+
+    // buy & hold equity at report time
+    pub fn current_benchmark(&self) -> Option<Decimal> {
+        self.benchmark.last().copied().flatten()
+    }
+
+    // summary row, trade ledger and equity curve as `<symbol>_report`, `<symbol>_trades`
+    // and `<symbol>_equity` tables for `--output`, decimals become floats
+    pub fn to_frames(&self) -> PolarsResult<Vec<(String, DataFrame)>> {
+        let stats = &self.pnl_stats;
+        let current = self.current();
+        let summary = df!(
+            "symbol" => [self.symbol.as_str()],
+            "generated_at" => [self.generated_at.timestamp_millis()],
+            "total_trades" => [stats.total_trades as u64],
+            "win_rate" => [stats.win_rate],
+            "gross_pnl" => [float(stats.gross_pnl)],
+            "profit_factor" => [stats.profit_factor],
+            "best_trade" => [float(stats.best_trade)],
+            "worst_trade" => [float(stats.worst_trade)],
+            "avg_hold_minutes" => [self.holding_time.avg_minutes],
+            "median_hold_minutes" => [self.holding_time.median_minutes],
+            "open_position_minutes" => [self.holding_time.open_minutes],
+            "avg_mae" => [float(self.excursions.avg_mae)],
+            "avg_mfe" => [float(self.excursions.avg_mfe)],
+            "worst_mae" => [float(self.excursions.worst_mae)],
+            "best_mfe" => [float(self.excursions.best_mfe)],
+            "winners_avg_mae" => [float(self.excursions.winners_avg_mae)],
+            "losers_avg_mfe" => [float(self.excursions.losers_avg_mfe)],
+            "edge_ratio" => [self.excursions.edge_ratio],
+            "last_price" => [self.last_price.map(float)],
+            "balance" => [float(current.balance)],
+            "unrealized_pnl" => [float(current.unrealized_pnl)],
+            "equity" => [float(current.equity)],
+            "exposure" => [float(current.exposure)],
+            "open_positions" => [self.open_positions.len() as u64],
+            "benchmark_equity" => [self.current_benchmark().map(float)],
+            "alpha" => [self.relative.alpha],
+            "beta" => [self.relative.beta],
+            "tracking_error" => [self.relative.tracking_error],
+            "information_ratio" => [self.relative.information_ratio],
+            "up_capture" => [self.relative.up_capture],
+            "down_capture" => [self.relative.down_capture],
+        )?
+        .hstack(metrics_frame(&[("session", &self.metrics)])?.get_columns())?;
+
+        let trades = df!(
+            "id" => self.trades.iter().map(|t| t.id).collect::<Vec<_>>(),
+            "status" => self.trades.iter().map(|t| t.status.as_str()).collect::<Vec<_>>(),
+            "entry_time" => self.trades.iter().map(|t| t.entry_time.timestamp_millis()).collect::<Vec<_>>(),
+            "exit_time" => self.trades.iter().map(|t| t.exit_time.map(|e| e.timestamp_millis())).collect::<Vec<_>>(),
+            "entry_price" => self.trades.iter().map(|t| float(t.entry_price)).collect::<Vec<_>>(),
+            "exit_price" => self.trades.iter().map(|t| t.exit_price.map(float)).collect::<Vec<_>>(),
+            "trade_size" => self.trades.iter().map(|t| float(t.trade_size)).collect::<Vec<_>>(),
+            "position_size" => self.trades.iter().map(|t| float(t.position_size)).collect::<Vec<_>>(),
+            "pnl" => self.trades.iter().map(|t| t.pnl.map(float)).collect::<Vec<_>>(),
+            "mae" => self.trades.iter().map(|t| t.mae.map(float)).collect::<Vec<_>>(),
+            "mfe" => self.trades.iter().map(|t| t.mfe.map(float)).collect::<Vec<_>>(),
+        )?;
+
+        let curve = &self.equity_curve;
+        let equity = df!(
+            "time" => curve.iter().map(|p| p.time.timestamp_millis()).collect::<Vec<_>>(),
+            "balance" => curve.iter().map(|p| float(p.balance)).collect::<Vec<_>>(),
+            "unrealized_pnl" => curve.iter().map(|p| float(p.unrealized_pnl)).collect::<Vec<_>>(),
+            "equity" => curve.iter().map(|p| float(p.equity)).collect::<Vec<_>>(),
+            "exposure" => curve.iter().map(|p| float(p.exposure)).collect::<Vec<_>>(),
+            "price" => curve.iter().map(|p| p.price.map(float)).collect::<Vec<_>>(),
+            "benchmark" => self.benchmark.iter().map(|b| b.map(float)).collect::<Vec<_>>(),
+        )?;
+
+        Ok(vec![
+            (
+                format!("{}_report", self.symbol),
+                with_datetimes(summary, &["generated_at"])?,
+            ),
+            (
+                format!("{}_trades", self.symbol),
+                with_datetimes(trades, &["entry_time", "exit_time"])?,
+            ),
+            (
+                format!("{}_equity", self.symbol),
+                with_datetimes(equity, &["time"])?,
+            ),
+        ])
+    }
+
     pub fn format_text(&self) -> String {
         let stats = &self.pnl_stats;
+        let m = &self.metrics;
+        let current = self.current();
+        let exposure_pct = if current.equity.is_zero() {
+            Decimal::ZERO
+        } else {
+            current.exposure / current.equity * Decimal::ONE_HUNDRED
+        };
+        let positions: String = self
+            .open_positions
+            .iter()
+            .map(|p| {
+                format!(
+                    "  #{} {} @ ${:.2} -> value ${:.2}, PnL ${:.2}\n",
+                    p.trade_id, p.quantity, p.entry_price, p.market_value, p.unrealized_pnl
+                )
+            })
+            .collect();
         format!(
             r#"=== Trading Performance Report: {symbol} ===
+Generated At       : {generated_at}
 Total Trades       : {tot}
 Winning Rate       : {wr:.1}%
 Gross PnL          : ${gpnl:.2}
@@ -54,43 +209,106 @@ Best Trade PnL     : ${best:.2}
 Worst Trade PnL    : ${worst:.2}
 Average Hold Time  : {avg_ht:.1} minutes
 Median Hold Time   : {med_ht:.1} minutes
-Balance Equity     : ${last_eq:.2}
+Open Position Age  : {open_ht:.1} minutes
+Avg MAE / MFE      : ${mae:.2} / ${mfe:.2}
+Worst MAE          : ${worst_mae:.2}
+Best MFE           : ${best_mfe:.2}
+Winners Avg MAE    : ${win_mae:.2}
+Losers Avg MFE     : ${loss_mfe:.2}
+Edge Ratio         : {edge:.2}
+Last Price         : {last_price}
+Realized Balance   : ${balance:.2}
+Unrealized PnL     : ${unpnl:.2}
+Balance Equity     : ${equity:.2}
+Exposure           : ${exposure:.2} ({exposure_pct:.1}% of equity)
+Open Positions     : {open}
+{positions}Total Return       : {ret:.2}%
+Annualized Return  : {ann_ret:.2}%
+Annualized Vol     : {ann_vol:.2}%
+Sharpe Ratio       : {sharpe:.2}
+Sortino Ratio      : {sortino:.2}
+Calmar Ratio       : {calmar:.2}
+Max Drawdown       : {mdd}
+Time in Market     : {in_market:.1}%
+Expectancy         : ${expectancy:.2} per trade
+Max Loss Streak    : {streak} trades
+Buy & Hold Equity  : {benchmark}
+Alpha              : {alpha:.2}%
+Beta               : {beta:.2}
+Information Ratio  : {ir:.2}
+Tracking Error     : {te:.2}%
+Up / Down Capture  : {up:.2} / {down:.2}
 "#,
             symbol = self.symbol,
+            generated_at = self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
             tot = stats.total_trades,
             wr = stats.win_rate * 100.0,
             gpnl = stats.gross_pnl,
             pf = stats.profit_factor,
             best = stats.best_trade,
             worst = stats.worst_trade,
-            // open = self.open_positions,
-            // unpnl = self.unrealized_pnl,
             avg_ht = self.holding_time.avg_minutes,
             med_ht = self.holding_time.median_minutes,
-            last_eq = self.equity_curve.last().map(|e| e.equity).unwrap_or(0.0),
+            open_ht = self.holding_time.open_minutes,
+            mae = self.excursions.avg_mae,
+            mfe = self.excursions.avg_mfe,
+            worst_mae = self.excursions.worst_mae,
+            best_mfe = self.excursions.best_mfe,
+            win_mae = self.excursions.winners_avg_mae,
+            loss_mfe = self.excursions.losers_avg_mfe,
+            edge = self.excursions.edge_ratio,
+            last_price = self
+                .last_price
+                .map(|p| format!("${:.2}", p))
+                .unwrap_or_else(|| "n/a".to_string()),
+            balance = current.balance,
+            unpnl = current.unrealized_pnl,
+            equity = current.equity,
+            exposure = current.exposure,
+            open = self.open_positions.len(),
+            ret = m.total_return * 100.0,
+            ann_ret = m.annualized_return * 100.0,
+            ann_vol = m.annualized_volatility * 100.0,
+            sharpe = m.sharpe_ratio,
+            sortino = m.sortino_ratio,
+            calmar = m.calmar_ratio,
+            mdd = m.drawdown_text(),
+            in_market = m.exposure * 100.0,
+            expectancy = m.expectancy,
+            streak = m.max_consecutive_losses,
+            benchmark = self
+                .current_benchmark()
+                .map(|b| format!("${:.2}", b))
+                .unwrap_or_else(|| "n/a".to_string()),
+            alpha = self.relative.alpha * 100.0,
+            beta = self.relative.beta,
+            ir = self.relative.information_ratio,
+            te = self.relative.tracking_error * 100.0,
+            up = self.relative.up_capture,
+            down = self.relative.down_capture,
         )
     }
 }
 
-// pub async fn get_last_price(pool: &PgPool, symbol: &str) -> Result<Option<f64>, sqlx::Error> {
-//     // prices uses column `coin`, while your in-memory struct uses `symbol`
-//     sqlx::query_scalar!(
-//         r#"
-//         SELECT close
-//         FROM prices
-//         WHERE coin = $1
-//         ORDER BY timestamp DESC, id DESC
-//         LIMIT 1
-//         "#,
-//         symbol
-//     )
-//     .fetch_optional(pool)
-//     .await
-// }
+pub(crate) fn float(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+// unix millis columns as datetimes
+fn with_datetimes(df: DataFrame, columns: &[&str]) -> PolarsResult<DataFrame> {
+    df.lazy()
+        .with_columns(
+            columns
+                .iter()
+                .map(|c| col(*c).cast(DataType::Datetime(TimeUnit::Milliseconds, None)))
+                .collect::<Vec<_>>(),
+        )
+        .collect()
+}
 
 // calculates the cumulative account equity after each trade is closed
 // updating EquityPoint struct with each closed trade
-pub fn build_equity_curve(initial_balance: f64, closed: &[Trade]) -> Vec<EquityPoint> {
+pub fn build_equity_curve(initial_balance: Decimal, closed: &[Trade]) -> Vec<EquityPoint> {
     let mut eq = initial_balance;
     let mut curve = Vec::with_capacity(closed.len());
     for tr in closed {
@@ -102,16 +320,61 @@ pub fn build_equity_curve(initial_balance: f64, closed: &[Trade]) -> Vec<EquityP
     curve
 }
 
-// sum unrealized PnL over all OPEN trades at a given last price
-// pub fn unrealized_pnl(open_trades: &[Trade], last_price: Option<f64>) -> f64 {
-//     let Some(lp) = last_price else {
-//         return 0.0;
-//     };
-//     open_trades
-//         .iter()
-//         .map(|time| (lp - time.entry_price) * time.trade_size)
-//         .sum()
-// }
+// mark-to-market equity at the close of every candle: `balance` plus pnl of trades
+// closed by then, trades open at that close are marked to it
+pub fn candle_equity_curve(
+    balance: Decimal,
+    ledger: &[Trade],
+    candles: &[CandleStick],
+    interval_ms: i64,
+) -> Vec<EquitySnapshot> {
+    candles
+        .iter()
+        .filter_map(|candle| {
+            let time = DateTime::from_timestamp_millis(candle.timestamp + interval_ms)?;
+            let price = Decimal::from_f64(candle.close)?;
+            let mut snapshot = EquitySnapshot {
+                time,
+                balance,
+                unrealized_pnl: Decimal::ZERO,
+                equity: Decimal::ZERO,
+                exposure: Decimal::ZERO,
+                price: None,
+            };
+            for t in ledger {
+                match t.exit_time {
+                    Some(exit) if exit <= time => snapshot.balance += t.pnl.unwrap_or_default(),
+                    _ if t.entry_time <= time => {
+                        snapshot.unrealized_pnl += (price - t.entry_price) * t.trade_size;
+                        snapshot.exposure += price * t.trade_size;
+                    }
+                    _ => {}
+                }
+            }
+            snapshot.equity = snapshot.balance + snapshot.unrealized_pnl;
+            snapshot.price = Some(price);
+            Some(snapshot)
+        })
+        .collect()
+}
+
+// OPEN trades marked to `last_price`, at entry price while no price is known
+pub fn open_positions(open_trades: &[Trade], last_price: Option<Decimal>) -> Vec<OpenPosition> {
+    open_trades
+        .iter()
+        .map(|t| {
+            let mark = last_price.unwrap_or(t.entry_price);
+            OpenPosition {
+                trade_id: t.id,
+                entry_time: t.entry_time,
+                entry_price: t.entry_price,
+                quantity: t.trade_size,
+                market_value: mark * t.trade_size,
+                unrealized_pnl: (mark - t.entry_price) * t.trade_size,
+            }
+        })
+        .collect()
+}
 
 // aggregate win/loss stats over CLOSED trades
 pub fn pnl_stats(closed: &[Trade]) -> PnlStats {
@@ -125,8 +388,8 @@ pub fn pnl_stats(closed: &[Trade]) -> PnlStats {
     let mut losses = Vec::new();
 
     for time in closed {
-        let stats = time.pnl.unwrap_or(0.0);
-        if stats >= 0.0 {
+        let stats = time.pnl.unwrap_or_default();
+        if stats >= Decimal::ZERO {
             wins.push(stats);
         } else {
             losses.push(stats);
@@ -136,48 +399,67 @@ pub fn pnl_stats(closed: &[Trade]) -> PnlStats {
     s.winners = wins.len();
     s.losers = losses.len();
     s.win_rate = s.winners as f64 / s.total_trades as f64;
-    s.gross_pnl = wins.iter().copied().sum::<f64>() + losses.iter().copied().sum::<f64>();
+    let sum_wins = wins.iter().copied().sum::<Decimal>();
+    let sum_losses = losses.iter().copied().sum::<Decimal>();
+    s.gross_pnl = sum_wins + sum_losses;
     s.avg_win = if !wins.is_empty() {
-        wins.iter().copied().sum::<f64>() / wins.len() as f64
+        sum_wins / Decimal::from(wins.len())
     } else {
-        0.0
+        Decimal::ZERO
     };
     s.avg_loss = if !losses.is_empty() {
-        losses.iter().copied().sum::<f64>() / losses.len() as f64
+        sum_losses / Decimal::from(losses.len())
     } else {
-        0.0
+        Decimal::ZERO
     };
-    let sum_wins = wins.iter().copied().sum::<f64>();
-    let sum_losses_abs = losses.iter().map(|x| x.abs()).sum::<f64>();
-    s.profit_factor = if sum_losses_abs > 0.0 {
-        sum_wins / sum_losses_abs
+    s.profit_factor = if !sum_losses.is_zero() {
+        (sum_wins / sum_losses.abs())
+            .to_f64()
+            .unwrap_or(f64::INFINITY)
     } else {
         f64::INFINITY
     };
     s.best_trade = closed
         .iter()
         .filter_map(|time| time.pnl)
-        .fold(f64::NEG_INFINITY, f64::max)
-        .max(0.0);
+        .max()
+        .unwrap_or_default()
+        .max(Decimal::ZERO);
     s.worst_trade = closed
         .iter()
         .filter_map(|time| time.pnl)
-        .fold(f64::INFINITY, f64::min)
-        .min(0.0);
+        .min()
+        .unwrap_or_default()
+        .min(Decimal::ZERO);
     s
 }
 
-// average/median holding time over CLOSED trades in minutes
-pub fn holding_time_stats(closed: &[Trade]) -> HoldingTimeStats {
+// average/median holding time over CLOSED trades in minutes,
+// OPEN trades are aged against `now` of the same clock that stamped them
+pub fn holding_time_stats(
+    closed: &[Trade],
+    open: &[Trade],
+    now: DateTime<Utc>,
+) -> HoldingTimeStats {
+    let open_minutes = open
+        .iter()
+        .map(|time| (now - time.entry_time).num_seconds() as f64 / 60.0)
+        .fold(0.0, f64::max);
     if closed.is_empty() {
-        return HoldingTimeStats::default();
+        return HoldingTimeStats {
+            open_minutes,
+            ..Default::default()
+        };
     }
     let mut minutes: Vec<f64> = closed
         .iter()
         .filter_map(|time| Some(((time.exit_time?) - time.entry_time).num_seconds() as f64 / 60.0))
         .collect();
     if minutes.is_empty() {
-        return HoldingTimeStats::default();
+        return HoldingTimeStats {
+            open_minutes,
+            ..Default::default()
+        };
     }
     minutes.sort_by(|a, b| a.partial_cmp(b).unwrap());
     let avg = minutes.iter().copied().sum::<f64>() / minutes.len() as f64;
@@ -190,29 +472,606 @@ pub fn holding_time_stats(closed: &[Trade]) -> HoldingTimeStats {
     HoldingTimeStats {
         avg_minutes: avg,
         median_minutes: med,
+        open_minutes,
+    }
+}
+
+// (mae, mfe) of a CLOSED trade as quote amount for its size, from highs and lows of
+// candles it was held through. the candle closing at entry is the entry bar itself
+// and is skipped; fill prices bound the range. None when no candle covers the trade
+pub fn trade_excursion(
+    trade: &Trade,
+    candles: &[CandleStick],
+    interval_ms: i64,
+) -> Option<(Decimal, Decimal)> {
+    let exit_price = trade.exit_price?;
+    let entry = trade.entry_time.timestamp_millis();
+    let exit = trade.exit_time?.timestamp_millis();
+    let held: Vec<&CandleStick> = candles
+        .iter()
+        .filter(|c| c.timestamp + interval_ms > entry && c.timestamp < exit)
+        .collect();
+    if held.is_empty() {
+        return None;
+    }
+
+    let mut low = trade.entry_price.min(exit_price);
+    let mut high = trade.entry_price.max(exit_price);
+    for c in held {
+        if let Some(l) = Decimal::from_f64(c.low) {
+            low = low.min(l);
+        }
+        if let Some(h) = Decimal::from_f64(c.high) {
+            high = high.max(h);
+        }
+    }
+    Some((
+        (trade.entry_price - low) * trade.trade_size,
+        (high - trade.entry_price) * trade.trade_size,
+    ))
+}
+
+// measures CLOSED trades of `symbol` that have no excursions yet against stored
+// `timeframe` candles, returns how many were recorded; trades no stored candle
+// covers keep NULL excursions. Only trades closed after `since` are measured, so
+// the trading loop tries each trade once
+pub async fn record_excursions(
+    store: &impl TradeStore,
+    symbol: &str,
+    timeframe: &str,
+    interval_ms: i64,
+    since: Option<DateTime<Utc>>,
+) -> Result<usize, sqlx::Error> {
+    let mut recorded = 0;
+    for trade in store.get_closed_trades(symbol).await? {
+        let Some(exit) = trade
+            .exit_time
+            .filter(|exit| trade.mae.is_none() && since.is_none_or(|since| *exit > since))
+        else {
+            continue;
+        };
+        let exit = exit.timestamp_millis();
+        let bars = (exit - trade.entry_time.timestamp_millis()) / interval_ms + 2;
+        let candles = store
+            .get_last_candles(symbol, timeframe, exit - 1, bars)
+            .await?;
+        if let Some((mae, mfe)) = trade_excursion(&trade, &candles, interval_ms) {
+            store.set_trade_excursion(trade.id, mae, mfe).await?;
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+// MAE/MFE summaries over CLOSED trades, edge ratio compares excursions as
+// fraction of position size so trades of different size weigh the same
+pub fn excursion_stats(closed: &[Trade]) -> ExcursionStats {
+    let measured: Vec<(&Trade, Decimal, Decimal)> = closed
+        .iter()
+        .filter_map(|t| Some((t, t.mae?, t.mfe?)))
+        .collect();
+    if measured.is_empty() {
+        return ExcursionStats::default();
+    }
+
+    let mean = |values: Vec<Decimal>| {
+        if values.is_empty() {
+            Decimal::ZERO
+        } else {
+            values.iter().sum::<Decimal>() / Decimal::from(values.len())
+        }
+    };
+    let is_winner = |t: &Trade| t.pnl.unwrap_or_default() >= Decimal::ZERO;
+    let relative: Vec<(f64, f64)> = measured
+        .iter()
+        .filter(|(t, _, _)| !t.position_size.is_zero())
+        .map(|(t, mae, mfe)| (float(mae / t.position_size), float(mfe / t.position_size)))
+        .collect();
+    let rel_mae = relative.iter().map(|r| r.0).sum::<f64>();
+    let rel_mfe = relative.iter().map(|r| r.1).sum::<f64>();
+
+    ExcursionStats {
+        measured: measured.len(),
+        avg_mae: mean(measured.iter().map(|m| m.1).collect()),
+        avg_mfe: mean(measured.iter().map(|m| m.2).collect()),
+        worst_mae: measured.iter().map(|m| m.1).max().unwrap_or_default(),
+        best_mfe: measured.iter().map(|m| m.2).max().unwrap_or_default(),
+        winners_avg_mae: mean(
+            measured
+                .iter()
+                .filter(|m| is_winner(m.0))
+                .map(|m| m.1)
+                .collect(),
+        ),
+        losers_avg_mfe: mean(
+            measured
+                .iter()
+                .filter(|m| !is_winner(m.0))
+                .map(|m| m.2)
+                .collect(),
+        ),
+        edge_ratio: if rel_mae > 0.0 {
+            rel_mfe / rel_mae
+        } else if rel_mfe > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        },
     }
 }
 
+// account balance of a session. Balance carries over between sessions, so a session
+// opens with its initial balance plus pnl of trades closed before it started
+#[derive(Debug, Clone)]
+pub struct SessionBalance {
+    pub opening: Decimal,
+    pub closed: Vec<Trade>, // closed since the session started
+}
+
+impl SessionBalance {
+    pub fn new(session: &Session, closed: &[Trade]) -> Self {
+        let mut opening = session.initial_balance;
+        let mut since = Vec::new();
+        for trade in closed {
+            match trade.exit_time {
+                Some(exit) if exit < session.started_at => opening += trade.pnl.unwrap_or_default(),
+                Some(_) => since.push(trade.clone()),
+                None => {}
+            }
+        }
+        Self {
+            opening,
+            closed: since,
+        }
+    }
+
+    // opening balance plus realized pnl of the session
+    pub fn current(&self) -> Decimal {
+        build_equity_curve(self.opening, &self.closed)
+            .last()
+            .map(|p| p.equity)
+            .unwrap_or(self.opening)
+    }
+}
+
+// report of `session_id` with open positions marked to `last_price` (close of the
+// latest candle); only trades closed since the session started count, its equity curve
+// is opening balance, stored snapshots of the session and current one, annualized with
+// `bars_per_year` of trading timeframe
 pub async fn generate_report(
-    pool: &PgPool,
+    store: &impl TradeStore,
+    session_id: i64,
     symbol: &str,
-    initial_balance: f64,
+    last_price: Option<Decimal>,
+    bars_per_year: f64,
+    clock: &dyn Clock,
 ) -> Result<AnalysisReport, sqlx::Error> {
-    let closed = get_closed_trades(pool, symbol).await?;
-    let open = get_open_trades(pool, symbol).await?;
-    // let last_price = get_last_price(pool, symbol).await?;
+    let session = store
+        .get_session(session_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    let account = SessionBalance::new(&session, &store.get_closed_trades(symbol).await?);
+    let open = store.get_open_trades(symbol).await?;
 
-    let curve = build_equity_curve(initial_balance, &closed);
-    //  let unrl = unrealized_pnl(&open, last_price);
+    let balance = account.current();
+    let closed = account.closed;
+    let positions = open_positions(&open, last_price);
+    let unrealized_pnl: Decimal = positions.iter().map(|p| p.unrealized_pnl).sum();
     let pnl = pnl_stats(&closed);
-    let ht = holding_time_stats(&closed);
+    let generated_at = clock.now();
+    let ht = holding_time_stats(&closed, &open, generated_at);
+    let excursions = excursion_stats(&closed);
+
+    let mut equity_curve = vec![EquitySnapshot {
+        time: session.started_at,
+        balance: account.opening,
+        unrealized_pnl: Decimal::ZERO,
+        equity: account.opening,
+        exposure: Decimal::ZERO,
+        price: None,
+    }];
+    equity_curve.extend(store.get_equity_snapshots(session_id).await?);
+    equity_curve.push(EquitySnapshot {
+        time: generated_at,
+        balance,
+        unrealized_pnl,
+        equity: balance + unrealized_pnl,
+        exposure: positions.iter().map(|p| p.market_value).sum(),
+        price: last_price,
+    });
+    let metrics = equity_metrics(&equity_curve, &closed, bars_per_year);
+    let benchmark = benchmark_curve(&equity_curve);
+    let relative = benchmark_metrics(&equity_curve, &benchmark, bars_per_year);
 
     Ok(AnalysisReport {
         symbol: symbol.to_string(),
-        equity_curve: curve,
+        generated_at,
+        equity_curve,
         pnl_stats: pnl,
-        // unrealized_pnl: unrl,
-        open_positions: open.len(),
+        last_price,
+        open_positions: positions,
         holding_time: ht,
+        excursions,
+        metrics,
+        benchmark,
+        relative,
+        trades: closed.into_iter().chain(open).collect(),
     })
 }
+
+// metrics of a mark-to-market curve, bars with exposure count as time in market
+pub fn equity_metrics(
+    curve: &[EquitySnapshot],
+    closed: &[Trade],
+    bars_per_year: f64,
+) -> PerformanceMetrics {
+    let equity: Vec<f64> = curve.iter().map(|p| float(p.equity)).collect();
+    let in_market: Vec<bool> = curve.iter().map(|p| !p.exposure.is_zero()).collect();
+    let pnls: Vec<f64> = closed.iter().filter_map(|t| t.pnl).map(float).collect();
+    performance_metrics(&equity, &in_market, &pnls, bars_per_year)
+}
+
+// equity of buying the asset with all equity at the first snapshot that has a price
+// and holding it, None for snapshots without price
+pub fn benchmark_curve(curve: &[EquitySnapshot]) -> Vec<Option<Decimal>> {
+    let start = curve
+        .iter()
+        .find_map(|p| Some((p.equity, p.price.filter(|price| !price.is_zero())?)));
+    curve
+        .iter()
+        .map(|p| {
+            let (equity, start_price) = start?;
+            Some(equity * p.price? / start_price)
+        })
+        .collect()
+}
+
+// strategy against buy & hold over snapshots that have a benchmark value
+pub fn benchmark_metrics(
+    curve: &[EquitySnapshot],
+    benchmark: &[Option<Decimal>],
+    bars_per_year: f64,
+) -> RelativeMetrics {
+    let (equity, market): (Vec<f64>, Vec<f64>) = curve
+        .iter()
+        .zip(benchmark)
+        .filter_map(|(p, b)| Some((float(p.equity), float((*b)?))))
+        .unzip();
+    relative_metrics(&equity, &market, bars_per_year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading_simulation::database::memory::MemoryStore;
+    use crate::utils::clock::SimulatedClock;
+    use chrono::Duration;
+    use rust_decimal::dec;
+
+    fn trade(entry_time: DateTime<Utc>, exit_time: Option<DateTime<Utc>>) -> Trade {
+        Trade {
+            id: 1,
+            symbol: "BTCUSDT".into(),
+            entry_price: dec!(100),
+            exit_price: exit_time.map(|_| dec!(110)),
+            trade_size: dec!(1),
+            position_size: dec!(100),
+            pnl: exit_time.map(|_| dec!(10)),
+            entry_time,
+            exit_time,
+            status: if exit_time.is_some() {
+                "CLOSED"
+            } else {
+                "OPEN"
+            }
+            .into(),
+            mae: None,
+            mfe: None,
+        }
+    }
+
+    #[test]
+    fn test_holding_time_stats() {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let minutes = |m: i64| t0 + Duration::minutes(m);
+        let closed = [
+            trade(t0, Some(minutes(10))),
+            trade(minutes(20), Some(minutes(50))),
+        ];
+        let open = [trade(minutes(60), None)];
+
+        let stats = holding_time_stats(&closed, &open, minutes(75));
+        assert_eq!(stats.avg_minutes, 20.0);
+        assert_eq!(stats.median_minutes, 20.0);
+        assert_eq!(stats.open_minutes, 15.0);
+    }
+
+    #[tokio::test]
+    async fn test_trade_excursions() {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let minutes = |m: i64| t0 + Duration::minutes(m);
+        let store = MemoryStore::default();
+        // bought at close of 00:00 candle, sold at close of 00:03 candle
+        store
+            .record_open_trade("BTCUSDT", dec!(100), dec!(2), dec!(200), minutes(1))
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(104), dec!(8), minutes(4))
+            .await
+            .unwrap();
+        let candles: Vec<CandleStick> = [
+            (0, 80.0, 120.0),
+            (1, 97.0, 103.0),
+            (2, 95.5, 101.0),
+            (3, 99.0, 106.0),
+            (4, 50.0, 150.0),
+        ]
+        .iter()
+        .map(|&(m, low, high)| CandleStick {
+            symbol: "BTCUSDT".into(),
+            open: low,
+            high,
+            low,
+            close: high,
+            volume: 1.0,
+            timestamp: minutes(m).timestamp_millis(),
+        })
+        .collect();
+        store.upsert_candles("1m", &candles).await.unwrap();
+
+        // closed by a bar that was already processed
+        assert_eq!(
+            record_excursions(&store, "BTCUSDT", "1m", 60_000, Some(minutes(4)))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            record_excursions(&store, "BTCUSDT", "1m", 60_000, None)
+                .await
+                .unwrap(),
+            1
+        );
+        // measured trades are not measured again
+        assert_eq!(
+            record_excursions(&store, "BTCUSDT", "1m", 60_000, None)
+                .await
+                .unwrap(),
+            0
+        );
+        // entry bar and the bar after exit are outside of the trade
+        let closed = store.get_closed_trades("BTCUSDT").await.unwrap();
+        assert_eq!(trade_excursion(&closed[0], &candles[4..], 60_000), None);
+        assert_eq!(closed[0].mae, Some(dec!(9)));
+        assert_eq!(closed[0].mfe, Some(dec!(12)));
+
+        let loser = Trade {
+            pnl: Some(dec!(-1)),
+            position_size: dec!(100),
+            mae: Some(dec!(3)),
+            mfe: Some(dec!(1)),
+            ..trade(t0, Some(minutes(10)))
+        };
+        let stats = excursion_stats(&[closed[0].clone(), loser, trade(t0, Some(minutes(5)))]);
+        assert_eq!(stats.measured, 2);
+        assert_eq!(stats.avg_mae, dec!(6));
+        assert_eq!(stats.avg_mfe, dec!(6.5));
+        assert_eq!(stats.worst_mae, dec!(9));
+        assert_eq!(stats.winners_avg_mae, dec!(9));
+        assert_eq!(stats.losers_avg_mfe, dec!(1));
+        // (0.06 + 0.01) / (0.045 + 0.03)
+        assert!((stats.edge_ratio - 0.07 / 0.075).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_equity_reconciles_with_fills() {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        // prices and sizes on which f64 accumulates rounding noise
+        let fills = [
+            (dec!(93561.23), dec!(93561.33), dec!(0.1)),
+            (dec!(0.3), dec!(0.1), dec!(3)),
+            (dec!(100.7), dec!(100.9), dec!(0.00535)),
+        ];
+        let closed: Vec<Trade> = fills
+            .iter()
+            .map(|&(entry, exit, size)| Trade {
+                entry_price: entry,
+                exit_price: Some(exit),
+                trade_size: size,
+                pnl: Some((exit - entry) * size),
+                ..trade(t0, Some(t0 + Duration::minutes(1)))
+            })
+            .collect();
+
+        let curve = build_equity_curve(dec!(500), &closed);
+        let stats = pnl_stats(&closed);
+        assert_eq!(stats.gross_pnl, dec!(0.01) - dec!(0.6) + dec!(0.00107));
+        assert_eq!(curve.last().unwrap().equity, dec!(500) + stats.gross_pnl);
+        assert_eq!(stats.worst_trade, dec!(-0.6));
+    }
+
+    #[tokio::test]
+    async fn test_balance_carries_over_sessions() {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let clock = SimulatedClock::new(t0 + Duration::minutes(5));
+        let store = MemoryStore::default();
+        let first = store
+            .start_session("BTCUSDT", "paper", dec!(500), t0)
+            .await
+            .unwrap();
+        store
+            .record_open_trade("BTCUSDT", dec!(100), dec!(1), dec!(100), t0)
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(110), dec!(10), t0 + Duration::minutes(2))
+            .await
+            .unwrap();
+        let report = generate_report(&store, first, "BTCUSDT", None, 525_600.0, &clock)
+            .await
+            .unwrap();
+        assert_eq!(report.equity_curve[0].equity, dec!(500));
+        assert_eq!(report.current().balance, dec!(510));
+        store
+            .end_session(first, t0 + Duration::minutes(5))
+            .await
+            .unwrap();
+
+        // second session opens with the balance the first one ended with
+        let second = store
+            .start_session("BTCUSDT", "paper", dec!(500), t0 + Duration::minutes(10))
+            .await
+            .unwrap();
+        store
+            .record_open_trade(
+                "BTCUSDT",
+                dec!(100),
+                dec!(1),
+                dec!(100),
+                t0 + Duration::minutes(11),
+            )
+            .await
+            .unwrap();
+        store
+            .record_close_trade(2, dec!(96), dec!(-4), t0 + Duration::minutes(12))
+            .await
+            .unwrap();
+        clock.advance_to(t0 + Duration::minutes(15));
+        let report = generate_report(&store, second, "BTCUSDT", None, 525_600.0, &clock)
+            .await
+            .unwrap();
+        assert_eq!(report.equity_curve[0].equity, dec!(510));
+        assert_eq!(report.pnl_stats.total_trades, 1);
+        assert_eq!(report.current().balance, dec!(506));
+
+        // orders of the session are sized from the same balance
+        let session = store.get_session(second).await.unwrap().unwrap();
+        let account =
+            SessionBalance::new(&session, &store.get_closed_trades("BTCUSDT").await.unwrap());
+        assert_eq!(account.opening, dec!(510));
+        assert_eq!(account.closed.len(), 1);
+        assert_eq!(account.current(), report.current().balance);
+    }
+
+    #[tokio::test]
+    async fn test_report_marks_open_position() {
+        let t0 = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let clock = SimulatedClock::new(t0);
+        let store = MemoryStore::default();
+        // closed by an earlier run: not a trade of this session, its pnl is part of
+        // the opening balance
+        store
+            .record_open_trade(
+                "BTCUSDT",
+                dec!(90000),
+                dec!(0.001),
+                dec!(90),
+                t0 - Duration::hours(2),
+            )
+            .await
+            .unwrap();
+        store
+            .record_close_trade(1, dec!(91000), dec!(1), t0 - Duration::hours(1))
+            .await
+            .unwrap();
+        let session = store
+            .start_session("BTCUSDT", "paper", dec!(500), t0)
+            .await
+            .unwrap();
+
+        // nothing closed yet: equity is the opening balance, not 0
+        store
+            .record_open_trade("BTCUSDT", dec!(93561.23), dec!(0.00535), dec!(500.55), t0)
+            .await
+            .unwrap();
+        let report = generate_report(&store, session, "BTCUSDT", None, 525_600.0, &clock)
+            .await
+            .unwrap();
+        assert_eq!(report.current().equity, dec!(501));
+        assert_eq!(report.pnl_stats.total_trades, 0);
+        assert_eq!(report.current().exposure, dec!(93561.23) * dec!(0.00535));
+        store
+            .insert_equity_snapshot(session, "BTCUSDT", report.current())
+            .await
+            .unwrap();
+
+        clock.advance_to(t0 + Duration::minutes(1));
+        let report = generate_report(
+            &store,
+            session,
+            "BTCUSDT",
+            Some(dec!(93661.23)),
+            525_600.0,
+            &clock,
+        )
+        .await
+        .unwrap();
+        let current = report.current();
+        assert_eq!(current.balance, dec!(501));
+        assert_eq!(current.unrealized_pnl, dec!(0.535));
+        assert_eq!(current.equity, dec!(501.535));
+        assert_eq!(current.exposure, dec!(93661.23) * dec!(0.00535));
+        assert_eq!(report.open_positions.len(), 1);
+        assert_eq!(report.open_positions[0].unrealized_pnl, dec!(0.535));
+        // opening balance, stored snapshot of previous bar, current one
+        assert_eq!(
+            report
+                .equity_curve
+                .iter()
+                .map(|p| p.equity)
+                .collect::<Vec<_>>(),
+            vec![dec!(501), dec!(501), dec!(501.535)]
+        );
+        assert_eq!(report.equity_curve[0].time, t0);
+        // flat only at the opening snapshot
+        assert!((report.metrics.exposure - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(report.metrics.max_drawdown, 0.0);
+        let text = report.format_text();
+        assert!(text.contains("Last Price         : $93661.23"));
+        assert!(text.contains("Open Positions     : 1"));
+
+        let frames = report.to_frames().unwrap();
+        let names: Vec<&str> = frames.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["BTCUSDT_report", "BTCUSDT_trades", "BTCUSDT_equity"]
+        );
+        assert_eq!(frames[0].1.height(), 1);
+        assert_eq!(
+            frames[0].1.column("equity").unwrap().f64().unwrap().get(0),
+            Some(501.535)
+        );
+        assert_eq!(frames[1].1.height(), 1);
+        assert_eq!(frames[1].1.column("exit_time").unwrap().null_count(), 1);
+        assert_eq!(frames[2].1.height(), 3);
+
+        // buy & hold starts at the first snapshot with a price
+        assert_eq!(report.benchmark, vec![None, None, Some(dec!(501.535))]);
+        store
+            .insert_equity_snapshot(session, "BTCUSDT", report.current())
+            .await
+            .unwrap();
+        clock.advance_to(t0 + Duration::minutes(2));
+        let report = generate_report(
+            &store,
+            session,
+            "BTCUSDT",
+            Some(dec!(94661.23)),
+            525_600.0,
+            &clock,
+        )
+        .await
+        .unwrap();
+        let benchmark = dec!(501.535) * dec!(94661.23) / dec!(93661.23);
+        assert_eq!(report.current_benchmark(), Some(benchmark));
+        assert_eq!(report.current().price, Some(dec!(94661.23)));
+        assert!(
+            report
+                .format_text()
+                .contains(&format!("Buy & Hold Equity  : ${:.2}", benchmark))
+        );
+        let frames = report.to_frames().unwrap();
+        assert_eq!(frames[2].1.column("benchmark").unwrap().null_count(), 2);
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+
+// source of "now" for everything that gets stamped (trades, reports), so the same
+// code path runs on wall clock in live trading and on candle time in replays/tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    // market time observed by the trading loop (close of the latest candle);
+    // clocks driven by market data move to it, wall clock ignores it
+    fn advance_to(&self, _time: DateTime<Utc>) {}
+}
+
+// wall clock for live trading
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// clock that only moves when told to, used for replays and deterministic tests
+#[derive(Debug)]
+pub struct SimulatedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        SimulatedClock {
+            now: Mutex::new(start),
+        }
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+
+    // never goes back in time, out of order candles don't rewind stamped trades
+    fn advance_to(&self, time: DateTime<Utc>) {
+        let mut now = self.now.lock().unwrap();
+        if time > *now {
+            *now = time;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_simulated_clock() {
+        let start = DateTime::from_timestamp_millis(1_735_689_600_000).unwrap();
+        let clock = SimulatedClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance_to(start + Duration::minutes(10));
+        assert_eq!(clock.now(), start + Duration::minutes(10));
+
+        clock.advance_to(start);
+        assert_eq!(clock.now(), start + Duration::minutes(10));
+    }
+
+    #[test]
+    fn test_system_clock_ignores_market_time() {
+        let clock = SystemClock;
+        clock.advance_to(DateTime::UNIX_EPOCH);
+        assert!(clock.now() > DateTime::UNIX_EPOCH);
+    }
+}
@@ -1,14 +1,93 @@
+use crate::utils::data_io::{ColumnMapping, DATABASE_SOURCE};
+use crate::utils::data_quality::QualityPolicy;
 use binance_spot_connector_rust::market::klines::KlineInterval;
+use chrono::Duration;
 use config::{Config, File};
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+// length of one candlestick for Binance kline intervals `1m` ... `1w`
+pub fn timeframe_duration(timeframe: &str) -> Result<Duration, String> {
+    match timeframe {
+        "1m" => Ok(Duration::minutes(1)),
+        "3m" => Ok(Duration::minutes(3)),
+        "5m" => Ok(Duration::minutes(5)),
+        "15m" => Ok(Duration::minutes(15)),
+        "30m" => Ok(Duration::minutes(30)),
+        "1h" => Ok(Duration::hours(1)),
+        "2h" => Ok(Duration::hours(2)),
+        "4h" => Ok(Duration::hours(4)),
+        "6h" => Ok(Duration::hours(6)),
+        "8h" => Ok(Duration::hours(8)),
+        "12h" => Ok(Duration::hours(12)),
+        "1d" => Ok(Duration::days(1)),
+        "3d" => Ok(Duration::days(3)),
+        "1w" => Ok(Duration::weeks(1)),
+        other => Err(format!("Invalid timeframe: {}", other)),
+    }
+}
+
+// where orders of the trading simulation end up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    // simulated fills on market candles, nothing leaves this machine
+    #[default]
+    Paper,
+    // real orders on Binance spot, signed with keys from `.env`
+    Live,
+}
+
+impl ExecutionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionMode::Paper => "paper",
+            ExecutionMode::Live => "live",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TradingSimulation {
     pub symbol: String,
     pub timeframe: String,
-    pub initial_balance: f64,
+    pub initial_balance: Decimal,
     pub fast_period: u32,
     pub slow_period: u32,
+    // handling of fetched candles that fail validation (gaps, duplicates, bad OHLC)
+    #[serde(default)]
+    pub data_quality: QualityPolicy,
+    // REST endpoint for market data, can point to a local mock
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    // websocket endpoint for account events of live mode, can point to a local mock
+    #[serde(default = "default_ws_url")]
+    pub ws_url: String,
+    // cached exchangeInfo response with symbol filters (tick size, lot size, min notional),
+    // fetched from `api_url` when missing
+    #[serde(default = "default_exchange_info_path")]
+    pub exchange_info_path: String,
+    // max share of candle volume a paper order can fill per candle, unlimited if not set
+    #[serde(default)]
+    pub participation_rate: Option<Decimal>,
+    // real trading has to be switched on explicitly with `mode = "live"`
+    #[serde(default)]
+    pub mode: ExecutionMode,
+    // address of the embedded HTTP server (`/metrics`), e.g. `0.0.0.0:9100`, off if not set
+    #[serde(default)]
+    pub http_addr: Option<String>,
+}
+
+fn default_api_url() -> String {
+    "https://api.binance.com".into()
+}
+
+fn default_ws_url() -> String {
+    "wss://stream.binance.com:9443".into()
+}
+
+fn default_exchange_info_path() -> String {
+    "data/exchange_info.json".into()
 }
 
 impl TradingSimulation {
@@ -33,7 +112,7 @@ impl TradingSimulation {
     }
 
     pub fn validate(&self) -> Result<(), String> {
-        if self.initial_balance < 0.0 {
+        if self.initial_balance < Decimal::ZERO {
             return Err("Initial balance cannot be negative".into());
         }
         if self.fast_period < 1 || self.slow_period < 1 {
@@ -42,26 +121,53 @@ impl TradingSimulation {
         if self.fast_period > 10_000 || self.slow_period > 10_000 {
             return Err("SMA periods cannot exceed 10,000".into());
         }
+        if let Some(rate) = self.participation_rate
+            && (rate <= Decimal::ZERO || rate > Decimal::ONE)
+        {
+            return Err("Participation rate must be in (0, 1]".into());
+        }
         Ok(())
     }
 
     pub fn print_trading_simulation_params(&self) {
         println!("--- Trading Simulation Config ---");
+        println!("Mode            : {:?}", self.mode);
         println!("Symbol          : {}", self.symbol);
         println!("Timeframe       : {}", self.timeframe);
         println!("Initial Balance : {}", self.initial_balance);
         println!("Fast SMA Period : {}", self.fast_period);
         println!("Slow SMA Period : {}", self.slow_period);
+        println!("Data Quality    : {:?}", self.data_quality);
+        println!("API URL         : {}", self.api_url);
+        println!("WS URL          : {}", self.ws_url);
+        println!("Exchange Info   : {}", self.exchange_info_path);
+        if let Some(rate) = self.participation_rate {
+            println!("Participation   : {}", rate);
+        }
+        if let Some(addr) = &self.http_addr {
+            println!("HTTP Address    : {}", addr);
+        }
         println!("--------------------------------");
     }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Backtest {
-    pub parquet_path: String,
+    // parquet, csv or Binance kline zip archive (or directory with such files),
+    // `database` reads candles stored in DATABASE_URL
+    #[serde(alias = "parquet_path")]
+    pub data_path: String,
+    #[serde(default)]
+    pub columns: ColumnMapping,
+    // candles of which symbol are read from database
+    pub symbol: Option<String>,
+    // bar size to resample historical data into, keeps source granularity if not set
+    pub timeframe: Option<String>,
     pub test_balance: f64,
     pub fast_period: usize,
     pub slow_period: usize,
+    #[serde(default)]
+    pub data_quality: QualityPolicy,
 }
 
 impl Backtest {
@@ -75,15 +181,29 @@ impl Backtest {
         if self.fast_period > 10_000 || self.slow_period > 10_000 {
             return Err("Period cannot exceed 10,000".into());
         }
+        if let Some(timeframe) = &self.timeframe {
+            timeframe_duration(timeframe)?;
+        }
+        if self.data_path == DATABASE_SOURCE && self.symbol.is_none() {
+            return Err("Symbol must be set to backtest on database candles".into());
+        }
         Ok(())
     }
     
     pub fn print_backtest_params(&self) {
         println!("--- Backtest Config ---");
-        println!("Parquet Path    : {}", self.parquet_path);
+        println!("Data Path       : {}", self.data_path);
+        if let Some(symbol) = &self.symbol {
+            println!("Symbol          : {}", symbol);
+        }
+        println!(
+            "Timeframe       : {}",
+            self.timeframe.as_deref().unwrap_or("source data")
+        );
         println!("Test Balance    : {}", self.test_balance);
         println!("Fast SMA Period : {}", self.fast_period);
         println!("Slow SMA Period : {}", self.slow_period);
+        println!("Data Quality    : {:?}", self.data_quality);
         println!("-----------------------");
     }
 }
@@ -1,11 +1,624 @@
+use crate::trading_simulation::database::crud::get_candle_history;
+use crate::trading_simulation::database::store::{Store, TradeStore};
+use crate::utils::config::timeframe_duration;
+use crate::utils::objects::CandleStick;
+use ::zip::ZipArchive;
 use polars::prelude::*;
-use std::path::Path;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use tokio::runtime::RuntimeFlavor;
 
-pub fn read_parquet(path: &str) -> PolarsResult<DataFrame> {
-    let abs_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .join(path)
-        .canonicalize()?;
+// backtest `data_path` reading candles stored by the bot instead of files
+pub const DATABASE_SOURCE: &str = "database";
+
+// Binance public data archives (data.binance.vision) ship klines as headerless csv
+const BINANCE_KLINE_COLUMNS: [&str; 12] = [
+    "open_time",
+    "open",
+    "high",
+    "low",
+    "close",
+    "volume",
+    "close_time",
+    "quote_volume",
+    "count",
+    "taker_buy_volume",
+    "taker_buy_quote_volume",
+    "ignore",
+];
+
+// epoch magnitude tells timestamp unit apart: seconds ~1e9, ms ~1e12, µs ~1e15
+// (Binance switched spot archives from ms to µs starting with 2025 data)
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+const MICROS_THRESHOLD: i64 = 100_000_000_000_000;
+
+// maps column names of a source file onto names used by backtest:
+// `date`, `open`, `high`, `low`, `close`, `volume`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub date: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            date: "date".into(),
+            open: "open".into(),
+            high: "high".into(),
+            low: "low".into(),
+            close: "close".into(),
+            volume: "volume".into(),
+        }
+    }
+}
+
+impl ColumnMapping {
+    fn binance_kline() -> Self {
+        ColumnMapping {
+            date: "open_time".into(),
+            ..ColumnMapping::default()
+        }
+    }
+}
+
+// relative paths are resolved against working directory, so installed binary
+// reads the same files as `cargo run` started from the same place
+pub fn resolve_path(path: &str) -> std::io::Result<PathBuf> {
+    let path = Path::new(path);
+    let abs_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    abs_path.canonicalize()
+}
 
-    let file = std::fs::File::open(abs_path)?;
+pub fn read_parquet(path: &str) -> PolarsResult<DataFrame> {
+    let file = File::open(resolve_path(path)?)?;
     ParquetReader::new(file).finish()
 }
+
+pub fn read_csv(path: &str, columns: &ColumnMapping) -> PolarsResult<DataFrame> {
+    let df = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(resolve_path(path)?))?
+        .finish()?;
+
+    normalize_columns(df, columns)
+}
+
+// reads every kline csv packed inside monthly (or daily) Binance archive
+pub fn read_binance_zip(path: &str) -> PolarsResult<DataFrame> {
+    let mut archive = ZipArchive::new(File::open(resolve_path(path)?)?)
+        .map_err(|e| polars_err!(ComputeError: "failed to open zip archive {}: {}", path, e))?;
+
+    let mut frames = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| polars_err!(ComputeError: "failed to read zip entry: {}", e))?;
+        if !entry.name().ends_with(".csv") {
+            continue;
+        }
+
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes)?;
+
+        // older archives are headerless, some newer ones start with column names
+        let has_header = bytes.first().is_some_and(|b| !b.is_ascii_digit());
+
+        let mut df = CsvReadOptions::default()
+            .with_has_header(has_header)
+            .into_reader_with_file_handle(Cursor::new(bytes))
+            .finish()?;
+        df.set_column_names(BINANCE_KLINE_COLUMNS.iter().take(df.width()).copied())?;
+
+        frames.push(normalize_columns(df, &ColumnMapping::binance_kline())?.lazy());
+    }
+
+    if frames.is_empty() {
+        polars_bail!(ComputeError: "no csv files found in zip archive {}", path);
+    }
+
+    concat(frames, UnionArgs::default())?.collect()
+}
+
+// single entry point for backtest and replay data, picks loader by file extension;
+// directory loads all supported files inside it (e.g. folder of monthly archives),
+// `database` reads `timeframe` candles of `symbol` stored by the trading bot
+pub fn read_market_data(
+    path: &str,
+    columns: &ColumnMapping,
+    symbol: Option<&str>,
+    timeframe: &str,
+) -> PolarsResult<DataFrame> {
+    if path == DATABASE_SOURCE {
+        dotenv::dotenv().ok();
+        let database_url = std::env::var("DATABASE_URL")
+            .map_err(|e| polars_err!(ComputeError: "DATABASE_URL: {}", e))?;
+        let symbol = symbol.ok_or_else(
+            || polars_err!(ComputeError: "symbol is required to read candles from database"),
+        )?;
+        return read_database(&database_url, symbol, timeframe);
+    }
+
+    let abs_path = resolve_path(path)?;
+
+    if abs_path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&abs_path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| is_supported_file(p))
+            .collect();
+        files.sort();
+
+        if files.is_empty() {
+            polars_bail!(ComputeError: "no parquet, csv or zip files found in {}", path);
+        }
+
+        let frames = files
+            .iter()
+            .map(|p| read_market_file(&p.to_string_lossy(), columns).map(|df| df.lazy()))
+            .collect::<PolarsResult<Vec<_>>>()?;
+
+        return concat(frames, UnionArgs::default())?.collect();
+    }
+
+    read_market_file(&abs_path.to_string_lossy(), columns)
+}
+
+fn read_market_file(path: &str, columns: &ColumnMapping) -> PolarsResult<DataFrame> {
+    match extension(Path::new(path)).as_deref() {
+        Some("parquet") => normalize_columns(read_parquet(path)?, columns),
+        Some("csv") => read_csv(path, columns),
+        Some("zip") => read_binance_zip(path),
+        _ => polars_bail!(ComputeError: "unsupported market data file: {}", path),
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+fn is_supported_file(path: &Path) -> bool {
+    matches!(extension(path).as_deref(), Some("parquet" | "csv" | "zip"))
+}
+
+// candles stored in database at `url` (any DATABASE_URL scheme) as a frame with the same
+// columns as file loaders; on TimescaleDB 1h/4h/1d come from continuous aggregates.
+// The database is only read, its schema is created by `bot db migrate`
+pub fn read_database(url: &str, symbol: &str, timeframe: &str) -> PolarsResult<DataFrame> {
+    let candles = block_on(async {
+        let store = Store::open(url).await?;
+        if !store.has_table("prices").await? {
+            return Err(sqlx::Error::Configuration(
+                "no candle table, run `bot db migrate` first".into(),
+            ));
+        }
+        match store {
+            Store::Postgres(pool) => get_candle_history(&pool, symbol, timeframe).await,
+            store => {
+                store
+                    .get_last_candles(symbol, timeframe, i64::MAX, i64::MAX)
+                    .await
+            }
+        }
+    })?
+    .map_err(|e| polars_err!(ComputeError: "failed to read candles from database: {}", e))?;
+
+    if candles.is_empty() {
+        polars_bail!(ComputeError: "no {} candles of {} in database", timeframe, symbol);
+    }
+    candles_to_frame(&candles)
+}
+
+// backtest is synchronous, so database queries are driven to completion on runtime
+// of the CLI, or on a temporary one when called outside of it; a current thread
+// runtime can't be blocked in place, the temporary one runs on its own thread then
+fn block_on<F>(future: F) -> std::io::Result<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    let run = |future: F| {
+        Ok(tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(future))
+    };
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| run(future))
+                .join()
+                .expect("Database read panicked")
+        }),
+        Err(_) => run(future),
+    }
+}
+
+// candlesticks as normalized frame, inverse of `frame_to_candles`
+pub fn candles_to_frame(candles: &[CandleStick]) -> PolarsResult<DataFrame> {
+    let values = |f: fn(&CandleStick) -> f64| candles.iter().map(f).collect::<Vec<f64>>();
+
+    df!(
+        "date" => candles.iter().map(|c| c.timestamp).collect::<Vec<i64>>(),
+        "open" => values(|c| c.open),
+        "high" => values(|c| c.high),
+        "low" => values(|c| c.low),
+        "close" => values(|c| c.close),
+        "volume" => values(|c| c.volume),
+    )?
+    .lazy()
+    .with_column(col("date").cast(DataType::Datetime(TimeUnit::Milliseconds, None)))
+    .collect()
+}
+
+// rows of a normalized frame as candlesticks, missing values become NaN
+// (and are caught by data-quality checks), missing volume becomes 0
+pub fn frame_to_candles(df: &DataFrame, symbol: &str) -> PolarsResult<Vec<CandleStick>> {
+    let timestamps: Vec<i64> = df
+        .column("date")?
+        .cast(&DataType::Int64)?
+        .i64()?
+        .into_no_null_iter()
+        .collect();
+
+    let values = |name: &str| -> PolarsResult<Vec<f64>> {
+        Ok(df
+            .column(name)?
+            .f64()?
+            .into_iter()
+            .map(|v| v.unwrap_or(f64::NAN))
+            .collect())
+    };
+    let (open, high, low, close) = (
+        values("open")?,
+        values("high")?,
+        values("low")?,
+        values("close")?,
+    );
+    let volume = if df.column("volume").is_ok() {
+        values("volume")?
+    } else {
+        vec![0.0; timestamps.len()]
+    };
+
+    Ok((0..timestamps.len())
+        .map(|i| CandleStick {
+            symbol: symbol.to_string(),
+            open: open[i],
+            high: high[i],
+            low: low[i],
+            close: close[i],
+            volume: volume[i],
+            timestamp: timestamps[i],
+        })
+        .collect())
+}
+
+// aggregates klines into `timeframe` bars (e.g. 1m -> 15m), windows are aligned
+// the same way as on Binance (epoch for minutes to days, Monday for weeks)
+pub fn resample_ohlcv(df: DataFrame, timeframe: &str) -> PolarsResult<DataFrame> {
+    let bar_ms = timeframe_duration(timeframe)
+        .map_err(|e| polars_err!(ComputeError: "{}", e))?
+        .num_milliseconds();
+
+    let df = df.sort(["date"], SortMultipleOptions::default())?;
+    let dates: Vec<i64> = df
+        .column("date")?
+        .cast(&DataType::Int64)?
+        .i64()?
+        .into_no_null_iter()
+        .collect();
+
+    // smallest step between consecutive klines is granularity of the source
+    let Some(source_ms) = dates
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d > 0)
+        .min()
+    else {
+        return Ok(df);
+    };
+    if source_ms == bar_ms {
+        return Ok(df);
+    }
+    if source_ms > bar_ms || bar_ms % source_ms != 0 {
+        polars_bail!(
+            ComputeError: "cannot resample {}ms klines into {} bars", source_ms, timeframe
+        );
+    }
+
+    let every = Duration::try_parse(timeframe)?;
+    let mut aggs = vec![
+        col("open").first(),
+        col("high").max(),
+        col("low").min(),
+        col("close").last(),
+    ];
+    if df.column("volume").is_ok() {
+        aggs.push(col("volume").sum());
+    }
+
+    let resampled = df
+        .lazy()
+        .group_by_dynamic(
+            col("date"),
+            [],
+            DynamicGroupOptions {
+                every,
+                period: every,
+                offset: Duration::parse("0ns"),
+                ..Default::default()
+            },
+        )
+        .agg(aggs)
+        .collect()?;
+
+    // last bar is still "open" if source data ends before its window closes
+    let last_source = dates.last().copied().unwrap_or_default();
+    let last_bar = resampled
+        .column("date")?
+        .cast(&DataType::Int64)?
+        .i64()?
+        .last()
+        .unwrap_or_default();
+    if last_bar + bar_ms > last_source + source_ms {
+        return Ok(resampled.slice(0, resampled.height().saturating_sub(1)));
+    }
+
+    Ok(resampled)
+}
+
+// integer epoch in seconds, milliseconds or microseconds -> milliseconds
+fn epoch_to_millis(ts: Expr) -> Expr {
+    when(ts.clone().gt_eq(lit(MICROS_THRESHOLD)))
+        .then(ts.clone() / lit(1_000i64))
+        .when(ts.clone().lt(lit(MILLIS_THRESHOLD)))
+        .then(ts.clone() * lit(1_000i64))
+        .otherwise(ts)
+}
+
+// select mapped columns, rename them to canonical names and unify dtypes;
+// `volume` is optional since not every historical dataset provides it
+fn normalize_columns(df: DataFrame, columns: &ColumnMapping) -> PolarsResult<DataFrame> {
+    let date = match df.column(&columns.date)?.dtype() {
+        dt if dt.is_integer() => epoch_to_millis(col(&columns.date).cast(DataType::Int64))
+            .cast(DataType::Datetime(TimeUnit::Milliseconds, None)),
+        DataType::String => col(&columns.date).str().to_datetime(
+            Some(TimeUnit::Milliseconds),
+            None,
+            StrptimeOptions::default(),
+            lit("raise"),
+        ),
+        _ => col(&columns.date).cast(DataType::Datetime(TimeUnit::Milliseconds, None)),
+    };
+
+    let mut selection = vec![date.alias("date")];
+    let mut prices = vec![
+        (&columns.open, "open"),
+        (&columns.high, "high"),
+        (&columns.low, "low"),
+        (&columns.close, "close"),
+    ];
+    if df.column(&columns.volume).is_ok() {
+        prices.push((&columns.volume, "volume"));
+    }
+    for (source, target) in prices {
+        selection.push(col(source).cast(DataType::Float64).alias(target));
+    }
+
+    df.lazy().select(selection).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates_as_millis(df: &DataFrame) -> Vec<i64> {
+        df.column("date")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_read_csv_with_column_mapping() {
+        let columns = ColumnMapping {
+            date: "timestamp".into(),
+            volume: "base_volume".into(),
+            ..ColumnMapping::default()
+        };
+
+        let df = read_market_data(
+            "tests/fixtures/klines_custom_columns.csv",
+            &columns,
+            None,
+            "1m",
+        )
+        .expect("Failed to read csv fixture");
+
+        assert_eq!(
+            df.get_column_names(),
+            ["date", "open", "high", "low", "close", "volume"]
+        );
+        assert_eq!(df.height(), 5);
+        assert_eq!(dates_as_millis(&df)[0], 1_609_459_200_000);
+    }
+
+    #[test]
+    fn test_read_binance_zip_microseconds() {
+        let df = read_market_data(
+            "tests/fixtures/BTCUSDT-1m-2025-01.zip",
+            &ColumnMapping::default(),
+            None,
+            "1m",
+        )
+        .expect("Failed to read zip fixture");
+
+        assert_eq!(df.height(), 5);
+        // archive stores open time in µs, loader must end up with ms
+        assert_eq!(
+            dates_as_millis(&df),
+            [
+                1_735_689_600_000,
+                1_735_689_660_000,
+                1_735_689_720_000,
+                1_735_689_780_000,
+                1_735_689_840_000
+            ]
+        );
+        assert_eq!(
+            df.column("close").unwrap().f64().unwrap().get(0),
+            Some(93_576.0)
+        );
+    }
+
+    #[test]
+    fn test_resample_ohlcv() {
+        let columns = ColumnMapping {
+            date: "timestamp".into(),
+            volume: "base_volume".into(),
+            ..ColumnMapping::default()
+        };
+        let df = read_market_data(
+            "tests/fixtures/klines_custom_columns.csv",
+            &columns,
+            None,
+            "1m",
+        )
+        .unwrap();
+
+        let bars = resample_ohlcv(df.clone(), "5m").expect("Failed to resample");
+        assert_eq!(bars.height(), 1);
+        assert_eq!(dates_as_millis(&bars), [1_609_459_200_000]);
+        let value = |name: &str| bars.column(name).unwrap().f64().unwrap().get(0).unwrap();
+        assert_eq!(value("open"), 28923.63);
+        assert_eq!(value("high"), 29017.50);
+        assert_eq!(value("low"), 28846.95);
+        assert_eq!(value("close"), 28939.43);
+        assert!((value("volume") - 638.43).abs() < 1e-9);
+
+        // second 3m bar would only cover 2 of 3 minutes, so it is dropped
+        let bars = resample_ohlcv(df.clone(), "3m").unwrap();
+        assert_eq!(bars.height(), 1);
+        assert_eq!(
+            bars.column("close").unwrap().f64().unwrap().get(0),
+            Some(28898.29)
+        );
+
+        // same granularity is passed through, finer one is rejected
+        assert_eq!(resample_ohlcv(df, "1m").unwrap().height(), 5);
+        let hourly = df!(
+            "date" => [0i64, 3_600_000, 7_200_000],
+            "open" => [1.0, 2.0, 3.0],
+            "high" => [1.0, 2.0, 3.0],
+            "low" => [1.0, 2.0, 3.0],
+            "close" => [1.0, 2.0, 3.0],
+        )
+        .unwrap();
+        assert!(resample_ohlcv(hourly, "1m").is_err());
+    }
+
+    #[test]
+    fn test_epoch_to_millis_units() {
+        let df = df!("ts" => [1_609_459_200i64, 1_609_459_200_000, 1_609_459_200_000_000])
+            .unwrap()
+            .lazy()
+            .select([epoch_to_millis(col("ts"))])
+            .collect()
+            .unwrap();
+
+        let millis: Vec<i64> = df
+            .column("ts")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(millis, [1_609_459_200_000; 3]);
+    }
+
+    #[test]
+    fn test_candles_to_frame_roundtrip() {
+        let df = read_market_data(
+            "tests/fixtures/BTCUSDT-1m-2025-01.zip",
+            &ColumnMapping::default(),
+            None,
+            "1m",
+        )
+        .unwrap();
+        let candles = frame_to_candles(&df, "BTCUSDT").unwrap();
+
+        let frame = candles_to_frame(&candles).unwrap();
+        assert_eq!(frame.get_column_names(), df.get_column_names());
+        assert!(frame.equals(&df));
+    }
+
+    // current thread runtime of the test can't be blocked in place
+    #[tokio::test]
+    async fn test_read_database() {
+        let path = std::env::temp_dir().join(format!("read_database_{}.db", std::process::id()));
+        let database_url = format!("sqlite://{}", path.display());
+        let symbol = "DBLOADUSDT";
+        let store = Store::connect(&database_url).await.unwrap();
+
+        let candles: Vec<CandleStick> = (0..3)
+            .map(|i| CandleStick {
+                symbol: symbol.to_string(),
+                open: 100.0 + i as f64,
+                high: 101.0 + i as f64,
+                low: 99.0 + i as f64,
+                close: 100.5 + i as f64,
+                volume: 1.0,
+                timestamp: 1_735_689_600_000 + i * 60_000,
+            })
+            .collect();
+        store.upsert_candles("1m", &candles).await.unwrap();
+
+        let df = read_database(&database_url, symbol, "1m").expect("Failed to read database");
+        assert_eq!(
+            df.get_column_names(),
+            ["date", "open", "high", "low", "close", "volume"]
+        );
+        assert_eq!(
+            dates_as_millis(&df),
+            [1_735_689_600_000, 1_735_689_660_000, 1_735_689_720_000]
+        );
+        assert_eq!(frame_to_candles(&df, symbol).unwrap()[2].close, 102.5);
+
+        // nothing stored for other timeframes
+        assert!(read_database(&database_url, symbol, "5m").is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // reading never migrates a database
+        std::fs::File::create(&path).unwrap();
+        let err = read_database(&database_url, symbol, "1m").unwrap_err();
+        assert!(err.to_string().contains("bot db migrate"));
+        let store = Store::open(&database_url).await.unwrap();
+        assert!(!store.has_table("prices").await.unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(read_market_data("Cargo.toml", &ColumnMapping::default(), None, "1m").is_err());
+    }
+}
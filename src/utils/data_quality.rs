@@ -0,0 +1,425 @@
+use crate::utils::data_io::frame_to_candles;
+use crate::utils::objects::CandleStick;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+// what to do with a candle series that did not pass validation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityPolicy {
+    // refuse whole series, nothing reaches the strategy
+    #[default]
+    Reject,
+    // keep time grid complete: gaps and corrupt bars become flat bars at previous close
+    ForwardFill,
+    // remove offending bars, gaps stay as they are
+    Drop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QualityIssue {
+    MissingBars { after: i64, count: i64 },
+    DuplicateTimestamp { timestamp: i64 },
+    NonMonotonic { timestamp: i64, previous: i64 },
+    ZeroVolume { timestamp: i64 },
+    InvalidOhlc { timestamp: i64 },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityReport {
+    pub total_bars: usize,
+    pub interval_ms: i64,
+    pub issues: Vec<QualityIssue>,
+}
+
+impl QualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn missing_bars(&self) -> i64 {
+        self.issues
+            .iter()
+            .map(|issue| match issue {
+                QualityIssue::MissingBars { count, .. } => *count,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    pub fn duplicates(&self) -> usize {
+        self.count(|i| matches!(i, QualityIssue::DuplicateTimestamp { .. }))
+    }
+
+    pub fn non_monotonic(&self) -> usize {
+        self.count(|i| matches!(i, QualityIssue::NonMonotonic { .. }))
+    }
+
+    pub fn zero_volume(&self) -> usize {
+        self.count(|i| matches!(i, QualityIssue::ZeroVolume { .. }))
+    }
+
+    pub fn invalid_ohlc(&self) -> usize {
+        self.count(|i| matches!(i, QualityIssue::InvalidOhlc { .. }))
+    }
+
+    fn count(&self, predicate: impl Fn(&QualityIssue) -> bool) -> usize {
+        self.issues.iter().filter(|i| predicate(i)).count()
+    }
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bars checked: {} missing, {} duplicate, {} out of order, {} zero volume, {} invalid OHLC",
+            self.total_bars,
+            self.missing_bars(),
+            self.duplicates(),
+            self.non_monotonic(),
+            self.zero_volume(),
+            self.invalid_ohlc(),
+        )
+    }
+}
+
+fn is_invalid_ohlc(c: &CandleStick) -> bool {
+    let prices = [c.open, c.high, c.low, c.close];
+    prices.iter().any(|p| !p.is_finite() || *p <= 0.0)
+        || !c.volume.is_finite()
+        || c.volume < 0.0
+        || c.high < c.low
+        || c.open > c.high
+        || c.open < c.low
+        || c.close > c.high
+        || c.close < c.low
+}
+
+// checks series in the given order, timestamps are candle open times in ms
+pub fn inspect_candles(
+    candles: &[CandleStick],
+    interval_ms: i64,
+    check_volume: bool,
+) -> QualityReport {
+    let mut report = QualityReport {
+        total_bars: candles.len(),
+        interval_ms,
+        issues: Vec::new(),
+    };
+    let mut latest: Option<i64> = None;
+
+    for c in candles {
+        if let Some(previous) = latest {
+            if c.timestamp == previous {
+                report.issues.push(QualityIssue::DuplicateTimestamp {
+                    timestamp: c.timestamp,
+                });
+                continue;
+            }
+            if c.timestamp < previous {
+                report.issues.push(QualityIssue::NonMonotonic {
+                    timestamp: c.timestamp,
+                    previous,
+                });
+                continue;
+            }
+            if interval_ms > 0 && c.timestamp - previous > interval_ms {
+                report.issues.push(QualityIssue::MissingBars {
+                    after: previous,
+                    count: (c.timestamp - previous) / interval_ms - 1,
+                });
+            }
+        }
+        latest = Some(c.timestamp);
+
+        if is_invalid_ohlc(c) {
+            report.issues.push(QualityIssue::InvalidOhlc {
+                timestamp: c.timestamp,
+            });
+        } else if check_volume && c.volume == 0.0 {
+            report.issues.push(QualityIssue::ZeroVolume {
+                timestamp: c.timestamp,
+            });
+        }
+    }
+
+    report
+}
+
+// flat candle carrying previous close forward, used for gaps and corrupt bars
+fn filled_candle(previous: &CandleStick, timestamp: i64) -> CandleStick {
+    CandleStick {
+        symbol: previous.symbol.clone(),
+        open: previous.close,
+        high: previous.close,
+        low: previous.close,
+        close: previous.close,
+        volume: 0.0,
+        timestamp,
+    }
+}
+
+// validates series and repairs it according to `policy`,
+// with `Reject` any issue returns report as an error
+pub fn apply_quality_policy(
+    candles: Vec<CandleStick>,
+    interval_ms: i64,
+    policy: QualityPolicy,
+    check_volume: bool,
+) -> Result<(Vec<CandleStick>, QualityReport), QualityReport> {
+    let report = inspect_candles(&candles, interval_ms, check_volume);
+    if report.is_clean() {
+        return Ok((candles, report));
+    }
+
+    if policy == QualityPolicy::Reject {
+        return Err(report);
+    }
+
+    // out of order bars are put back in place (stable, so first duplicate wins)
+    let mut candles = candles;
+    candles.sort_by_key(|c| c.timestamp);
+    let is_corrupt = |c: &CandleStick| is_invalid_ohlc(c) || (check_volume && c.volume == 0.0);
+
+    let mut cleaned: Vec<CandleStick> = Vec::with_capacity(candles.len());
+    for c in candles {
+        let Some(previous) = cleaned.last() else {
+            // nothing to fill from before the first valid candle
+            if !is_corrupt(&c) {
+                cleaned.push(c);
+            }
+            continue;
+        };
+        if c.timestamp == previous.timestamp {
+            continue;
+        }
+
+        if policy == QualityPolicy::Drop {
+            if !is_corrupt(&c) {
+                cleaned.push(c);
+            }
+            continue;
+        }
+
+        let mut fills = Vec::new();
+        if interval_ms > 0 {
+            let mut t = previous.timestamp + interval_ms;
+            while t < c.timestamp {
+                fills.push(filled_candle(previous, t));
+                t += interval_ms;
+            }
+        }
+        let candle = if is_corrupt(&c) {
+            filled_candle(previous, c.timestamp)
+        } else {
+            c
+        };
+        cleaned.extend(fills);
+        cleaned.push(candle);
+    }
+
+    Ok((cleaned, report))
+}
+
+// most frequent step between consecutive candles, robust against gaps
+fn infer_interval_ms(timestamps: &[i64]) -> i64 {
+    let mut steps: HashMap<i64, usize> = HashMap::new();
+    for w in timestamps.windows(2) {
+        if w[1] > w[0] {
+            *steps.entry(w[1] - w[0]).or_default() += 1;
+        }
+    }
+    steps
+        .into_iter()
+        .max_by_key(|(step, count)| (*count, -step))
+        .map(|(step, _)| step)
+        .unwrap_or_default()
+}
+
+// same validation for historical frames with `date` and OHLC(V) columns
+pub fn validate_frame(
+    df: DataFrame,
+    policy: QualityPolicy,
+) -> PolarsResult<(DataFrame, QualityReport)> {
+    let has_volume = df.column("volume").is_ok();
+    let candles = frame_to_candles(&df, "")?;
+    let timestamps: Vec<i64> = candles.iter().map(|c| c.timestamp).collect();
+    let interval_ms = infer_interval_ms(&timestamps);
+
+    let (candles, report) = apply_quality_policy(candles, interval_ms, policy, has_volume)
+        .map_err(|report| polars_err!(ComputeError: "rejected market data, {}", report))?;
+
+    let mut columns = vec![
+        Series::new(
+            "date".into(),
+            candles.iter().map(|c| c.timestamp).collect::<Vec<_>>(),
+        )
+        .cast(df.column("date")?.dtype())?
+        .into(),
+        Column::new(
+            "open".into(),
+            candles.iter().map(|c| c.open).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "high".into(),
+            candles.iter().map(|c| c.high).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "low".into(),
+            candles.iter().map(|c| c.low).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "close".into(),
+            candles.iter().map(|c| c.close).collect::<Vec<_>>(),
+        ),
+    ];
+    if has_volume {
+        columns.push(Column::new(
+            "volume".into(),
+            candles.iter().map(|c| c.volume).collect::<Vec<_>>(),
+        ));
+    }
+
+    Ok((DataFrame::new(columns)?, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn candle(timestamp: i64, close: f64) -> CandleStick {
+        CandleStick {
+            symbol: "BTCUSDT".into(),
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 10.0,
+            timestamp,
+        }
+    }
+
+    fn dirty_series() -> Vec<CandleStick> {
+        let mut corrupt = candle(4 * MINUTE, 104.0);
+        corrupt.high = 90.0; // high < low
+        let mut idle = candle(5 * MINUTE, 105.0);
+        idle.volume = 0.0;
+
+        vec![
+            candle(0, 100.0),
+            candle(MINUTE, 101.0),
+            candle(MINUTE, 101.5),     // duplicate
+            candle(0, 99.0),           // back in time, duplicate once sorted
+            candle(3 * MINUTE, 103.0), // one bar missing before it
+            corrupt,
+            idle,
+        ]
+    }
+
+    #[test]
+    fn test_inspect_candles_detects_all_issues() {
+        let report = inspect_candles(&dirty_series(), MINUTE, true);
+
+        assert_eq!(report.total_bars, 7);
+        assert_eq!(report.missing_bars(), 1);
+        assert_eq!(report.duplicates(), 1);
+        assert_eq!(report.non_monotonic(), 1);
+        assert_eq!(report.invalid_ohlc(), 1);
+        assert_eq!(report.zero_volume(), 1);
+        assert!(report.issues.contains(&QualityIssue::MissingBars {
+            after: MINUTE,
+            count: 1
+        }));
+        println!("{}", report);
+    }
+
+    #[test]
+    fn test_clean_series_passes_all_policies() {
+        let candles: Vec<_> = (0..5)
+            .map(|i| candle(i * MINUTE, 100.0 + i as f64))
+            .collect();
+
+        for policy in [
+            QualityPolicy::Reject,
+            QualityPolicy::Drop,
+            QualityPolicy::ForwardFill,
+        ] {
+            let (out, report) = apply_quality_policy(candles.clone(), MINUTE, policy, true)
+                .expect("Clean series must pass");
+            assert!(report.is_clean());
+            assert_eq!(out.len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_reject_policy() {
+        let result = apply_quality_policy(dirty_series(), MINUTE, QualityPolicy::Reject, true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_drop_policy() {
+        let (out, _) =
+            apply_quality_policy(dirty_series(), MINUTE, QualityPolicy::Drop, true).unwrap();
+
+        let timestamps: Vec<i64> = out.iter().map(|c| c.timestamp).collect();
+        assert_eq!(timestamps, [0, MINUTE, 3 * MINUTE]);
+        // first of duplicates is kept
+        assert_eq!(out[0].close, 100.0);
+        assert_eq!(out[1].close, 101.0);
+    }
+
+    #[test]
+    fn test_forward_fill_policy() {
+        let (out, _) =
+            apply_quality_policy(dirty_series(), MINUTE, QualityPolicy::ForwardFill, true).unwrap();
+
+        let timestamps: Vec<i64> = out.iter().map(|c| c.timestamp).collect();
+        assert_eq!(timestamps, (0..6).map(|i| i * MINUTE).collect::<Vec<_>>());
+        // gap and corrupt bars are flat at previous close
+        assert_eq!(out[2].close, 101.0);
+        assert_eq!(out[2].high, out[2].low);
+        assert_eq!(out[4].close, 103.0);
+        assert_eq!(out[5].close, 103.0);
+        assert_eq!(
+            inspect_candles(&out, MINUTE, false).issues,
+            Vec::<QualityIssue>::new()
+        );
+    }
+
+    #[test]
+    fn test_validate_frame() {
+        let df = df!(
+            "date" => [0i64, MINUTE, MINUTE, 3 * MINUTE],
+            "open" => [1.0, 2.0, 2.0, 4.0],
+            "high" => [1.5, 2.5, 2.5, 4.5],
+            "low" => [0.5, 1.5, 1.5, 3.5],
+            "close" => [1.0, 2.0, 2.0, 4.0],
+        )
+        .unwrap()
+        .lazy()
+        .with_column(col("date").cast(DataType::Datetime(TimeUnit::Milliseconds, None)))
+        .collect()
+        .unwrap();
+
+        let (filled, report) = validate_frame(df.clone(), QualityPolicy::ForwardFill).unwrap();
+        assert_eq!(report.interval_ms, MINUTE);
+        assert_eq!(report.duplicates(), 1);
+        assert_eq!(report.missing_bars(), 1);
+        assert_eq!(filled.height(), 4);
+        assert_eq!(
+            filled.column("date").unwrap().dtype(),
+            df.column("date").unwrap().dtype()
+        );
+        assert_eq!(
+            filled.column("close").unwrap().f64().unwrap().get(2),
+            Some(2.0)
+        );
+
+        assert!(validate_frame(df, QualityPolicy::Reject).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use polars::prelude::*;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    Parquet,
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Parquet => "parquet",
+        }
+    }
+}
+
+// `--output` of `trade` and `backtest`: every table is written into its own file
+// `<dir>/<name>.<format>`, existing files are replaced
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub format: OutputFormat,
+    pub dir: PathBuf,
+}
+
+impl Export {
+    pub fn write(&self, name: &str, df: &mut DataFrame) -> PolarsResult<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self
+            .dir
+            .join(format!("{}.{}", name, self.format.extension()));
+        write_frame(&path, df, self.format)?;
+        Ok(path)
+    }
+
+    // tables of one result set, written together
+    pub fn write_all(&self, tables: Vec<(String, DataFrame)>) -> PolarsResult<Vec<PathBuf>> {
+        tables
+            .into_iter()
+            .map(|(name, mut df)| self.write(&name, &mut df))
+            .collect()
+    }
+}
+
+// json is an array of row objects, dates are written as `YYYY-MM-DD HH:MM:SS` strings
+fn write_frame(path: &Path, df: &mut DataFrame, format: OutputFormat) -> PolarsResult<()> {
+    let file = File::create(path)?;
+    match format {
+        OutputFormat::Json => JsonWriter::new(file)
+            .with_json_format(JsonFormat::Json)
+            .finish(df),
+        OutputFormat::Csv => CsvWriter::new(file).include_header(true).finish(df),
+        OutputFormat::Parquet => ParquetWriter::new(file).finish(df).map(|_| ()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("export_test_{}", std::process::id()));
+        let export = |format| Export {
+            format,
+            dir: dir.clone(),
+        };
+        let frame = || {
+            df!(
+                "date" => [1_735_689_600_000i64, 1_735_689_660_000],
+                "equity" => [500.0, 500.535],
+            )
+            .unwrap()
+            .lazy()
+            .with_column(col("date").cast(DataType::Datetime(TimeUnit::Milliseconds, None)))
+            .collect()
+            .unwrap()
+        };
+
+        let parquet = export(OutputFormat::Parquet)
+            .write("equity", &mut frame())
+            .unwrap();
+        let read = ParquetReader::new(File::open(&parquet).unwrap())
+            .finish()
+            .unwrap();
+        assert!(read.equals(&frame()));
+
+        let csv = export(OutputFormat::Csv)
+            .write("equity", &mut frame())
+            .unwrap();
+        let text = fs::read_to_string(&csv).unwrap();
+        assert!(text.starts_with("date,equity\n"));
+        assert_eq!(text.lines().count(), 3);
+
+        let json = export(OutputFormat::Json)
+            .write("equity", &mut frame())
+            .unwrap();
+        let rows: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(rows[1]["equity"], 500.535);
+        assert!(rows[0]["date"].as_str().unwrap().starts_with("2025-01-01"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
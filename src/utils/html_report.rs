@@ -0,0 +1,470 @@
+use crate::utils::metrics::PerformanceMetrics;
+use chrono::DateTime;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+// longer series are thinned to about this many points so the page stays small
+const MAX_POINTS: usize = 2000;
+const WIDTH: f64 = 1000.0;
+const HEIGHT: f64 = 300.0;
+// plot area inside the svg: left for y labels, bottom for dates
+const LEFT: f64 = 80.0;
+const RIGHT: f64 = 20.0;
+const TOP: f64 = 30.0;
+const BOTTOM: f64 = 30.0;
+
+// trade marked on the price chart and listed in the trade table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartTrade {
+    pub entry_time: i64, // unix millis
+    pub entry_price: f64,
+    pub exit_time: Option<i64>, // None while open
+    pub exit_price: Option<f64>,
+    pub pnl: f64, // realized, open trades marked to last close
+}
+
+// everything drawn by `bot report --html`; series hold one value per bar of `times`
+#[derive(Debug, Clone, Default)]
+pub struct HtmlReport {
+    pub title: String,
+    pub summary: Vec<(String, String)>,
+    pub times: Vec<i64>, // unix millis
+    pub close: Vec<f64>,
+    pub fast_sma: Vec<Option<f64>>,
+    pub slow_sma: Vec<Option<f64>>,
+    pub equity: Vec<f64>,
+    pub benchmark: Vec<f64>, // buy & hold from the same starting balance
+    pub trades: Vec<ChartTrade>,
+    // one column of the metrics table per labelled set, e.g. strategy and buy & hold
+    pub metrics: Vec<(String, PerformanceMetrics)>,
+}
+
+// row label and formatter of the metrics table
+type MetricRow = (&'static str, fn(&PerformanceMetrics) -> String);
+
+struct Line<'a> {
+    label: &'a str,
+    color: &'a str,
+    values: Vec<Option<f64>>,
+}
+
+struct Marker {
+    time: i64,
+    price: f64,
+    buy: bool,
+}
+
+impl HtmlReport {
+    // single html file without scripts or external resources, charts are inline svg
+    pub fn render(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 24px auto; max-width: 1040px; color: #222; }}
+h1 {{ font-size: 22px; }}
+h2 {{ font-size: 17px; margin-top: 32px; }}
+table {{ border-collapse: collapse; font-size: 13px; }}
+th, td {{ border-bottom: 1px solid #ddd; padding: 4px 10px; text-align: right; }}
+th:first-child, td:first-child {{ text-align: left; }}
+svg {{ background: #fcfcfc; border: 1px solid #ddd; }}
+svg text {{ font-size: 11px; fill: #555; }}
+.pos {{ color: #1a7f37; }}
+.neg {{ color: #c62828; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+"#,
+            title = escape(&self.title)
+        );
+
+        html.push_str("<table>\n");
+        for (key, value) in &self.summary {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(key),
+                escape(value)
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Metrics</h2>\n");
+        html.push_str(&self.metrics_table());
+
+        let markers: Vec<Marker> = self
+            .trades
+            .iter()
+            .flat_map(|t| {
+                let entry = Marker {
+                    time: t.entry_time,
+                    price: t.entry_price,
+                    buy: true,
+                };
+                let exit = t.exit_time.zip(t.exit_price).map(|(time, price)| Marker {
+                    time,
+                    price,
+                    buy: false,
+                });
+                std::iter::once(entry).chain(exit)
+            })
+            .collect();
+        html.push_str("<h2>Price</h2>\n");
+        html.push_str(&self.chart(
+            &[
+                Line {
+                    label: "close",
+                    color: "#888",
+                    values: self.close.iter().copied().map(Some).collect(),
+                },
+                Line {
+                    label: "fast SMA",
+                    color: "#f08c00",
+                    values: self.fast_sma.clone(),
+                },
+                Line {
+                    label: "slow SMA",
+                    color: "#1c7ed6",
+                    values: self.slow_sma.clone(),
+                },
+            ],
+            &markers,
+            |v| format!("{:.2}", v),
+        ));
+
+        html.push_str("<h2>Equity</h2>\n");
+        html.push_str(&self.chart(
+            &[
+                Line {
+                    label: "strategy",
+                    color: "#1c7ed6",
+                    values: self.equity.iter().copied().map(Some).collect(),
+                },
+                Line {
+                    label: "buy & hold",
+                    color: "#888",
+                    values: self.benchmark.iter().copied().map(Some).collect(),
+                },
+            ],
+            &[],
+            |v| format!("{:.2}", v),
+        ));
+
+        html.push_str("<h2>Drawdown</h2>\n");
+        html.push_str(&self.chart(
+            &[
+                Line {
+                    label: "strategy",
+                    color: "#c62828",
+                    values: drawdown(&self.equity),
+                },
+                Line {
+                    label: "buy & hold",
+                    color: "#888",
+                    values: drawdown(&self.benchmark),
+                },
+            ],
+            &[],
+            |v| format!("{:.1}%", v),
+        ));
+
+        let _ = writeln!(html, "<h2>Trades ({})</h2>", self.trades.len());
+        html.push_str(&self.trade_table());
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.render())
+    }
+
+    fn metrics_table(&self) -> String {
+        let rows: [MetricRow; 10] = [
+            ("Total Return", |m| percent(m.total_return)),
+            ("Annualized Return", |m| percent(m.annualized_return)),
+            ("Annualized Volatility", |m| {
+                percent(m.annualized_volatility)
+            }),
+            ("Sharpe Ratio", |m| format!("{:.2}", m.sharpe_ratio)),
+            ("Sortino Ratio", |m| format!("{:.2}", m.sortino_ratio)),
+            ("Calmar Ratio", |m| format!("{:.2}", m.calmar_ratio)),
+            ("Max Drawdown", |m| m.drawdown_text()),
+            ("Time in Market", |m| percent(m.exposure)),
+            ("Expectancy", |m| format!("{:.2}", m.expectancy)),
+            ("Max Loss Streak", |m| m.max_consecutive_losses.to_string()),
+        ];
+        let mut table = String::from("<table>\n<tr><th></th>");
+        for (label, _) in &self.metrics {
+            let _ = write!(table, "<th>{}</th>", escape(label));
+        }
+        table.push_str("</tr>\n");
+        for (name, value) in rows {
+            let _ = write!(table, "<tr><td>{}</td>", name);
+            for (_, m) in &self.metrics {
+                let _ = write!(table, "<td>{}</td>", escape(&value(m)));
+            }
+            table.push_str("</tr>\n");
+        }
+        table.push_str("</table>\n");
+        table
+    }
+
+    fn trade_table(&self) -> String {
+        let mut table = String::from(
+            "<table>\n<tr><th>#</th><th>Entry Time</th><th>Entry Price</th>\
+             <th>Exit Time</th><th>Exit Price</th><th>Return</th><th>PnL</th></tr>\n",
+        );
+        for (i, t) in self.trades.iter().enumerate() {
+            let class = if t.pnl < 0.0 { "neg" } else { "pos" };
+            let _ = writeln!(
+                table,
+                "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td>\
+                 <td class=\"{class}\">{}</td><td class=\"{class}\">{:.2}</td></tr>",
+                i + 1,
+                date(t.entry_time),
+                t.entry_price,
+                t.exit_time.map(date).unwrap_or_else(|| "open".to_string()),
+                t.exit_price
+                    .map(|p| format!("{:.2}", p))
+                    .unwrap_or_default(),
+                t.exit_price
+                    .filter(|_| t.entry_price > 0.0)
+                    .map(|p| percent(p / t.entry_price - 1.0))
+                    .unwrap_or_default(),
+                t.pnl,
+            );
+        }
+        table.push_str("</table>\n");
+        table
+    }
+
+    // line chart over `times`, lines break where values are missing
+    fn chart(&self, lines: &[Line], markers: &[Marker], label: fn(f64) -> String) -> String {
+        let (Some(first), Some(last)) = (self.times.first(), self.times.last()) else {
+            return "<p>No data</p>\n".to_string();
+        };
+        let span = (last - first).max(1) as f64;
+        let x = |time: i64| LEFT + (time - first) as f64 / span * (WIDTH - LEFT - RIGHT);
+
+        let values = lines
+            .iter()
+            .flat_map(|l| l.values.iter().flatten().copied())
+            .chain(markers.iter().map(|m| m.price));
+        let (mut low, mut high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        if !low.is_finite() {
+            return "<p>No data</p>\n".to_string();
+        }
+        let pad = ((high - low) * 0.05).max(high.abs() * 1e-6).max(1e-9);
+        low -= pad;
+        high += pad;
+        let y = |value: f64| TOP + (high - value) / (high - low) * (HEIGHT - TOP - BOTTOM);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+        );
+        for i in 0..=4 {
+            let value = low + (high - low) * i as f64 / 4.0;
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{LEFT}" x2="{}" y1="{y:.1}" y2="{y:.1}" stroke="#eee"/><text x="{}" y="{:.1}" text-anchor="end">{}</text>"##,
+                WIDTH - RIGHT,
+                LEFT - 6.0,
+                y(value) + 4.0,
+                label(value),
+                y = y(value),
+            );
+        }
+        for i in 0..=4 {
+            let time = first + ((last - first) as f64 * i as f64 / 4.0) as i64;
+            let anchor = match i {
+                0 => "start",
+                4 => "end",
+                _ => "middle",
+            };
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{}" text-anchor="{anchor}">{}</text>"#,
+                x(time),
+                HEIGHT - 10.0,
+                date(time)
+            );
+        }
+
+        let step = self.times.len().div_ceil(MAX_POINTS).max(1);
+        let sampled: Vec<usize> = (0..self.times.len())
+            .step_by(step)
+            .chain(std::iter::once(self.times.len() - 1))
+            .collect();
+        for (n, line) in lines.iter().enumerate() {
+            let mut segment: Vec<String> = Vec::new();
+            let mut segments: Vec<Vec<String>> = Vec::new();
+            for i in sampled.iter().copied() {
+                match line.values.get(i).copied().flatten() {
+                    Some(value) => segment.push(format!("{:.1},{:.1}", x(self.times[i]), y(value))),
+                    None if !segment.is_empty() => segments.push(std::mem::take(&mut segment)),
+                    None => {}
+                }
+            }
+            segments.push(segment);
+            for points in segments.iter().filter(|s| !s.is_empty()) {
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline fill="none" stroke="{}" stroke-width="1.2" points="{}"/>"#,
+                    line.color,
+                    points.join(" ")
+                );
+            }
+            let legend = LEFT + 10.0 + n as f64 * 110.0;
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{legend}" y="8" width="12" height="3" fill="{}"/><text x="{}" y="14">{}</text>"#,
+                line.color,
+                legend + 16.0,
+                escape(line.label)
+            );
+        }
+
+        for m in markers {
+            let (mx, my) = (x(m.time), y(m.price));
+            let (color, path) = if m.buy {
+                ("#1a7f37", format!("M{:.1},{:.1} l-5,9 h10 z", mx, my + 2.0))
+            } else {
+                (
+                    "#c62828",
+                    format!("M{:.1},{:.1} l-5,-9 h10 z", mx, my - 2.0),
+                )
+            };
+            let _ = writeln!(svg, r#"<path d="{path}" fill="{color}"/>"#);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// simple moving average of `period` values, None until the window is full
+pub fn sma_series(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= period {
+                sum -= values[i - period];
+            }
+            (period > 0 && i + 1 >= period).then(|| sum / period as f64)
+        })
+        .collect()
+}
+
+// percent below the running peak, 0 at new highs
+fn drawdown(equity: &[f64]) -> Vec<Option<f64>> {
+    let mut peak = f64::NEG_INFINITY;
+    equity
+        .iter()
+        .map(|value| {
+            peak = peak.max(*value);
+            (peak > 0.0).then(|| (value / peak - 1.0) * 100.0)
+        })
+        .collect()
+}
+
+fn percent(value: f64) -> String {
+    format!("{:.2}%", value * 100.0)
+}
+
+fn date(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sma_series_and_drawdown() {
+        assert_eq!(
+            sma_series(&[1.0, 2.0, 3.0, 4.0], 2),
+            vec![None, Some(1.5), Some(2.5), Some(3.5)]
+        );
+        assert_eq!(
+            drawdown(&[100.0, 120.0, 90.0, 130.0]),
+            vec![Some(0.0), Some(0.0), Some(-25.0), Some(0.0)]
+        );
+    }
+
+    #[test]
+    fn test_render_self_contained_page() {
+        let minute = 60_000;
+        let times: Vec<i64> = (0..5000).map(|i| 1_735_689_600_000 + i * minute).collect();
+        let close: Vec<f64> = (0..5000).map(|i| 100.0 + (i as f64 / 50.0).sin()).collect();
+        let report = HtmlReport {
+            title: "BTCUSDT <1m>".into(),
+            summary: vec![("Symbol".into(), "BTCUSDT".into())],
+            fast_sma: sma_series(&close, 10),
+            slow_sma: sma_series(&close, 25),
+            equity: close.iter().map(|c| c * 5.0).collect(),
+            benchmark: close.iter().map(|c| c * 5.0).collect(),
+            trades: vec![
+                ChartTrade {
+                    entry_time: times[30],
+                    entry_price: close[30],
+                    exit_time: Some(times[90]),
+                    exit_price: Some(close[90]),
+                    pnl: -1.25,
+                },
+                ChartTrade {
+                    entry_time: times[4000],
+                    entry_price: close[4000],
+                    exit_time: None,
+                    exit_price: None,
+                    pnl: 0.5,
+                },
+            ],
+            metrics: vec![("strategy".into(), PerformanceMetrics::default())],
+            times,
+            close,
+        };
+
+        let html = report.render();
+        assert!(html.contains("<title>BTCUSDT &lt;1m&gt;</title>"));
+        assert_eq!(html.matches("<svg").count(), 3);
+        assert!(!html.contains("<script"));
+        // 2 buy markers and 1 sell marker
+        assert_eq!(html.matches("l-5,9").count(), 2);
+        assert_eq!(html.matches("l-5,-9").count(), 1);
+        assert!(html.contains("<td class=\"neg\">-1.25</td>"));
+        assert!(html.contains("<td>open</td>"));
+        assert!(html.contains("<tr><td>Sharpe Ratio</td><td>0.00</td></tr>"));
+
+        // 5000 bars are thinned to at most MAX_POINTS + 1 per line
+        let close_line = html
+            .lines()
+            .find(|l| l.starts_with("<polyline") && l.contains("#888"))
+            .unwrap();
+        assert!(close_line.matches(',').count() <= MAX_POINTS + 1);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::utils::config::timeframe_duration;
+use polars::prelude::*;
+use serde::Serialize;
+
+// crypto markets trade around the clock, so a year is 365 full days of bars
+const YEAR_MS: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+// risk-adjusted performance of an equity curve sampled once per bar;
+// returns and drawdowns are fractions (0.1 = 10%), durations are in bars
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PerformanceMetrics {
+    pub total_return: f64,
+    pub annualized_return: f64, // compounded (CAGR)
+    pub annualized_volatility: f64,
+    pub sharpe_ratio: f64,            // risk-free rate 0
+    pub sortino_ratio: f64,           // only returns below 0 count as risk
+    pub calmar_ratio: f64,            // annualized return / max drawdown
+    pub max_drawdown: f64,            // positive depth below running peak
+    pub max_drawdown_bars: usize,     // from peak to trough of max drawdown
+    pub recovery_bars: Option<usize>, // from that trough back to the peak, None if not yet
+    pub exposure: f64,                // share of bars with open position
+    pub expectancy: f64,              // average pnl per trade
+    pub max_consecutive_losses: usize,
+}
+
+impl PerformanceMetrics {
+    // e.g. `2.50% over 12 bars, recovered in 30 bars`
+    pub fn drawdown_text(&self) -> String {
+        if self.max_drawdown == 0.0 {
+            return "none".to_string();
+        }
+        let recovery = match self.recovery_bars {
+            Some(bars) => format!("recovered in {} bars", bars),
+            None => "not recovered".to_string(),
+        };
+        format!(
+            "{:.2}% over {} bars, {}",
+            self.max_drawdown * 100.0,
+            self.max_drawdown_bars,
+            recovery
+        )
+    }
+}
+
+// strategy against a benchmark (buy & hold) sampled on the same bars, annualized like
+// `PerformanceMetrics`; the risk-free rate is 0
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RelativeMetrics {
+    pub alpha: f64,             // annualized return not explained by beta
+    pub beta: f64,              // sensitivity of strategy returns to benchmark returns
+    pub tracking_error: f64,    // annualized volatility of active (strategy - benchmark) returns
+    pub information_ratio: f64, // annualized active return / tracking error
+    pub up_capture: f64,        // mean strategy / benchmark return over bars the benchmark rose
+    pub down_capture: f64,      // same over bars it fell, below 1 loses less than holding
+}
+
+// number of `timeframe` bars in a year, e.g. 525600 for `1m`
+pub fn bars_per_year(timeframe: &str) -> Result<f64, String> {
+    Ok(bars_per_year_ms(
+        timeframe_duration(timeframe)?.num_milliseconds(),
+    ))
+}
+
+pub fn bars_per_year_ms(bar_ms: i64) -> f64 {
+    YEAR_MS / bar_ms as f64
+}
+
+// `equity` holds one value per bar (first one is starting balance), `in_market` tells
+// if a position was held during that bar and `trade_pnls` are results of closed trades
+pub fn performance_metrics(
+    equity: &[f64],
+    in_market: &[bool],
+    trade_pnls: &[f64],
+    bars_per_year: f64,
+) -> PerformanceMetrics {
+    let mut m = PerformanceMetrics {
+        exposure: if in_market.is_empty() {
+            0.0
+        } else {
+            in_market.iter().filter(|held| **held).count() as f64 / in_market.len() as f64
+        },
+        expectancy: mean(trade_pnls),
+        max_consecutive_losses: max_consecutive_losses(trade_pnls),
+        ..Default::default()
+    };
+    let (Some(first), Some(last)) = (equity.first(), equity.last()) else {
+        return m;
+    };
+    if *first <= 0.0 || equity.len() < 2 {
+        return m;
+    }
+
+    let returns: Vec<f64> = equity.windows(2).map(|w| w[1] / w[0] - 1.0).collect();
+    let growth = last / first;
+    m.total_return = growth - 1.0;
+    m.annualized_return = growth.powf(bars_per_year / returns.len() as f64) - 1.0;
+
+    let avg = mean(&returns);
+    let volatility = sample_std(&returns, avg);
+    let downside =
+        (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64).sqrt();
+    m.annualized_volatility = volatility * bars_per_year.sqrt();
+    m.sharpe_ratio = ratio(avg, volatility) * bars_per_year.sqrt();
+    m.sortino_ratio = ratio(avg, downside) * bars_per_year.sqrt();
+
+    let drawdown = max_drawdown(equity);
+    m.max_drawdown = drawdown.depth;
+    m.max_drawdown_bars = drawdown.bars;
+    m.recovery_bars = drawdown.recovery_bars;
+    m.calmar_ratio = ratio(m.annualized_return, m.max_drawdown);
+    m
+}
+
+// `equity` and `benchmark` hold one value per bar, longer one is cut to the shorter
+pub fn relative_metrics(equity: &[f64], benchmark: &[f64], bars_per_year: f64) -> RelativeMetrics {
+    let bars = equity.len().min(benchmark.len());
+    let (equity, benchmark) = (&equity[..bars], &benchmark[..bars]);
+    if bars < 2 || equity.iter().chain(benchmark).any(|v| *v <= 0.0) {
+        return RelativeMetrics::default();
+    }
+    let returns =
+        |curve: &[f64]| -> Vec<f64> { curve.windows(2).map(|w| w[1] / w[0] - 1.0).collect() };
+    let strategy = returns(equity);
+    let market = returns(benchmark);
+    let (strategy_avg, market_avg) = (mean(&strategy), mean(&market));
+
+    let covariance = if strategy.len() < 2 {
+        0.0
+    } else {
+        strategy
+            .iter()
+            .zip(&market)
+            .map(|(s, m)| (s - strategy_avg) * (m - market_avg))
+            .sum::<f64>()
+            / (strategy.len() - 1) as f64
+    };
+    let beta = ratio(covariance, sample_std(&market, market_avg).powi(2));
+
+    let active: Vec<f64> = strategy.iter().zip(&market).map(|(s, m)| s - m).collect();
+    let active_avg = mean(&active);
+    let tracking = sample_std(&active, active_avg);
+
+    // mean returns of both over bars where the benchmark moved in one direction
+    let capture = |up: bool| -> f64 {
+        let (s, m): (Vec<f64>, Vec<f64>) = strategy
+            .iter()
+            .zip(&market)
+            .filter(|(_, m)| if up { **m > 0.0 } else { **m < 0.0 })
+            .map(|(s, m)| (*s, *m))
+            .unzip();
+        if up {
+            ratio(mean(&s), mean(&m))
+        } else {
+            ratio(-mean(&s), -mean(&m))
+        }
+    };
+
+    RelativeMetrics {
+        alpha: (strategy_avg - beta * market_avg) * bars_per_year,
+        beta,
+        tracking_error: tracking * bars_per_year.sqrt(),
+        information_ratio: ratio(active_avg, tracking) * bars_per_year.sqrt(),
+        up_capture: capture(true),
+        down_capture: capture(false),
+    }
+}
+
+// runs of consecutive bars in market as (bar before entry, last held bar)
+pub fn trade_spans(in_market: &[bool]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut entry: Option<usize> = None;
+    for (i, held) in in_market.iter().enumerate() {
+        match (held, entry) {
+            (true, None) => entry = Some(i.saturating_sub(1)),
+            (false, Some(start)) => {
+                spans.push((start, i - 1));
+                entry = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = entry {
+        spans.push((start, in_market.len() - 1));
+    }
+    spans
+}
+
+// pnl of each run of bars in market, measured on the equity curve
+pub fn trade_pnls(equity: &[f64], in_market: &[bool]) -> Vec<f64> {
+    let held = &in_market[..in_market.len().min(equity.len())];
+    trade_spans(held)
+        .into_iter()
+        .map(|(start, end)| equity[end] - equity[start])
+        .collect()
+}
+
+// one row per labelled metrics set (e.g. benchmark and strategy) for `--output`
+pub fn metrics_frame(rows: &[(&str, &PerformanceMetrics)]) -> PolarsResult<DataFrame> {
+    let column = |f: fn(&PerformanceMetrics) -> f64| -> Vec<f64> {
+        rows.iter().map(|(_, m)| f(m)).collect()
+    };
+    df!(
+        "series" => rows.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        "total_return" => column(|m| m.total_return),
+        "annualized_return" => column(|m| m.annualized_return),
+        "annualized_volatility" => column(|m| m.annualized_volatility),
+        "sharpe_ratio" => column(|m| m.sharpe_ratio),
+        "sortino_ratio" => column(|m| m.sortino_ratio),
+        "calmar_ratio" => column(|m| m.calmar_ratio),
+        "max_drawdown" => column(|m| m.max_drawdown),
+        "max_drawdown_bars" => rows.iter().map(|(_, m)| m.max_drawdown_bars as u64).collect::<Vec<_>>(),
+        "recovery_bars" => rows.iter().map(|(_, m)| m.recovery_bars.map(|b| b as u64)).collect::<Vec<_>>(),
+        "time_in_market" => column(|m| m.exposure),
+        "expectancy" => column(|m| m.expectancy),
+        "max_consecutive_losses" => rows.iter().map(|(_, m)| m.max_consecutive_losses as u64).collect::<Vec<_>>(),
+    )
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Drawdown {
+    depth: f64,
+    bars: usize,
+    recovery_bars: Option<usize>,
+}
+
+fn max_drawdown(equity: &[f64]) -> Drawdown {
+    let mut worst = Drawdown::default();
+    let mut worst_at = (0, 0); // (peak, trough) indices
+    let mut peak = 0;
+    for (i, value) in equity.iter().enumerate() {
+        if *value > equity[peak] {
+            peak = i;
+        }
+        let depth = 1.0 - value / equity[peak];
+        if depth > worst.depth {
+            worst.depth = depth;
+            worst.bars = i - peak;
+            worst_at = (peak, i);
+        }
+    }
+    let (peak, trough) = worst_at;
+    worst.recovery_bars = equity[trough..]
+        .iter()
+        .position(|value| *value >= equity[peak])
+        .filter(|_| worst.depth > 0.0);
+    worst
+}
+
+fn max_consecutive_losses(pnls: &[f64]) -> usize {
+    pnls.iter()
+        .fold((0, 0), |(streak, longest), pnl| {
+            let streak = if *pnl < 0.0 { streak + 1 } else { 0 };
+            (streak, longest.max(streak))
+        })
+        .1
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn sample_std(values: &[f64], mean: f64) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+// ratios are 0 when there is no risk to divide by (flat curve, no drawdown)
+fn ratio(value: f64, risk: f64) -> f64 {
+    if risk > 0.0 { value / risk } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_bars_per_year() {
+        assert_eq!(bars_per_year("1m").unwrap(), 525_600.0);
+        assert_eq!(bars_per_year("1d").unwrap(), 365.0);
+        assert!(bars_per_year("2m").is_err());
+    }
+
+    #[test]
+    fn test_drawdown_duration_and_recovery() {
+        // peak 120 at bar 2, trough 90 at bar 4, back to 120 at bar 6
+        let equity = [100.0, 110.0, 120.0, 100.0, 90.0, 105.0, 125.0, 115.0];
+        let m = performance_metrics(&equity, &[true; 8], &[], 365.0);
+        assert!(close(m.max_drawdown, 0.25));
+        assert_eq!(m.max_drawdown_bars, 2);
+        assert_eq!(m.recovery_bars, Some(2));
+        assert_eq!(m.drawdown_text(), "25.00% over 2 bars, recovered in 2 bars");
+        assert!(close(m.total_return, 0.15));
+        assert!(close(m.exposure, 1.0));
+
+        // still under water at the end
+        let m = performance_metrics(&[100.0, 80.0, 90.0], &[], &[], 365.0);
+        assert!(close(m.max_drawdown, 0.2));
+        assert_eq!(m.recovery_bars, None);
+    }
+
+    #[test]
+    fn test_risk_adjusted_ratios() {
+        let equity = [100.0, 102.0, 101.0, 104.0, 103.0];
+        let returns: Vec<f64> = equity.windows(2).map(|w| w[1] / w[0] - 1.0).collect();
+        let avg = returns.iter().sum::<f64>() / 4.0;
+        let std = (returns.iter().map(|r| (r - avg).powi(2)).sum::<f64>() / 3.0).sqrt();
+        let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / 4.0).sqrt();
+
+        let m = performance_metrics(&equity, &[], &[], 365.0);
+        assert!(close(m.annualized_volatility, std * 365f64.sqrt()));
+        assert!(close(m.sharpe_ratio, avg / std * 365f64.sqrt()));
+        assert!(close(m.sortino_ratio, avg / downside * 365f64.sqrt()));
+        assert!(close(m.annualized_return, 1.03f64.powf(365.0 / 4.0) - 1.0));
+        assert!(close(
+            m.calmar_ratio,
+            m.annualized_return / (1.0 - 101.0 / 102.0)
+        ));
+
+        // flat curve has no risk, ratios stay finite
+        let flat = performance_metrics(&[100.0; 3], &[], &[], 365.0);
+        assert_eq!(flat.sharpe_ratio, 0.0);
+        assert_eq!(flat.calmar_ratio, 0.0);
+        assert_eq!(flat.drawdown_text(), "none");
+    }
+
+    #[test]
+    fn test_relative_metrics() {
+        // strategy moves twice as much as the benchmark
+        let benchmark = [100.0, 101.0, 99.0, 102.0, 100.0];
+        let returns: Vec<f64> = benchmark.windows(2).map(|w| w[1] / w[0] - 1.0).collect();
+        let equity: Vec<f64> = std::iter::once(100.0)
+            .chain(returns.iter().scan(100.0, |e, r| {
+                *e *= 1.0 + 2.0 * r;
+                Some(*e)
+            }))
+            .collect();
+
+        let m = relative_metrics(&equity, &benchmark, 365.0);
+        assert!(close(m.beta, 2.0));
+        assert!(close(m.alpha, 0.0));
+        assert!(close(m.up_capture, 2.0));
+        assert!(close(m.down_capture, 2.0));
+        let std = sample_std(&returns, mean(&returns));
+        assert!(close(m.tracking_error, std * 365f64.sqrt()));
+        assert!(close(
+            m.information_ratio,
+            mean(&returns) / std * 365f64.sqrt()
+        ));
+
+        // holding the benchmark itself
+        let m = relative_metrics(&benchmark, &benchmark, 365.0);
+        assert!(close(m.beta, 1.0));
+        assert_eq!(m.tracking_error, 0.0);
+        assert_eq!(m.information_ratio, 0.0);
+        assert_eq!(
+            relative_metrics(&[100.0], &[100.0], 365.0),
+            RelativeMetrics::default()
+        );
+    }
+
+    #[test]
+    fn test_trade_stats() {
+        let equity = [
+            100.0, 101.0, 105.0, 105.0, 103.0, 101.0, 101.0, 100.0, 104.0,
+        ];
+        let held = [false, true, true, false, true, true, false, true, true];
+        let pnls = trade_pnls(&equity, &held);
+        assert_eq!(pnls, vec![5.0, -4.0, 3.0]);
+        assert_eq!(trade_spans(&held), vec![(0, 2), (3, 5), (6, 8)]);
+
+        let m = performance_metrics(&equity, &held, &pnls, 365.0);
+        assert!(close(m.expectancy, 4.0 / 3.0));
+        assert_eq!(m.max_consecutive_losses, 1);
+        assert_eq!(max_consecutive_losses(&[-1.0, -2.0, 3.0, -1.0]), 2);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod clock;
 pub mod config;
 pub mod data_io;
+pub mod data_quality;
+pub mod export;
+pub mod html_report;
+pub mod metrics;
 pub mod objects;
@@ -1,26 +1,32 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct Trade {
     pub id: i64,
     #[allow(dead_code)]
     pub symbol: String,
     #[allow(dead_code)]
-    pub entry_price: f64,
+    pub entry_price: Decimal,
     #[allow(dead_code)]
-    pub exit_price: Option<f64>,
+    pub exit_price: Option<Decimal>,
     #[allow(dead_code)]
-    pub trade_size: f64,
+    pub trade_size: Decimal,
     #[allow(dead_code)]
-    pub position_size: f64,
+    pub position_size: Decimal,
     #[allow(dead_code)]
-    pub pnl: Option<f64>,
+    pub pnl: Option<Decimal>,
     #[allow(dead_code)]
     pub entry_time: DateTime<Utc>,
     #[allow(dead_code)]
     pub exit_time: Option<DateTime<Utc>>,
     #[allow(dead_code)]
     pub status: String, // `OPEN` or `CLOSED`
+    // max adverse / favourable excursion while the trade was open, quote amount for the
+    // whole trade size from candle lows / highs. none until the trade is closed and measured
+    pub mae: Option<Decimal>,
+    pub mfe: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -41,6 +47,28 @@ pub struct CandleStick {
     pub timestamp: i64,
 }
 
+// one run of the trading loop
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct Session {
+    pub id: i64,
+    pub symbol: String,
+    pub mode: String, // `paper` or `live`
+    pub initial_balance: Decimal,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>, // None while running or if the bot crashed
+}
+
+// mark-to-market state of the account at the close of a bar
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct EquitySnapshot {
+    pub time: DateTime<Utc>,
+    pub balance: Decimal,        // initial balance + realized pnl
+    pub unrealized_pnl: Decimal, // open positions marked to last close
+    pub equity: Decimal,         // balance + unrealized pnl
+    pub exposure: Decimal,       // market value of open positions
+    pub price: Option<Decimal>,  // mark price (last close), None if not known
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TradeAction {
     EnterLong,